@@ -721,7 +721,7 @@ impl BenchmarkRunner {
             File::open(
                 self.metadata
                     .workspace_root
-                    .join("iai-callgrind-runner/schemas/summary.v3.schema.json"),
+                    .join("iai-callgrind-runner/schemas/summary.v4.schema.json"),
             )
             .unwrap(),
         )
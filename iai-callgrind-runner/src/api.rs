@@ -41,7 +41,9 @@ pub struct BinaryBenchmarkConfig {
     pub current_dir: Option<PathBuf>,
     pub entry_point: Option<String>,
     pub exit_with: Option<ExitWith>,
+    pub default_tool: Option<ValgrindTool>,
     pub callgrind_args: RawArgs,
+    pub cachegrind_args: RawArgs,
     pub valgrind_args: RawArgs,
     pub envs: Vec<(OsString, Option<OsString>)>,
     pub flamegraph_config: Option<FlamegraphConfig>,
@@ -164,11 +166,11 @@ pub enum EntryPoint {
     Custom(String),
 }
 
-// The error metrics from a tool which reports errors
-//
-// The tools which report only errors are `helgrind`, `drd` and `memcheck`. The order in which the
-// variants are defined in this enum determines the order of the metrics in the benchmark terminal
-// output.
+/// The error metrics from a tool which reports errors
+///
+/// The tools which report only errors are `helgrind`, `drd` and `memcheck`. The order in which the
+/// variants are defined in this enum determines the order of the metrics in the benchmark terminal
+/// output.
 #[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub enum ErrorMetricKind {
@@ -308,7 +310,9 @@ pub struct LibraryBenchmarkBench {
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct LibraryBenchmarkConfig {
     pub env_clear: Option<bool>,
+    pub default_tool: Option<ValgrindTool>,
     pub callgrind_args: RawArgs,
+    pub cachegrind_args: RawArgs,
     pub valgrind_args: RawArgs,
     pub envs: Vec<(OsString, Option<OsString>)>,
     pub flamegraph_config: Option<FlamegraphConfig>,
@@ -317,6 +321,7 @@ pub struct LibraryBenchmarkConfig {
     pub tools_override: Option<Tools>,
     pub entry_point: Option<EntryPoint>,
     pub output_format: Option<OutputFormat>,
+    pub sweep: Option<bool>,
 }
 
 /// The model for the `library_benchmark_group` macro
@@ -341,12 +346,32 @@ pub struct LibraryBenchmarkGroups {
     pub has_teardown: bool,
 }
 
+/// The metrics extracted from the output file of Massif
+///
+/// The order in which the variants are defined in this enum determines the order of the metrics in
+/// the benchmark terminal output.
+#[derive(Debug, Clone, Copy, Hash, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub enum MassifMetricKind {
+    /// The peak of the useful heap bytes allocated by the program over all snapshots
+    PeakHeapBytes,
+    /// The peak of the extra heap bytes allocated in excess of what the program asked for (e.g.
+    /// administrative bytes and alignment) over all snapshots
+    PeakExtraHeapBytes,
+    /// The peak of the stack size over all snapshots (only non-zero with `--stacks=yes`)
+    PeakStacksBytes,
+    /// The total amount of snapshots taken
+    Snapshots,
+}
+
 /// The configuration values for the output format
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct OutputFormat {
     pub truncate_description: Option<Option<usize>>,
     pub show_intermediate: Option<bool>,
     pub show_grid: Option<bool>,
+    pub show_allocation_sites: Option<usize>,
+    pub show_functions: Option<(usize, EventKind)>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
@@ -355,6 +380,11 @@ pub struct RawArgs(pub Vec<String>);
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 pub struct RegressionConfig {
     pub limits: Vec<(EventKind, f64)>,
+    pub hard_limits: Vec<(EventKind, u64)>,
+    pub absolute_limits: Vec<(EventKind, i64)>,
+    pub dhat_limits: Vec<(DhatMetricKind, f64)>,
+    pub error_limits: Vec<(ErrorMetricKind, u64)>,
+    pub fail_on_error_increase: Option<bool>,
     pub fail_fast: Option<bool>,
 }
 
@@ -430,14 +460,23 @@ pub struct Tool {
     pub enable: Option<bool>,
     pub raw_args: RawArgs,
     pub show_log: Option<bool>,
+    pub gen_suppressions: Option<bool>,
+    pub xml: Option<bool>,
 }
 
 #[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Tools(pub Vec<Tool>);
 
-/// The valgrind tools which can be run in addition to callgrind
+/// The valgrind tools which can be run
+///
+/// [`ValgrindTool::Callgrind`] and [`ValgrindTool::Cachegrind`] can be the default tool of a
+/// benchmark, all tools can be run in addition to the default tool.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
 pub enum ValgrindTool {
+    /// [Callgrind: a call-graph generating cache and branch prediction profiler](https://valgrind.org/docs/manual/cl-manual.html)
+    Callgrind,
+    /// [Cachegrind: a high-precision tracing profiler](https://valgrind.org/docs/manual/cg-manual.html)
+    Cachegrind,
     /// [Memcheck: a memory error detector](https://valgrind.org/docs/manual/mc-manual.html)
     Memcheck,
     /// [Helgrind: a thread error detector](https://valgrind.org/docs/manual/hg-manual.html)
@@ -462,9 +501,12 @@ impl BinaryBenchmarkConfig {
             self.current_dir = update_option(&self.current_dir, &other.current_dir);
             self.entry_point = update_option(&self.entry_point, &other.entry_point);
             self.exit_with = update_option(&self.exit_with, &other.exit_with);
+            self.default_tool = update_option(&self.default_tool, &other.default_tool);
 
             self.callgrind_args
                 .extend_ignore_flag(other.callgrind_args.0.iter());
+            self.cachegrind_args
+                .extend_ignore_flag(other.cachegrind_args.0.iter());
 
             self.valgrind_args
                 .extend_ignore_flag(other.valgrind_args.0.iter());
@@ -540,6 +582,25 @@ impl Display for DhatMetricKind {
 #[cfg(feature = "runner")]
 impl Summarize for DhatMetricKind {}
 
+impl DhatMetricKind {
+    pub fn from_str_ignore_case(value: &str) -> Option<Self> {
+        match value.to_lowercase().as_str() {
+            "totalbytes" => Some(Self::TotalBytes),
+            "totalblocks" => Some(Self::TotalBlocks),
+            "attgmaxbytes" => Some(Self::AtTGmaxBytes),
+            "attgmaxblocks" => Some(Self::AtTGmaxBlocks),
+            "attendbytes" => Some(Self::AtTEndBytes),
+            "attendblocks" => Some(Self::AtTEndBlocks),
+            "readsbytes" => Some(Self::ReadsBytes),
+            "writesbytes" => Some(Self::WritesBytes),
+            "totallifetimes" => Some(Self::TotalLifetimes),
+            "maximumbytes" => Some(Self::MaximumBytes),
+            "maximumblocks" => Some(Self::MaximumBlocks),
+            _ => None,
+        }
+    }
+}
+
 impl<T> From<T> for EntryPoint
 where
     T: Into<String>,
@@ -687,9 +748,12 @@ impl LibraryBenchmarkConfig {
     {
         for other in others.into_iter().flatten() {
             self.env_clear = update_option(&self.env_clear, &other.env_clear);
+            self.default_tool = update_option(&self.default_tool, &other.default_tool);
 
             self.callgrind_args
                 .extend_ignore_flag(other.callgrind_args.0.iter());
+            self.cachegrind_args
+                .extend_ignore_flag(other.cachegrind_args.0.iter());
             self.valgrind_args
                 .extend_ignore_flag(other.valgrind_args.0.iter());
 
@@ -708,6 +772,7 @@ impl LibraryBenchmarkConfig {
 
             self.entry_point = update_option(&self.entry_point, &other.entry_point);
             self.output_format = update_option(&self.output_format, &other.output_format);
+            self.sweep = update_option(&self.sweep, &other.sweep);
         }
         self
     }
@@ -730,6 +795,20 @@ impl LibraryBenchmarkConfig {
     }
 }
 
+impl Display for MassifMetricKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            MassifMetricKind::PeakHeapBytes => f.write_str("Peak heap bytes"),
+            MassifMetricKind::PeakExtraHeapBytes => f.write_str("Peak extra bytes"),
+            MassifMetricKind::PeakStacksBytes => f.write_str("Peak stacks bytes"),
+            MassifMetricKind::Snapshots => f.write_str("Snapshots"),
+        }
+    }
+}
+
+#[cfg(feature = "runner")]
+impl Summarize for MassifMetricKind {}
+
 impl RawArgs {
     pub fn new(args: Vec<String>) -> Self {
         Self(args)
@@ -980,7 +1059,9 @@ mod tests {
         let base = LibraryBenchmarkConfig::default();
         let other = LibraryBenchmarkConfig {
             env_clear: Some(true),
+            default_tool: Some(ValgrindTool::Cachegrind),
             callgrind_args: RawArgs(vec!["--just-testing=yes".to_owned()]),
+            cachegrind_args: RawArgs(vec!["--cache-sim=yes".to_owned()]),
             valgrind_args: RawArgs(vec!["--valgrind-arg=yes".to_owned()]),
             envs: vec![(OsString::from("MY_ENV"), Some(OsString::from("value")))],
             flamegraph_config: Some(FlamegraphConfig::default()),
@@ -990,10 +1071,13 @@ mod tests {
                 enable: None,
                 raw_args: RawArgs(vec![]),
                 show_log: None,
+                gen_suppressions: None,
+                xml: None,
             }]),
             tools_override: None,
             entry_point: None,
             output_format: None,
+            sweep: None,
         };
 
         assert_eq!(base.update_from_all([Some(&other.clone())]), other);
@@ -1004,7 +1088,9 @@ mod tests {
         let base = LibraryBenchmarkConfig::default();
         let other = LibraryBenchmarkConfig {
             env_clear: Some(true),
+            default_tool: Some(ValgrindTool::Cachegrind),
             callgrind_args: RawArgs(vec!["--just-testing=yes".to_owned()]),
+            cachegrind_args: RawArgs(vec!["--cache-sim=yes".to_owned()]),
             valgrind_args: RawArgs(vec!["--valgrind-arg=yes".to_owned()]),
             envs: vec![(OsString::from("MY_ENV"), Some(OsString::from("value")))],
             flamegraph_config: Some(FlamegraphConfig::default()),
@@ -1014,10 +1100,13 @@ mod tests {
                 enable: None,
                 raw_args: RawArgs(vec![]),
                 show_log: None,
+                gen_suppressions: None,
+                xml: None,
             }]),
             tools_override: Some(Tools(vec![])),
             entry_point: Some(EntryPoint::default()),
             output_format: Some(OutputFormat::default()),
+            sweep: Some(true),
         };
         let expected = LibraryBenchmarkConfig {
             tools: other.tools_override.as_ref().unwrap().clone(),
@@ -3,7 +3,7 @@ use std::io::Write;
 
 use colored::{control, Colorize};
 use env_logger::Env;
-use iai_callgrind_runner::runner::envs;
+use iai_callgrind_runner::runner::{envs, output};
 use log::{error, warn};
 
 /// Print warnings for deprecated usages of environment variables
@@ -48,9 +48,11 @@ fn main() {
                     .map_or_else(|| envs::CARGO_TERM_COLOR, |_| envs::IAI_CALLGRIND_COLOR),
             ),
     )
-    .format(|buf, record| {
+    // The log messages are written to the `stderr` of the `output` module instead of the `buf`, so
+    // they are captured together with the other output of benchmarks which are run in parallel
+    .format(|_, record| {
         writeln!(
-            buf,
+            output::stderr(),
             "{}: {:<5}: {}",
             record
                 .module_path()
@@ -0,0 +1,408 @@
+//! Export and import a named baseline of a benchmark target as portable archive
+//!
+//! The output files of a baseline are scattered over the directories of the benchmarks of a
+//! benchmark target. The [`BaselineArchive`] is a single self-describing json file with the gzip
+//! compressed content of all output and log files of a baseline, which can be shared for example
+//! with CI workers and imported into the home directory of another checkout.
+
+use std::fs::File;
+use std::io::{BufReader, BufWriter, Write};
+use std::path::{Component, Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use log::{debug, warn};
+use serde::{Deserialize, Serialize};
+
+use super::common::Config;
+use super::output::stdout;
+use super::summary::{valgrind_version, BaselineName};
+
+/// The current version of the archive format
+pub const ARCHIVE_VERSION: &str = "2";
+
+/// A named baseline of a benchmark target with all its output and log files
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct BaselineArchive {
+    /// The version of the archive format
+    pub version: String,
+    /// The name of the baseline
+    pub baseline: BaselineName,
+    /// The name of the package
+    pub package: String,
+    /// The name of the benchmark target as in `cargo bench --bench NAME`
+    pub bench_name: String,
+    /// The module of the benchmark target, which is also the directory of its output files
+    pub module: String,
+    /// The architecture of the machine on which the baseline was created
+    pub arch: String,
+    /// The valgrind version with which the baseline was created
+    pub valgrind_version: Option<String>,
+    /// The version of the `iai-callgrind-runner` which created this archive
+    pub runner_version: String,
+    /// The output and log files of the baseline
+    pub files: Vec<ArchivedFile>,
+}
+
+/// A file in the [`BaselineArchive`]
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ArchivedFile {
+    /// The path relative to the directory of the benchmark target
+    pub path: PathBuf,
+    /// The raw bytes of the file, gzip compressed and base64 encoded in the json
+    #[serde(with = "crate::serde::gzip_base64")]
+    pub content: Vec<u8>,
+}
+
+impl BaselineArchive {
+    /// Create a new `BaselineArchive` of the `baseline` of the benchmark target
+    pub fn new(config: &Config, baseline: &BaselineName) -> Result<Self> {
+        let dir = bench_dir(config);
+        let mut files = vec![];
+        collect_files(&dir, &dir, &format!(".base@{baseline}"), &mut files)?;
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        Ok(Self {
+            version: ARCHIVE_VERSION.to_owned(),
+            baseline: baseline.clone(),
+            package: config.meta.package_name.clone(),
+            bench_name: config.meta.bench_name.clone(),
+            module: config.module_path.to_string(),
+            arch: config.meta.arch.clone(),
+            valgrind_version: valgrind_version(&config.meta.valgrind.bin),
+            runner_version: env!("CARGO_PKG_VERSION").to_owned(),
+            files,
+        })
+    }
+
+    /// Return the default path of the archive of the `baseline` of the benchmark target
+    ///
+    /// The archive is stored in the directory of the benchmark target, for example
+    /// `target/iai/my_package/my_bench/main.baseline.json`.
+    pub fn path(config: &Config, baseline: &BaselineName) -> PathBuf {
+        bench_dir(config).join(format!("{baseline}.baseline.json"))
+    }
+
+    /// Load a `BaselineArchive` from the file at `path`
+    pub fn load(path: &Path) -> Result<Self> {
+        let file = File::open(path)
+            .with_context(|| format!("Failed to open baseline archive '{}'", path.display()))?;
+        let archive: Self = serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("Failed to read baseline archive '{}'", path.display()))?;
+
+        if archive.version != ARCHIVE_VERSION {
+            return Err(anyhow!(
+                "Unsupported version '{}' of baseline archive '{}': Expected version '{}'",
+                archive.version,
+                path.display(),
+                ARCHIVE_VERSION
+            ));
+        }
+
+        Ok(archive)
+    }
+
+    /// Save this `BaselineArchive` to the file at `path`
+    pub fn save(&self, path: &Path) -> Result<()> {
+        let file = File::create(path)
+            .with_context(|| format!("Failed to create baseline archive '{}'", path.display()))?;
+        let mut writer = BufWriter::new(file);
+        serde_json::to_writer(&mut writer, self)
+            .map_err(Into::into)
+            .and_then(|()| writer.flush())
+            .with_context(|| format!("Failed to write baseline archive '{}'", path.display()))
+    }
+
+    /// Return a warning for each mismatch of the architecture and valgrind version
+    ///
+    /// The metrics of a baseline created on another architecture or with another valgrind version
+    /// are usually not comparable to the metrics of the current machine.
+    pub fn mismatches(&self, arch: &str, valgrind_version: Option<&str>) -> Vec<String> {
+        let mut mismatches = vec![];
+        if self.arch != arch {
+            mismatches.push(format!(
+                "The baseline '{}' was created on the architecture '{}' but the current \
+                 architecture is '{arch}'",
+                self.baseline, self.arch
+            ));
+        }
+        if self.valgrind_version.as_deref() != valgrind_version {
+            mismatches.push(format!(
+                "The baseline '{}' was created with valgrind version '{}' but the current version \
+                 is '{}'",
+                self.baseline,
+                self.valgrind_version.as_deref().unwrap_or("unknown"),
+                valgrind_version.unwrap_or("unknown")
+            ));
+        }
+        mismatches
+    }
+
+    /// Write all files of this archive into the directory `dir` of the benchmark target
+    ///
+    /// Existing files are overwritten. Paths which would escape the `dir` are rejected.
+    pub fn extract(&self, dir: &Path) -> Result<()> {
+        for file in &self.files {
+            if !file
+                .path
+                .components()
+                .all(|component| matches!(component, Component::Normal(_)))
+            {
+                return Err(anyhow!(
+                    "Invalid path '{}' in baseline archive",
+                    file.path.display()
+                ));
+            }
+
+            let path = dir.join(&file.path);
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent).with_context(|| {
+                    format!(
+                        "Failed to create benchmark directory '{}'",
+                        parent.display()
+                    )
+                })?;
+            }
+            std::fs::write(&path, &file.content)
+                .with_context(|| format!("Failed to write file '{}'", path.display()))?;
+        }
+
+        Ok(())
+    }
+}
+
+/// Return the directory of the benchmark target, for example `target/iai/my_package/my_bench`
+fn bench_dir(config: &Config) -> PathBuf {
+    config.meta.target_dir.join(config.module_path.to_string())
+}
+
+/// Recursively collect all files in `dir` with a file name ending with `suffix`
+fn collect_files(
+    root: &Path,
+    dir: &Path,
+    suffix: &str,
+    files: &mut Vec<ArchivedFile>,
+) -> Result<()> {
+    if !dir.exists() {
+        return Ok(());
+    }
+
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory '{}'", dir.display()))?;
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(root, &path, suffix, files)?;
+            continue;
+        }
+
+        if entry.file_name().to_string_lossy().ends_with(suffix) {
+            let content = std::fs::read(&path)
+                .with_context(|| format!("Failed to read file '{}'", path.display()))?;
+            files.push(ArchivedFile {
+                path: path
+                    .strip_prefix(root)
+                    .expect("The path should be inside the root directory")
+                    .to_owned(),
+                content,
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Export the `baseline` of the benchmark target into a [`BaselineArchive`]
+pub fn export(config: &Config, baseline: &BaselineName) -> Result<()> {
+    let archive = BaselineArchive::new(config, baseline)?;
+    if archive.files.is_empty() {
+        warn!(
+            "No files of baseline '{baseline}' found for benchmark target '{}'",
+            config.meta.bench_name
+        );
+        return Ok(());
+    }
+
+    let path = BaselineArchive::path(config, baseline);
+    archive.save(&path)?;
+    writeln!(
+        stdout(),
+        "Exported {} files of baseline '{baseline}' of '{}' to '{}'",
+        archive.files.len(),
+        config.meta.bench_name,
+        path.display()
+    )?;
+
+    Ok(())
+}
+
+/// Import the [`BaselineArchive`] at `path` into the directory of the benchmark target
+///
+/// Archives of other packages or benchmark targets are ignored, so the same archive can be passed
+/// to all benchmark targets of a `cargo bench` run.
+pub fn import(config: &Config, path: &Path) -> Result<()> {
+    let archive = BaselineArchive::load(path)?;
+    if archive.package != config.meta.package_name || archive.bench_name != config.meta.bench_name {
+        debug!(
+            "Skipping import of baseline archive '{}' of '{}::{}' into '{}::{}'",
+            path.display(),
+            archive.package,
+            archive.bench_name,
+            config.meta.package_name,
+            config.meta.bench_name
+        );
+        return Ok(());
+    }
+
+    for mismatch in archive.mismatches(
+        &config.meta.arch,
+        valgrind_version(&config.meta.valgrind.bin).as_deref(),
+    ) {
+        warn!("{mismatch}");
+    }
+
+    archive.extract(&bench_dir(config))?;
+    writeln!(
+        stdout(),
+        "Imported {} files of baseline '{}' of '{}' from '{}'",
+        archive.files.len(),
+        archive.baseline,
+        config.meta.bench_name,
+        path.display()
+    )?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn archive_fixture(files: Vec<ArchivedFile>) -> BaselineArchive {
+        BaselineArchive {
+            version: ARCHIVE_VERSION.to_owned(),
+            baseline: "main".parse().unwrap(),
+            package: "my_package".to_owned(),
+            bench_name: "my_bench".to_owned(),
+            module: "my_bench".to_owned(),
+            arch: "x86_64".to_owned(),
+            valgrind_version: Some("valgrind-3.23.0".to_owned()),
+            runner_version: "0.14.0".to_owned(),
+            files,
+        }
+    }
+
+    #[test]
+    fn test_collect_files() {
+        let root = tempfile::tempdir().unwrap();
+        let dir = root.path().join("my_group").join("bench_fib.short");
+        std::fs::create_dir_all(&dir).unwrap();
+        for name in [
+            "callgrind.bench_fib.short.out",
+            "callgrind.bench_fib.short.log.base@main",
+            "callgrind.bench_fib.short.out.base@main_too",
+            "dhat.bench_fib.short.out.base@main",
+            "summary.json",
+        ] {
+            std::fs::write(dir.join(name), name).unwrap();
+        }
+        // The output files of valgrind are not necessarily valid UTF-8
+        std::fs::write(
+            dir.join("callgrind.bench_fib.short.out.base@main"),
+            b"fn=\xff\xfe\n",
+        )
+        .unwrap();
+
+        let mut actual = vec![];
+        collect_files(root.path(), root.path(), ".base@main", &mut actual).unwrap();
+        actual.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let expected = [
+            (
+                "callgrind.bench_fib.short.log.base@main",
+                b"callgrind.bench_fib.short.log.base@main".to_vec(),
+            ),
+            (
+                "callgrind.bench_fib.short.out.base@main",
+                b"fn=\xff\xfe\n".to_vec(),
+            ),
+            (
+                "dhat.bench_fib.short.out.base@main",
+                b"dhat.bench_fib.short.out.base@main".to_vec(),
+            ),
+        ]
+        .into_iter()
+        .map(|(name, content)| ArchivedFile {
+            path: PathBuf::from("my_group/bench_fib.short").join(name),
+            content,
+        })
+        .collect::<Vec<_>>();
+
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case::utf8(b"events: Ir\n")]
+    #[case::not_utf8(b"fn=\xff\xfe\x00\n")]
+    fn test_save_load_and_extract(#[case] content: &[u8]) {
+        let tmp = tempfile::tempdir().unwrap();
+        let archive = archive_fixture(vec![ArchivedFile {
+            path: PathBuf::from("my_group/bench/callgrind.bench.out.base@main"),
+            content: content.to_vec(),
+        }]);
+
+        let path = tmp.path().join("main.baseline.json");
+        archive.save(&path).unwrap();
+        let loaded = BaselineArchive::load(&path).unwrap();
+        assert_eq!(loaded, archive);
+
+        let dir = tmp.path().join("other");
+        loaded.extract(&dir).unwrap();
+        assert_eq!(
+            std::fs::read(dir.join("my_group/bench/callgrind.bench.out.base@main")).unwrap(),
+            content
+        );
+    }
+
+    #[test]
+    fn test_load_when_unsupported_version_then_error() {
+        let tmp = tempfile::tempdir().unwrap();
+        let mut archive = archive_fixture(vec![]);
+        archive.version = "1".to_owned();
+
+        let path = tmp.path().join("main.baseline.json");
+        archive.save(&path).unwrap();
+        assert!(BaselineArchive::load(&path).is_err());
+    }
+
+    #[rstest]
+    #[case::absolute("/etc/passwd")]
+    #[case::parent("../../callgrind.bench.out.base@main")]
+    #[case::current("./callgrind.bench.out.base@main")]
+    fn test_extract_when_invalid_path_then_error(#[case] path: &str) {
+        let tmp = tempfile::tempdir().unwrap();
+        let archive = archive_fixture(vec![ArchivedFile {
+            path: PathBuf::from(path),
+            content: vec![],
+        }]);
+
+        assert!(archive.extract(tmp.path()).is_err());
+    }
+
+    #[rstest]
+    #[case::no_mismatch("x86_64", Some("valgrind-3.23.0"), 0)]
+    #[case::arch("aarch64", Some("valgrind-3.23.0"), 1)]
+    #[case::valgrind_version("x86_64", Some("valgrind-3.22.0"), 1)]
+    #[case::unknown_valgrind_version("x86_64", None, 1)]
+    #[case::both("aarch64", Some("valgrind-3.22.0"), 2)]
+    fn test_mismatches(
+        #[case] arch: &str,
+        #[case] valgrind_version: Option<&str>,
+        #[case] expected: usize,
+    ) {
+        let archive = archive_fixture(vec![]);
+        assert_eq!(archive.mismatches(arch, valgrind_version).len(), expected);
+    }
+}
@@ -1,31 +1,82 @@
+use std::num::NonZeroUsize;
 use std::path::PathBuf;
 use std::process::{Command, Stdio};
 use std::str::FromStr;
+use std::time::Duration;
 
+use anyhow::Result;
 use clap::builder::BoolishValueParser;
 use clap::{ArgAction, Parser};
+use log::warn;
+use regex::Regex;
 
+use super::clean::CleanMode;
+use super::common::ModulePath;
 use super::format::OutputFormatKind;
+use super::list::ListFormat;
+use super::report::ReportFormat;
 use super::summary::{BaselineName, SummaryFormat};
-use crate::api::{EventKind, RawArgs, RegressionConfig};
+use super::tool::ValgrindTool;
+use crate::api::{DhatMetricKind, EventKind, RawArgs, RegressionConfig};
+use crate::error::Error;
 
 /// A filter for benchmarks
 ///
-/// # Developer Notes
-///
-/// This enum is used instead of a plain `String` for possible future usages to filter by benchmark
-/// ids, group name, file name etc.
+/// Without a prefix, the [`Pattern`] is matched against the name of the benchmark file and the full
+/// name of the benchmark `<module>::<group>::<function>[.<id>]`. With one of the prefixes `group=`,
+/// `function=` or `id=`, the pattern is only matched against the group id, the name of the
+/// benchmark function or the bench id.
 #[derive(Debug, Clone)]
 pub enum BenchmarkFilter {
     /// The name of the benchmark
-    Name(String),
+    Name(Pattern),
+    /// The id of the benchmark group
+    Group(Pattern),
+    /// The name of the benchmark function
+    Function(Pattern),
+    /// The id of the benchmark (`#[bench::id]`, `#[benches::id]`)
+    Id(Pattern),
+}
+
+/// The pattern of a [`BenchmarkFilter`]
+///
+/// The pattern is a regular expression which has to match anywhere in the haystack or, if `exact`
+/// is true, a string which has to be equal to the haystack.
+#[derive(Debug, Clone)]
+pub struct Pattern {
+    raw: String,
+    regex: Regex,
+}
+
+/// The names and ids of a single benchmark a [`BenchmarkFilter`] is matched against
+#[derive(Debug, Clone, Copy)]
+pub struct BenchmarkIdentifiers<'a> {
+    /// The name of the benchmark file
+    pub bench_name: &'a str,
+    /// The full name `<module>::<group>::<function>[.<id>]`
+    pub full_name: &'a str,
+    /// The id of the benchmark group
+    pub group: &'a str,
+    /// The name of the benchmark function
+    pub function: &'a str,
+    /// The id of the benchmark if present
+    pub id: Option<&'a str>,
 }
 
 impl BenchmarkFilter {
-    /// Return true if the haystack contains the filter
-    pub fn apply(&self, haystack: &str) -> bool {
-        let Self::Name(name) = self;
-        haystack.contains(name)
+    /// Return true if the filter matches the `identifiers` of a benchmark
+    pub fn apply(&self, identifiers: &BenchmarkIdentifiers, exact: bool) -> bool {
+        match self {
+            Self::Name(pattern) => {
+                pattern.is_match(identifiers.bench_name, exact)
+                    || pattern.is_match(identifiers.full_name, exact)
+            }
+            Self::Group(pattern) => pattern.is_match(identifiers.group, exact),
+            Self::Function(pattern) => pattern.is_match(identifiers.function, exact),
+            Self::Id(pattern) => identifiers
+                .id
+                .map_or(false, |id| pattern.is_match(id, exact)),
+        }
     }
 }
 
@@ -33,7 +84,46 @@ impl FromStr for BenchmarkFilter {
     type Err = String;
 
     fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Ok(BenchmarkFilter::Name(s.to_owned()))
+        let filter = match s.split_once('=') {
+            Some(("group", pattern)) => BenchmarkFilter::Group(pattern.parse()?),
+            Some(("function", pattern)) => BenchmarkFilter::Function(pattern.parse()?),
+            Some(("id", pattern)) => BenchmarkFilter::Id(pattern.parse()?),
+            _ => BenchmarkFilter::Name(s.parse()?),
+        };
+        Ok(filter)
+    }
+}
+
+impl Pattern {
+    /// Return true if the pattern matches the `haystack`
+    pub fn is_match(&self, haystack: &str, exact: bool) -> bool {
+        if exact {
+            self.raw == haystack
+        } else {
+            self.regex.is_match(haystack)
+        }
+    }
+}
+
+impl FromStr for Pattern {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let regex = Regex::new(s).map_err(|error| format!("Invalid regex '{s}': {error}"))?;
+        Ok(Self {
+            raw: s.to_owned(),
+            regex,
+        })
+    }
+}
+
+impl BenchmarkIdentifiers<'_> {
+    /// Return the full name `<module>::<group>::<function>[.<id>]` of a benchmark
+    pub fn full_name(module_path: &ModulePath, id: Option<&str>) -> String {
+        match id {
+            Some(id) => format!("{module_path}.{id}"),
+            None => module_path.to_string(),
+        }
     }
 }
 
@@ -59,12 +149,45 @@ impl NoCapture {
     }
 }
 
+/// What to do if the baseline given with `--baseline` is missing for a benchmark
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum MissingBaseline {
+    /// Fail before running the benchmark
+    Fail,
+    /// Print a warning and run the benchmark without comparison
+    Warn,
+    /// Run the benchmark without comparison
+    #[default]
+    Ignore,
+}
+
+impl MissingBaseline {
+    /// Fail or warn depending on this `MissingBaseline` if the `baseline` of the benchmark at
+    /// `module_path` is missing
+    pub fn check(
+        self,
+        baseline: &BaselineName,
+        tool: ValgrindTool,
+        module_path: &ModulePath,
+    ) -> Result<()> {
+        let message = format!("The baseline '{baseline}' is missing");
+        match self {
+            MissingBaseline::Fail => {
+                return Err(Error::BenchmarkError(tool, module_path.clone(), message).into());
+            }
+            MissingBaseline::Warn => warn!("{module_path}: {message}"),
+            MissingBaseline::Ignore => {}
+        }
+        Ok(())
+    }
+}
+
 /// The command line arguments the user provided after `--` when running cargo bench
 ///
 /// These arguments are not the command line arguments passed to `iai-callgrind-runner`. We collect
 /// the command line arguments in the `iai-callgrind::main!` macro without the binary as first
 /// argument, that's why `no_binary_name` is set to `true`.
-#[allow(clippy::partial_pub_fields)]
+#[allow(clippy::partial_pub_fields, clippy::struct_excessive_bools)]
 #[derive(Parser, Debug, Clone)]
 #[command(
     author,
@@ -85,12 +208,96 @@ pub struct CommandLineArgs {
     #[arg(long = "bench", hide = true, action = ArgAction::SetTrue, required = false)]
     _bench: bool,
 
-    /// If specified, only run benches containing this string in their names
+    /// If specified, only run benches matching this regular expression
     ///
-    /// Note that a benchmark name might differ from the benchmark file name.
+    /// The pattern is matched against the name of the benchmark file and the full name of the
+    /// benchmark `<module>::<group>::<function>[.<id>]`. Note that a benchmark name might differ
+    /// from the benchmark file name. Prefix the pattern with `group=`, `function=` or `id=` to
+    /// match only the group id, the name of the benchmark function or the bench id, for example
+    /// `id=^small$`.
     #[arg(name = "BENCHNAME", num_args = 0..=1, env = "IAI_CALLGRIND_FILTER")]
     pub filter: Option<BenchmarkFilter>,
 
+    /// Match the `BENCHNAME` filter and the `--skip` patterns exactly instead of as regex
+    #[arg(
+        long = "exact",
+        default_missing_value = "true",
+        default_value = "false",
+        num_args = 0..=1,
+        require_equals = true,
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        env = "IAI_CALLGRIND_EXACT",
+    )]
+    pub exact: bool,
+
+    /// Skip benches matching this pattern (can be given multiple times)
+    ///
+    /// The pattern has the same format as the `BENCHNAME` filter. The `--skip` patterns are
+    /// applied after the `BENCHNAME` filter.
+    #[arg(long = "skip", num_args = 1, action = ArgAction::Append)]
+    pub skip: Vec<BenchmarkFilter>,
+
+    /// List all benchmarks instead of running them
+    ///
+    /// The benchmarks are filtered by the `BENCHNAME` filter and the `--skip` patterns. Each
+    /// benchmark is listed with its full name, arguments and tools. Use `--format=terse` for the
+    /// libtest compatible `<name>: benchmark` lines and `--output-format=json` to list the
+    /// benchmarks in json format.
+    #[arg(
+        long = "list",
+        default_missing_value = "true",
+        default_value = "false",
+        num_args = 0..=1,
+        require_equals = true,
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        env = "IAI_CALLGRIND_LIST",
+    )]
+    pub list: bool,
+
+    /// Print the valgrind invocations of all benchmarks instead of running them
+    ///
+    /// The configuration of each benchmark is resolved like in a real run and the complete
+    /// valgrind command line of the default tool and all enabled tools is shown with the
+    /// current directory and environment variables. Use `--output-format=json` to print the
+    /// invocations in json format. The benchmarks are filtered by the `BENCHNAME` filter and
+    /// the `--skip` patterns.
+    #[arg(
+        long = "dry-run",
+        default_missing_value = "true",
+        default_value = "false",
+        num_args = 0..=1,
+        require_equals = true,
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        env = "IAI_CALLGRIND_DRY_RUN",
+    )]
+    pub dry_run: bool,
+
+    /// Print the effective configuration of all benchmarks instead of running them
+    ///
+    /// The configuration of the `main!` macro, the group, the benchmark function, the bench
+    /// attribute and the command-line arguments are merged like in a real run. Each field of the
+    /// resolved configuration is shown with the layers the value came from. Use
+    /// `--output-format=json` to print the configuration in json format. The benchmarks are
+    /// filtered by the `BENCHNAME` filter and the `--skip` patterns.
+    #[arg(
+        long = "show-config",
+        default_missing_value = "true",
+        default_value = "false",
+        num_args = 0..=1,
+        require_equals = true,
+        value_parser = BoolishValueParser::new(),
+        action = ArgAction::Set,
+        env = "IAI_CALLGRIND_SHOW_CONFIG",
+    )]
+    pub show_config: bool,
+
+    /// The format of the `--list` output
+    #[arg(long = "format", value_enum, num_args = 1, default_value = "pretty")]
+    pub list_format: ListFormat,
+
     /// The raw arguments to pass through to Callgrind
     ///
     /// This is a space separated list of command-line-arguments specified as if they were
@@ -108,8 +315,42 @@ pub struct CommandLineArgs {
     )]
     pub callgrind_args: Option<RawArgs>,
 
+    /// The raw arguments to pass through to Cachegrind
+    ///
+    /// This is a space separated list of command-line-arguments specified as if they were
+    /// passed directly to valgrind. The arguments are only used if cachegrind is the default tool.
+    ///
+    /// Examples:
+    ///   * --cachegrind-args=--cache-sim=yes
+    ///   * --cachegrind-args='--cache-sim=yes --branch-sim=yes'
+    #[arg(
+        long = "cachegrind-args",
+        value_parser = parse_args,
+        num_args = 1,
+        verbatim_doc_comment,
+        env = "IAI_CALLGRIND_CACHEGRIND_ARGS"
+    )]
+    pub cachegrind_args: Option<RawArgs>,
+
+    /// The default tool used to benchmark the library and binary benchmarks
+    ///
+    /// Possible values are `callgrind` and `cachegrind`. The metrics of the default tool are shown
+    /// first and are used for the baselines and the performance regression checks. This option
+    /// overrides the default tool of the benchmark configuration. Cachegrind can't be the default
+    /// tool of library benchmarks because it doesn't support the entry point.
+    #[arg(
+        long = "default-tool",
+        num_args = 1,
+        value_parser = parse_default_tool,
+        env = "IAI_CALLGRIND_DEFAULT_TOOL"
+    )]
+    pub default_tool: Option<ValgrindTool>,
+
     /// Save a machine-readable summary of each benchmark run in json format next to the usual
     /// benchmark output
+    ///
+    /// In addition, the summaries of all benchmarks of the `cargo bench` invocation are aggregated
+    /// in the `run-summary.json` file in the iai home directory (per default `target/iai`).
     #[arg(
         long = "save-summary",
         value_enum,
@@ -120,6 +361,20 @@ pub struct CommandLineArgs {
     )]
     pub save_summary: Option<SummaryFormat>,
 
+    /// Save an aggregated report of all benchmarks of a benchmark target
+    ///
+    /// The report is saved in the directory of the benchmark target, for example
+    /// `target/iai/my_package/my_bench/report.junit.xml`. Multiple formats can be separated by
+    /// comma.
+    #[arg(
+        long = "save-report",
+        value_enum,
+        num_args = 1,
+        value_delimiter = ',',
+        env = "IAI_CALLGRIND_SAVE_REPORT"
+    )]
+    pub save_report: Vec<ReportFormat>,
+
     /// Allow ASLR (Address Space Layout Randomization)
     ///
     /// If possible, ASLR is disabled on platforms that support it (linux, freebsd) because ASLR
@@ -137,15 +392,24 @@ pub struct CommandLineArgs {
     )]
     pub allow_aslr: Option<bool>,
 
-    /// Set performance regression limits for specific `EventKinds`
+    /// Set performance regression limits for specific `EventKinds` and `DhatMetricKinds`
     ///
-    /// This is a `,` separate list of EventKind=limit (key=value) pairs with the limit being a
-    /// positive or negative percentage. If positive, a performance regression check for this
-    /// `EventKind` fails if the limit is exceeded. If negative, the regression check fails if the
-    /// value comes below the limit. The `EventKind` is matched case-insensitive. For a list of
-    /// valid `EventKinds` see the docs: <https://docs.rs/iai-callgrind/latest/iai_callgrind/enum.EventKind.html>
+    /// This is a `,` separate list of EventKind=limit or DhatMetricKind=limit (key=value) pairs
+    /// with the limit being a positive or negative percentage. If positive, a performance
+    /// regression check for this `EventKind` fails if the limit is exceeded. If negative, the
+    /// regression check fails if the value comes below the limit. The `EventKind` and
+    /// `DhatMetricKind` are matched case-insensitive. For a list of valid `EventKinds` see the
+    /// docs: <https://docs.rs/iai-callgrind/latest/iai_callgrind/enum.EventKind.html> and for the
+    /// `DhatMetricKinds`: <https://docs.rs/iai-callgrind/latest/iai_callgrind/enum.DhatMetricKind.html>
     ///
-    /// Examples: --regression='ir=0.0' or --regression='ir=0, EstimatedCycles=10'
+    /// In addition to the percentage limits, it's possible to specify a hard limit (the maximum
+    /// value) of an `EventKind` with `EventKind<=limit` and a limit of the absolute difference
+    /// with `EventKind+=limit` (the value may grow by at most `limit`) or `EventKind-=limit`
+    /// (the value may shrink by at most `limit`). Hard limits are checked even if there is no
+    /// baseline.
+    ///
+    /// Examples: --regression='ir=0.0' or --regression='ir=0, EstimatedCycles=10' or
+    /// --regression='TotalBytes=5' or --regression='ir<=1500000, EstimatedCycles+=10000'
     #[arg(
         long = "regression",
         num_args = 1,
@@ -201,6 +465,132 @@ pub struct CommandLineArgs {
     )]
     pub load_baseline: Option<BaselineName>,
 
+    /// What to do if the baseline given with `--baseline` is missing for a benchmark
+    ///
+    /// A baseline is missing for example if the benchmark is new or was renamed. The baseline of
+    /// the default tool and of all enabled tools is checked. With `fail` the benchmark run fails
+    /// before running the benchmark, with `warn` a warning is printed and with `ignore` the
+    /// benchmark runs silently without comparison. The `baseline_status` of the benchmark summary
+    /// of such a benchmark is `New`.
+    #[arg(
+        long = "missing-baseline",
+        value_enum,
+        num_args = 1,
+        require_equals = true,
+        default_value = "ignore",
+        env = "IAI_CALLGRIND_MISSING_BASELINE"
+    )]
+    pub missing_baseline: MissingBaseline,
+
+    /// Export this baseline of the benchmark target into an archive instead of running the
+    /// benchmarks
+    ///
+    /// The archive is a single json file with the gzip compressed content of all output and log
+    /// files of the baseline. It is saved in the directory of the benchmark target, for example
+    /// `target/iai/my_package/my_bench/main.baseline.json`, and can be imported with
+    /// `--import-baseline` into another checkout.
+    #[arg(
+        long = "export-baseline",
+        default_missing_value = "default",
+        num_args = 0..=1,
+        require_equals = true,
+        conflicts_with_all = &["save_baseline", "LOAD_BASELINE", "import_baseline"],
+        env = "IAI_CALLGRIND_EXPORT_BASELINE"
+    )]
+    pub export_baseline: Option<BaselineName>,
+
+    /// Import the baseline archive at this path instead of running the benchmarks
+    ///
+    /// An archive created with `--export-baseline` is only imported into the benchmark target
+    /// from which it was exported, so it is safe to run all benchmark targets with this option.
+    /// Existing files of the same baseline are overwritten. A warning is printed if the
+    /// architecture or the valgrind version differ from the current ones.
+    #[arg(
+        long = "import-baseline",
+        num_args = 1,
+        env = "IAI_CALLGRIND_IMPORT_BASELINE"
+    )]
+    pub import_baseline: Option<PathBuf>,
+
+    /// Remove stale output files of the benchmark target instead of running the benchmarks
+    ///
+    /// The files of benchmarks which don't exist anymore, for example after renaming a benchmark
+    /// function or removing an id, are stale. Possible values are `list` to only list the files
+    /// and `delete` (the default) to remove and list the files. Named baselines of the existing
+    /// benchmarks can additionally be pruned with `--keep-baselines` and `--max-baseline-age`.
+    #[arg(
+        long = "clean",
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "delete",
+        conflicts_with_all = &["export_baseline", "import_baseline"],
+        env = "IAI_CALLGRIND_CLEAN"
+    )]
+    pub clean: Option<CleanMode>,
+
+    /// With `--clean`, keep only this number of the newest named baselines of each benchmark
+    #[arg(
+        long = "keep-baselines",
+        num_args = 1,
+        requires = "clean",
+        env = "IAI_CALLGRIND_KEEP_BASELINES"
+    )]
+    pub keep_baselines: Option<usize>,
+
+    /// With `--clean`, remove the named baselines older than this age
+    ///
+    /// The age is a number followed by one of the units `s`, `m`, `h`, `d` or `w`, for example
+    /// `30d` for 30 days.
+    #[arg(
+        long = "max-baseline-age",
+        num_args = 1,
+        requires = "clean",
+        value_parser = parse_age,
+        env = "IAI_CALLGRIND_MAX_BASELINE_AGE"
+    )]
+    pub max_baseline_age: Option<Duration>,
+
+    /// Append the total metrics of each benchmark run to the history of the benchmark
+    ///
+    /// The history is a `history.jsonl` file in the directory of the benchmark which records the
+    /// metrics of the default tool together with the time, the git commit and the baseline name of
+    /// every run. Use `--show-history` to show the history.
+    #[arg(
+        long = "save-history",
+        default_missing_value = "true",
+        num_args = 0..=1,
+        require_equals = true,
+        value_parser = BoolishValueParser::new(),
+        env = "IAI_CALLGRIND_SAVE_HISTORY"
+    )]
+    pub save_history: Option<bool>,
+
+    /// Show the last N runs in the history of each benchmark instead of running the benchmarks
+    ///
+    /// Changes between two consecutive runs of at least `--history-threshold` percent are flagged
+    /// as step change. A total change of at least this threshold over the shown runs without any
+    /// step change is flagged as slow creep.
+    #[arg(
+        long = "show-history",
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "10",
+        conflicts_with_all = &["export_baseline", "import_baseline", "clean"],
+        env = "IAI_CALLGRIND_SHOW_HISTORY"
+    )]
+    pub show_history: Option<NonZeroUsize>,
+
+    /// The threshold in percent above which a change is flagged by `--show-history`
+    #[arg(
+        long = "history-threshold",
+        num_args = 1,
+        default_value = "5",
+        value_parser = parse_threshold,
+        env = "IAI_CALLGRIND_HISTORY_THRESHOLD"
+    )]
+    pub history_threshold: f64,
+
     /// The terminal output format in default human-readable format or in machine-readable json
     /// format
     ///
@@ -261,6 +651,41 @@ pub struct CommandLineArgs {
     #[arg(long = "home", num_args = 1, env = "IAI_CALLGRIND_HOME")]
     pub home: Option<PathBuf>,
 
+    /// The number of benchmarks of a benchmark target which are run in parallel
+    ///
+    /// The metrics of valgrind runs don't depend on the number of parallel runs and the terminal
+    /// output is still printed in the order of the declaration of the benchmarks. Benchmarks which
+    /// need isolation are always run alone: All benchmarks of groups with a `setup` or `teardown`
+    /// function and binary benchmarks with a `Sandbox`, `setup` or `teardown`. If `--nocapture`
+    /// is given, all benchmarks are run one after the other.
+    #[arg(
+        long = "jobs",
+        default_value = "1",
+        num_args = 1,
+        env = "IAI_CALLGRIND_JOBS"
+    )]
+    pub jobs: NonZeroUsize,
+
+    /// Run each benchmark this number of times with the default tool to detect noisy metrics
+    ///
+    /// For benchmarks which don't produce exactly the same metrics in each run, for example hash
+    /// maps with random seeds. The minimum, median and maximum of each metric over all runs are
+    /// shown and the output files of the run with the median of the first metric are kept. The
+    /// metrics of this run are shown, saved and checked for regressions but changes within the
+    /// spread (`max - min`) of the runs are ignored.
+    #[arg(long = "repeat", num_args = 1, env = "IAI_CALLGRIND_REPEAT")]
+    pub repeat: Option<NonZeroUsize>,
+
+    /// Annotate this number of functions which gained the most cost at the source line level
+    ///
+    /// Only applies to benchmarks with callgrind as default tool. The self costs per source line
+    /// (or instruction address if callgrind is run with `--dump-instr=yes`) of the new run are
+    /// compared with the old or base run and written to the file `callgrind.*.annotate` next to
+    /// the callgrind output files. The functions are ranked by the increase of the event kind
+    /// given to `OutputFormat::show_functions` (`Ir` by default).
+    #[arg(long = "annotate", num_args = 1, env = "IAI_CALLGRIND_ANNOTATE")]
+    pub annotate: Option<NonZeroUsize>,
+
     /// Don't capture terminal output of benchmarks
     ///
     /// Possible values are one of [true, false, stdout, stderr].
@@ -288,6 +713,20 @@ pub struct CommandLineArgs {
     pub nocapture: NoCapture,
 }
 
+impl CommandLineArgs {
+    /// Return true if the benchmark with the `identifiers` is excluded by the `BENCHNAME` filter or
+    /// one of the `--skip` patterns
+    pub fn is_filtered_out(&self, identifiers: &BenchmarkIdentifiers) -> bool {
+        self.filter
+            .as_ref()
+            .map_or(false, |filter| !filter.apply(identifiers, self.exact))
+            || self
+                .skip
+                .iter()
+                .any(|skip| skip.apply(identifiers, self.exact))
+    }
+}
+
 /// This function parses a space separated list of raw argument strings into [`crate::api::RawArgs`]
 fn parse_args(value: &str) -> Result<RawArgs, String> {
     shlex::split(value)
@@ -295,6 +734,15 @@ fn parse_args(value: &str) -> Result<RawArgs, String> {
         .map(RawArgs::new)
 }
 
+fn parse_default_tool(value: &str) -> Result<ValgrindTool, String> {
+    match ValgrindTool::try_from(value.trim().to_lowercase().as_str()) {
+        Ok(tool) if tool.is_default_capable() => Ok(tool),
+        Ok(_) | Err(_) => Err(format!(
+            "Invalid default tool '{value}': Valid tools are 'callgrind' and 'cachegrind'"
+        )),
+    }
+}
+
 fn parse_regression_config(value: &str) -> Result<RegressionConfig, String> {
     let value = value.trim();
     if value.is_empty() {
@@ -305,19 +753,55 @@ fn parse_regression_config(value: &str) -> Result<RegressionConfig, String> {
         RegressionConfig::default()
     } else {
         let mut limits = vec![];
+        let mut hard_limits = vec![];
+        let mut absolute_limits = vec![];
+        let mut dhat_limits = vec![];
 
         for split in value.split(',') {
             let split = split.trim();
 
             if let Some((key, value)) = split.split_once('=') {
-                let (key, value) = (key.trim(), value.trim());
-                let event_kind = EventKind::from_str_ignore_case(key)
-                    .ok_or_else(|| -> String { format!("Unknown event kind: '{key}'") })?;
-
-                let pct = value.parse::<f64>().map_err(|error| -> String {
-                    format!("Invalid percentage for '{key}': {error}")
-                })?;
-                limits.push((event_kind, pct));
+                let (key, value) = (key.trim_end(), value.trim());
+                if let Some(key) = key.strip_suffix('<') {
+                    let key = key.trim();
+                    let event_kind = EventKind::from_str_ignore_case(key)
+                        .ok_or_else(|| -> String { format!("Unknown event kind: '{key}'") })?;
+                    let limit = value.parse::<u64>().map_err(|error| -> String {
+                        format!("Invalid hard limit for '{key}': {error}")
+                    })?;
+                    hard_limits.push((event_kind, limit));
+                } else if let Some((key, sign)) = key
+                    .strip_suffix('+')
+                    .map(|k| (k, 1))
+                    .or_else(|| key.strip_suffix('-').map(|k| (k, -1)))
+                {
+                    let key = key.trim();
+                    let event_kind = EventKind::from_str_ignore_case(key)
+                        .ok_or_else(|| -> String { format!("Unknown event kind: '{key}'") })?;
+                    let limit = value
+                        .parse::<i64>()
+                        .map_err(|error| -> String {
+                            format!("Invalid absolute limit for '{key}': {error}")
+                        })?
+                        .checked_mul(sign)
+                        .ok_or_else(|| -> String {
+                            format!("Invalid absolute limit for '{key}': number too large")
+                        })?;
+                    absolute_limits.push((event_kind, limit));
+                } else {
+                    let key = key.trim();
+                    let pct = value.parse::<f64>().map_err(|error| -> String {
+                        format!("Invalid percentage for '{key}': {error}")
+                    })?;
+
+                    if let Some(event_kind) = EventKind::from_str_ignore_case(key) {
+                        limits.push((event_kind, pct));
+                    } else if let Some(metric_kind) = DhatMetricKind::from_str_ignore_case(key) {
+                        dhat_limits.push((metric_kind, pct));
+                    } else {
+                        return Err(format!("Unknown event kind: '{key}'"));
+                    }
+                }
             } else {
                 return Err(format!("Invalid format of key/value pair: '{split}'"));
             }
@@ -325,6 +809,9 @@ fn parse_regression_config(value: &str) -> Result<RegressionConfig, String> {
 
         RegressionConfig {
             limits,
+            hard_limits,
+            absolute_limits,
+            dhat_limits,
             ..Default::default()
         }
     };
@@ -342,6 +829,50 @@ impl From<&CommandLineArgs> for Option<RegressionConfig> {
     }
 }
 
+/// Parse a non-negative threshold in percent
+fn parse_threshold(value: &str) -> Result<f64, String> {
+    let threshold = value
+        .parse::<f64>()
+        .map_err(|error| format!("Invalid threshold '{value}': {error}"))?;
+    if threshold.is_finite() && threshold >= 0.0 {
+        Ok(threshold)
+    } else {
+        Err(format!(
+            "Invalid threshold '{value}': The threshold must be a non-negative number"
+        ))
+    }
+}
+
+/// Parse an age like `30d` into a [`Duration`]
+fn parse_age(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let (number, unit) = value.split_at(
+        value
+            .find(|c: char| !c.is_ascii_digit())
+            .unwrap_or(value.len()),
+    );
+    let number = number
+        .parse::<u64>()
+        .map_err(|error| format!("Invalid age '{value}': {error}"))?;
+    let seconds = match unit {
+        "s" => 1,
+        "m" => 60,
+        "h" => 60 * 60,
+        "d" => 24 * 60 * 60,
+        "w" => 7 * 24 * 60 * 60,
+        _ => {
+            return Err(format!(
+                "Invalid age '{value}': Expected one of the units 's', 'm', 'h', 'd' or 'w'"
+            ))
+        }
+    };
+
+    number
+        .checked_mul(seconds)
+        .map(Duration::from_secs)
+        .ok_or_else(|| format!("Invalid age '{value}': number too large"))
+}
+
 fn parse_nocapture(value: &str) -> Result<NoCapture, String> {
     // Taken from clap source code
     const TRUE_LITERALS: [&str; 6] = ["y", "yes", "t", "true", "on", "1"];
@@ -395,6 +926,63 @@ mod tests {
     ) {
         let expected = RegressionConfig {
             limits: expected_limits,
+            hard_limits: vec![],
+            absolute_limits: vec![],
+            dhat_limits: vec![],
+            error_limits: vec![],
+            fail_on_error_increase: None,
+            fail_fast: None,
+        };
+
+        let actual = parse_regression_config(regression_var).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case::dhat_only("TotalBytes=10", vec![], vec![(DhatMetricKind::TotalBytes, 10f64)])]
+    #[case::dhat_case_insensitive("attgmaxbytes=5", vec![], vec![(DhatMetricKind::AtTGmaxBytes, 5f64)])]
+    #[case::mixed(
+        "Ir=10, TotalBlocks=-5",
+        vec![(Ir, 10f64)],
+        vec![(DhatMetricKind::TotalBlocks, -5f64)]
+    )]
+    fn test_parse_regression_config_with_dhat_limits(
+        #[case] regression_var: &str,
+        #[case] expected_limits: Vec<(EventKind, f64)>,
+        #[case] expected_dhat_limits: Vec<(DhatMetricKind, f64)>,
+    ) {
+        let expected = RegressionConfig {
+            limits: expected_limits,
+            hard_limits: vec![],
+            absolute_limits: vec![],
+            dhat_limits: expected_dhat_limits,
+            error_limits: vec![],
+            fail_on_error_increase: None,
+            fail_fast: None,
+        };
+
+        let actual = parse_regression_config(regression_var).unwrap();
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case::hard("Ir<=1500000", vec![(Ir, 1_500_000)], vec![])]
+    #[case::hard_with_whitespace(" ir <= 10 ", vec![(Ir, 10)], vec![])]
+    #[case::absolute_positive("EstimatedCycles+=10000", vec![], vec![(EstimatedCycles, 10_000)])]
+    #[case::absolute_negative("Ir-=5", vec![], vec![(Ir, -5)])]
+    #[case::mixed("Ir<=100, Ir+=5", vec![(Ir, 100)], vec![(Ir, 5)])]
+    fn test_parse_regression_config_with_hard_and_absolute_limits(
+        #[case] regression_var: &str,
+        #[case] expected_hard_limits: Vec<(EventKind, u64)>,
+        #[case] expected_absolute_limits: Vec<(EventKind, i64)>,
+    ) {
+        let expected = RegressionConfig {
+            limits: vec![],
+            hard_limits: expected_hard_limits,
+            absolute_limits: expected_absolute_limits,
+            dhat_limits: vec![],
+            error_limits: vec![],
+            fail_on_error_increase: None,
             fail_fast: None,
         };
 
@@ -413,6 +1001,22 @@ mod tests {
         "Invalid percentage for 'Ir': invalid float literal"
     )]
     #[case::regression_empty_limits("", "No limits found: At least one limit must be specified")]
+    #[case::regression_hard_limit_unknown_event_kind(
+        "TotalBytes<=10",
+        "Unknown event kind: 'TotalBytes'"
+    )]
+    #[case::regression_invalid_hard_limit(
+        "Ir<=-10",
+        "Invalid hard limit for 'Ir': invalid digit found in string"
+    )]
+    #[case::regression_invalid_absolute_limit(
+        "Ir+=1.5",
+        "Invalid absolute limit for 'Ir': invalid digit found in string"
+    )]
+    #[case::regression_absolute_limit_overflow(
+        "Ir-=-9223372036854775808",
+        "Invalid absolute limit for 'Ir': number too large"
+    )]
     fn test_try_regression_config_from_env_then_error(
         #[case] regression_var: &str,
         #[case] expected_reason: &str,
@@ -479,6 +1083,21 @@ mod tests {
         assert_eq!(result.save_summary, Some(expected));
     }
 
+    #[rstest]
+    #[case::junit("junit", &[ReportFormat::Junit])]
+    #[case::markdown("markdown", &[ReportFormat::Markdown])]
+    #[case::multiple("junit,markdown", &[ReportFormat::Junit, ReportFormat::Markdown])]
+    fn test_save_report_cli(#[case] value: &str, #[case] expected: &[ReportFormat]) {
+        let result = CommandLineArgs::parse_from([format!("--save-report={value}")]);
+        assert_eq!(result.save_report, expected);
+    }
+
+    #[test]
+    fn test_save_report_cli_when_invalid_then_error() {
+        let result = CommandLineArgs::try_parse_from(["--save-report=xml"]);
+        assert!(result.is_err());
+    }
+
     #[test]
     #[serial_test::serial]
     fn test_allow_aslr_env() {
@@ -535,6 +1154,290 @@ mod tests {
         assert_eq!(result.home, Some(PathBuf::from("/test_me")));
     }
 
+    #[test]
+    #[serial_test::serial]
+    fn test_jobs_env() {
+        std::env::set_var("IAI_CALLGRIND_JOBS", "4");
+        let result = CommandLineArgs::parse_from::<[_; 0], &str>([]);
+        assert_eq!(result.jobs, NonZeroUsize::new(4).unwrap());
+    }
+
+    #[rstest]
+    #[case::one("1", 1)]
+    #[case::many("8", 8)]
+    fn test_jobs_cli(#[case] value: &str, #[case] expected: usize) {
+        let result = CommandLineArgs::parse_from([format!("--jobs={value}")]);
+        assert_eq!(result.jobs, NonZeroUsize::new(expected).unwrap());
+    }
+
+    #[rstest]
+    #[case::zero("0")]
+    #[case::negative("-1")]
+    #[case::not_a_number("all")]
+    fn test_jobs_cli_when_invalid(#[case] value: &str) {
+        assert!(CommandLineArgs::try_parse_from([format!("--jobs={value}")]).is_err());
+    }
+
+    #[rstest]
+    #[case::default("--export-baseline", "default")]
+    #[case::name("--export-baseline=main", "main")]
+    fn test_export_baseline_cli(#[case] value: &str, #[case] expected: &str) {
+        let result = CommandLineArgs::parse_from([value]);
+        assert_eq!(result.export_baseline, Some(expected.parse().unwrap()));
+    }
+
+    #[test]
+    fn test_import_baseline_cli() {
+        let result = CommandLineArgs::parse_from(["--import-baseline=/tmp/main.baseline.json"]);
+        assert_eq!(
+            result.import_baseline,
+            Some(PathBuf::from("/tmp/main.baseline.json"))
+        );
+    }
+
+    #[rstest]
+    #[case::save_baseline(&["--export-baseline=main", "--save-baseline=main"])]
+    #[case::load_baseline(&["--export-baseline=main", "--load-baseline=main", "--baseline=old"])]
+    #[case::import_baseline(&["--export-baseline=main", "--import-baseline=main.baseline.json"])]
+    fn test_export_baseline_cli_when_conflicts_then_error(#[case] args: &[&str]) {
+        assert!(CommandLineArgs::try_parse_from(args).is_err());
+    }
+
+    #[rstest]
+    #[case::default("--clean", CleanMode::Delete)]
+    #[case::list("--clean=list", CleanMode::List)]
+    #[case::delete("--clean=delete", CleanMode::Delete)]
+    fn test_clean_cli(#[case] value: &str, #[case] expected: CleanMode) {
+        let result = CommandLineArgs::parse_from([value]);
+        assert_eq!(result.clean, Some(expected));
+    }
+
+    #[rstest]
+    #[case::keep_baselines("--keep-baselines=3")]
+    #[case::max_baseline_age("--max-baseline-age=30d")]
+    fn test_prune_baselines_cli_when_no_clean_then_error(#[case] value: &str) {
+        assert!(CommandLineArgs::try_parse_from([value]).is_err());
+    }
+
+    #[rstest]
+    #[case::default(&[], None, 5.0f64)]
+    #[case::show_default(&["--show-history"], Some(10), 5.0f64)]
+    #[case::show_last(&["--show-history=3"], Some(3), 5.0f64)]
+    #[case::threshold(&["--show-history", "--history-threshold=0.5"], Some(10), 0.5f64)]
+    fn test_show_history_cli(
+        #[case] args: &[&str],
+        #[case] expected_last: Option<usize>,
+        #[case] expected_threshold: f64,
+    ) {
+        let result = CommandLineArgs::parse_from(args);
+        assert_eq!(result.show_history.map(NonZeroUsize::get), expected_last);
+        assert!((result.history_threshold - expected_threshold).abs() < f64::EPSILON);
+    }
+
+    #[rstest]
+    #[case::zero("--show-history=0")]
+    #[case::negative_threshold("--history-threshold=-1")]
+    #[case::nan_threshold("--history-threshold=NaN")]
+    fn test_show_history_cli_when_invalid_then_error(#[case] value: &str) {
+        assert!(CommandLineArgs::try_parse_from([value]).is_err());
+    }
+
+    #[test]
+    fn test_save_history_cli() {
+        let result = CommandLineArgs::parse_from(["--save-history"]);
+        assert_eq!(result.save_history, Some(true));
+    }
+
+    #[rstest]
+    #[case::default(&[], None)]
+    #[case::one(&["--repeat=1"], Some(1))]
+    #[case::many(&["--repeat=5"], Some(5))]
+    fn test_repeat_cli(#[case] args: &[&str], #[case] expected: Option<usize>) {
+        let result = CommandLineArgs::parse_from(args);
+        assert_eq!(result.repeat.map(NonZeroUsize::get), expected);
+    }
+
+    #[test]
+    fn test_repeat_cli_when_zero_then_error() {
+        assert!(CommandLineArgs::try_parse_from(["--repeat=0"]).is_err());
+    }
+
+    #[rstest]
+    #[case::default(&[], None)]
+    #[case::some(&["--annotate=5"], Some(5))]
+    #[case::zero(&["--annotate=0"], None)]
+    fn test_annotate_cli(#[case] args: &[&str], #[case] expected: Option<usize>) {
+        let result = CommandLineArgs::try_parse_from(args);
+        match expected {
+            Some(expected) => {
+                assert_eq!(
+                    result.unwrap().annotate.map(NonZeroUsize::get),
+                    Some(expected)
+                );
+            }
+            None if args.is_empty() => assert_eq!(result.unwrap().annotate, None),
+            None => assert!(result.is_err()),
+        }
+    }
+
+    #[rstest]
+    #[case::no_filter(&[], true)]
+    #[case::file_name(&["my-bench"], true)]
+    #[case::function_substring(&["bench_fn"], true)]
+    #[case::full_name_regex(&["my_group::bench_fn\\.small$"], true)]
+    #[case::full_name_regex_no_match(&["^my_group"], false)]
+    #[case::exact(&["--exact", "my_bench::my_group::bench_fn.small"], true)]
+    #[case::exact_when_substring(&["--exact", "bench_fn"], false)]
+    #[case::group(&["group=^my_group$"], true)]
+    #[case::group_no_match(&["group=other"], false)]
+    #[case::function(&["function=bench"], true)]
+    #[case::function_exact(&["--exact", "function=bench"], false)]
+    #[case::id(&["id=^small$"], true)]
+    #[case::id_exact_no_match(&["--exact", "id=smal"], false)]
+    #[case::skip(&["--skip=small"], false)]
+    #[case::skip_no_match(&["--skip=big"], true)]
+    #[case::skip_multiple(&["--skip=big", "--skip", "id=small"], false)]
+    #[case::filter_and_skip(&["bench_fn", "--skip=group=my_group"], false)]
+    fn test_is_filtered_out(#[case] args: &[&str], #[case] expected_is_match: bool) {
+        let full_name = BenchmarkIdentifiers::full_name(
+            &ModulePath::new("my_bench::my_group::bench_fn"),
+            Some("small"),
+        );
+        let identifiers = BenchmarkIdentifiers {
+            bench_name: "my-bench",
+            full_name: &full_name,
+            group: "my_group",
+            function: "bench_fn",
+            id: Some("small"),
+        };
+        let args = CommandLineArgs::try_parse_from(args).unwrap();
+        assert_eq!(args.is_filtered_out(&identifiers), !expected_is_match);
+    }
+
+    #[rstest]
+    #[case::name("foo", None)]
+    #[case::group("group=foo", Some("group"))]
+    #[case::function("function=foo", Some("function"))]
+    #[case::id("id=foo", Some("id"))]
+    #[case::unknown_prefix("other=foo", None)]
+    fn test_benchmark_filter_from_str(#[case] value: &str, #[case] expected: Option<&str>) {
+        let actual = match BenchmarkFilter::from_str(value).unwrap() {
+            BenchmarkFilter::Name(pattern) => {
+                assert_eq!(pattern.raw, value);
+                None
+            }
+            BenchmarkFilter::Group(pattern) => {
+                assert_eq!(pattern.raw, "foo");
+                Some("group")
+            }
+            BenchmarkFilter::Function(pattern) => {
+                assert_eq!(pattern.raw, "foo");
+                Some("function")
+            }
+            BenchmarkFilter::Id(pattern) => {
+                assert_eq!(pattern.raw, "foo");
+                Some("id")
+            }
+        };
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_benchmark_filter_when_invalid_regex_then_error() {
+        assert!(CommandLineArgs::try_parse_from(["id=(foo"]).is_err());
+    }
+
+    #[rstest]
+    #[case::default(&[], false, ListFormat::Pretty)]
+    #[case::list(&["--list"], true, ListFormat::Pretty)]
+    #[case::list_terse(&["--list", "--format", "terse"], true, ListFormat::Terse)]
+    #[case::list_terse_with_equals(&["--list", "--format=terse"], true, ListFormat::Terse)]
+    #[case::list_false(&["--list=no"], false, ListFormat::Pretty)]
+    fn test_list_cli(
+        #[case] args: &[&str],
+        #[case] expected_list: bool,
+        #[case] expected_format: ListFormat,
+    ) {
+        let actual = CommandLineArgs::try_parse_from(args).unwrap();
+        assert_eq!(actual.list, expected_list);
+        assert_eq!(actual.list_format, expected_format);
+    }
+
+    #[rstest]
+    #[case::default(&[], false)]
+    #[case::dry_run(&["--dry-run"], true)]
+    #[case::dry_run_yes(&["--dry-run=yes"], true)]
+    #[case::dry_run_false(&["--dry-run=false"], false)]
+    fn test_dry_run_cli(#[case] args: &[&str], #[case] expected: bool) {
+        let actual = CommandLineArgs::try_parse_from(args).unwrap();
+        assert_eq!(actual.dry_run, expected);
+    }
+
+    #[rstest]
+    #[case::default(&[], false)]
+    #[case::show_config(&["--show-config"], true)]
+    #[case::show_config_no(&["--show-config=no"], false)]
+    fn test_show_config_cli(#[case] args: &[&str], #[case] expected: bool) {
+        let actual = CommandLineArgs::try_parse_from(args).unwrap();
+        assert_eq!(actual.show_config, expected);
+    }
+
+    #[rstest]
+    #[case::default(&[], MissingBaseline::Ignore)]
+    #[case::fail(&["--missing-baseline=fail"], MissingBaseline::Fail)]
+    #[case::warn(&["--missing-baseline=warn"], MissingBaseline::Warn)]
+    #[case::ignore(&["--missing-baseline=ignore"], MissingBaseline::Ignore)]
+    fn test_missing_baseline_cli(#[case] args: &[&str], #[case] expected: MissingBaseline) {
+        let actual = CommandLineArgs::try_parse_from(args).unwrap();
+        assert_eq!(actual.missing_baseline, expected);
+    }
+
+    #[rstest]
+    #[case::fail(MissingBaseline::Fail, false)]
+    #[case::warn(MissingBaseline::Warn, true)]
+    #[case::ignore(MissingBaseline::Ignore, true)]
+    fn test_missing_baseline_check(#[case] missing_baseline: MissingBaseline, #[case] is_ok: bool) {
+        let module_path = ModulePath::new("my_bench::my_group::bench_fn");
+        let actual = missing_baseline.check(
+            &BaselineName::from_str("main").unwrap(),
+            ValgrindTool::Callgrind,
+            &module_path,
+        );
+        if is_ok {
+            assert!(actual.is_ok());
+        } else {
+            assert_eq!(
+                actual.unwrap_err().downcast::<Error>().unwrap(),
+                Error::BenchmarkError(
+                    ValgrindTool::Callgrind,
+                    module_path,
+                    "The baseline 'main' is missing".to_owned()
+                )
+            );
+        }
+    }
+
+    #[rstest]
+    #[case::seconds("10s", 10)]
+    #[case::minutes("2m", 120)]
+    #[case::hours("1h", 3600)]
+    #[case::days("30d", 30 * 24 * 3600)]
+    #[case::weeks("2w", 2 * 7 * 24 * 3600)]
+    fn test_parse_age(#[case] value: &str, #[case] expected: u64) {
+        assert_eq!(parse_age(value), Ok(Duration::from_secs(expected)));
+    }
+
+    #[rstest]
+    #[case::empty("")]
+    #[case::no_unit("10")]
+    #[case::no_number("d")]
+    #[case::unknown_unit("10y")]
+    #[case::negative("-1d")]
+    #[case::overflow("999999999999999999d")]
+    fn test_parse_age_when_invalid_then_error(#[case] value: &str) {
+        assert!(parse_age(value).is_err());
+    }
+
     #[test]
     fn test_home_cli_when_no_value_then_error() {
         let result = CommandLineArgs::try_parse_from(["--home=".to_owned()]);
@@ -564,6 +1467,23 @@ mod tests {
         assert_eq!(result.nocapture, NoCapture::True);
     }
 
+    #[rstest]
+    #[case::callgrind("callgrind", ValgrindTool::Callgrind)]
+    #[case::cachegrind("cachegrind", ValgrindTool::Cachegrind)]
+    #[case::case_insensitive("CacheGrind", ValgrindTool::Cachegrind)]
+    fn test_default_tool_cli(#[case] value: &str, #[case] expected: ValgrindTool) {
+        let result = CommandLineArgs::parse_from([format!("--default-tool={value}")]);
+        assert_eq!(result.default_tool, Some(expected));
+    }
+
+    #[rstest]
+    #[case::not_default_capable("dhat")]
+    #[case::unknown("unknown")]
+    fn test_default_tool_cli_when_invalid_then_error(#[case] value: &str) {
+        let result = CommandLineArgs::try_parse_from([format!("--default-tool={value}")]);
+        assert!(result.is_err());
+    }
+
     #[rstest]
     #[case::y("y", true)]
     #[case::yes("yes", true)]
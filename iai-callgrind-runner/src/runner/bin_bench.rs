@@ -1,6 +1,5 @@
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::ffi::OsString;
-use std::io::stderr;
 use std::io::ErrorKind::WouldBlock;
 use std::net::{Ipv4Addr, Ipv6Addr, SocketAddr, TcpStream, UdpSocket};
 use std::path::{Path, PathBuf};
@@ -11,26 +10,31 @@ use std::{panic, thread};
 use anyhow::{anyhow, Context, Result};
 use log::{debug, warn};
 
-use super::args::NoCapture;
+use super::args::{BenchmarkIdentifiers, NoCapture};
 use super::callgrind::args::Args;
 use super::callgrind::flamegraph::{
     BaselineFlamegraphGenerator, Config as FlamegraphConfig, Flamegraph, FlamegraphGenerator,
     LoadBaselineFlamegraphGenerator, SaveBaselineFlamegraphGenerator,
 };
-use super::callgrind::parser::CallgrindParser;
-use super::callgrind::summary_parser::SummaryParser;
-use super::callgrind::{RegressionConfig, Summaries};
+use super::callgrind::summary_parser::parser_factory;
+use super::callgrind::{annotate, functions, RegressionConfig, Summaries};
 use super::common::{Assistant, AssistantKind, Config, ModulePath, Sandbox};
+use super::dry_run::BenchmarkInvocations;
+use super::effective_config::{EffectiveConfig, Layer};
 use super::format::{BinaryBenchmarkHeader, Formatter, OutputFormat, VerticalFormatter};
+use super::list::BenchmarkListing;
 use super::meta::Metadata;
+use super::output::stderr;
 use super::summary::{
-    BaselineKind, BaselineName, BenchmarkKind, BenchmarkSummary, CallgrindSummary, MetricsSummary,
-    SummaryOutput, ToolRun,
+    BaselineKind, BaselineName, BaselineStatus, BenchmarkKind, BenchmarkSummary, CachegrindSummary,
+    CallgrindSummary, MetricsSummary, NoiseSummary, RunSummary, SummaryOutput, ToolRun,
 };
+use super::tool::args::ToolArgs;
 use super::tool::{
-    RunOptions, ToolCommand, ToolConfig, ToolConfigs, ToolOutputPath, ToolOutputPathKind,
-    ValgrindTool,
+    RunOptions, ToolCommand, ToolConfig, ToolConfigs, ToolOutput, ToolOutputPath,
+    ToolOutputPathKind, ValgrindTool,
 };
+use super::{clean, dry_run, effective_config, history, jobs, list, noise, report};
 use crate::api::{
     self, BinaryBenchmarkBench, BinaryBenchmarkConfig, BinaryBenchmarkGroups, DelayKind, Stdin,
 };
@@ -59,7 +63,9 @@ pub struct BinBench {
     pub function_name: String,
     pub command: Command,
     pub run_options: RunOptions,
+    pub default_tool: ValgrindTool,
     pub callgrind_args: Args,
+    pub cachegrind_args: ToolArgs,
     pub flamegraph_config: Option<FlamegraphConfig>,
     pub regression_config: Option<RegressionConfig>,
     pub tools: ToolConfigs,
@@ -68,6 +74,7 @@ pub struct BinBench {
     pub sandbox: Option<api::Sandbox>,
     pub module_path: ModulePath,
     pub output_format: OutputFormat,
+    pub effective_config: Option<EffectiveConfig>,
 }
 
 /// The Command we derive from the `api::Command`
@@ -105,7 +112,11 @@ struct Group {
 }
 
 #[derive(Debug)]
-struct Groups(Vec<Group>);
+struct Groups {
+    groups: Vec<Group>,
+    /// The output directories of the benchmarks which are filtered out
+    filtered_out_dirs: BTreeSet<PathBuf>,
+}
 
 #[derive(Debug)]
 struct LoadBaselineBenchmark {
@@ -127,7 +138,7 @@ struct SaveBaselineBenchmark {
     baseline: BaselineName,
 }
 
-trait Benchmark: std::fmt::Debug {
+trait Benchmark: std::fmt::Debug + Sync {
     fn output_path(&self, bin_bench: &BinBench, config: &Config, group: &Group) -> ToolOutputPath;
     fn baselines(&self) -> (Option<String>, Option<String>);
     fn run(&self, bin_bench: &BinBench, config: &Config, group: &Group)
@@ -138,7 +149,7 @@ impl Benchmark for BaselineBenchmark {
     fn output_path(&self, bin_bench: &BinBench, config: &Config, group: &Group) -> ToolOutputPath {
         ToolOutputPath::new(
             ToolOutputPathKind::Out,
-            ValgrindTool::Callgrind,
+            bin_bench.default_tool,
             &self.baseline_kind,
             &config.meta.target_dir,
             &group.module_path,
@@ -162,20 +173,16 @@ impl Benchmark for BaselineBenchmark {
         let header = BinaryBenchmarkHeader::new(&config.meta, bin_bench);
         header.print();
 
-        let callgrind_command = ToolCommand::new(
-            ValgrindTool::Callgrind,
-            &config.meta,
-            config.meta.args.nocapture,
-        );
-
-        let tool_config = ToolConfig::new(
-            ValgrindTool::Callgrind,
-            true,
-            bin_bench.callgrind_args.clone(),
-            None,
-        );
-
         let out_path = self.output_path(bin_bench, config, group);
+        if let BaselineKind::Name(name) = &self.baseline_kind {
+            for tool in bin_bench.tools.missing_baselines(&out_path) {
+                config
+                    .meta
+                    .args
+                    .missing_baseline
+                    .check(name, tool, &bin_bench.module_path)?;
+            }
+        }
         out_path.init()?;
         out_path.shift()?;
 
@@ -195,57 +202,21 @@ impl Benchmark for BaselineBenchmark {
             header.description(),
         )?;
 
-        // We're implicitly applying the default here: In the absence of a user provided sandbox we
-        // don't run the benchmarks in a sandbox. Everything from here on runs with the current
-        // directory set to the sandbox directory until the sandbox is reset.
-        let sandbox = bin_bench
-            .sandbox
-            .as_ref()
-            .map(|sandbox| Sandbox::setup(sandbox, &config.meta))
-            .transpose()?;
-
-        let mut child = bin_bench
-            .setup
-            .as_ref()
-            .map_or(Ok(None), |setup| setup.run(config, &bin_bench.module_path))?;
-
-        if let Some(delay) = &bin_bench.command.delay {
-            if let Err(error) = delay.run() {
-                if let Some(mut child) = child.take() {
-                    // To avoid zombies
-                    child.kill()?;
-                    return Err(error);
-                }
-            }
-        }
-
-        let output = callgrind_command.run(
-            tool_config,
-            &bin_bench.command.path,
-            &bin_bench.command.args,
-            bin_bench.run_options.clone(),
-            &out_path,
-            &bin_bench.module_path,
-            child,
-        )?;
-
-        if let Some(teardown) = &bin_bench.teardown {
-            teardown.run(config, &bin_bench.module_path)?;
-        }
+        let parser = parser_factory(bin_bench.default_tool);
+        let (output, noise) =
+            noise::run_repeated(config.meta.args.repeat, &out_path, parser.as_ref(), || {
+                bin_bench.run_default_tool(config, &out_path)
+            })?;
 
-        // We print the no capture footer after the teardown to keep the output consistent with
-        // library benchmarks.
-        bin_bench.print_nocapture_footer(config.meta.args.nocapture);
-
-        if let Some(sandbox) = sandbox {
-            sandbox.reset()?;
-        }
-
-        let parsed_new = SummaryParser.parse(&out_path)?;
+        let parsed_new = parser.parse(&out_path)?;
         let parsed_old = old_path
             .exists()
-            .then(|| SummaryParser.parse(&old_path))
+            .then(|| parser.parse(&old_path))
             .transpose()?;
+        benchmark_summary.baseline_status = BaselineStatus::new(
+            &self.baseline_kind,
+            bin_bench.tools.missing_baselines(&out_path).is_empty(),
+        );
 
         let summaries = Summaries::new(parsed_new, parsed_old);
         VerticalFormatter::new(bin_bench.output_format).print(
@@ -253,28 +224,23 @@ impl Benchmark for BaselineBenchmark {
             self.baselines(),
             &ToolRun::from(&summaries),
         )?;
+        noise::print(noise.as_ref(), &bin_bench.output_format);
 
         output.dump_log(log::Level::Info);
         log_path.dump_log(log::Level::Info, &mut stderr())?;
 
-        let regressions = bin_bench.check_and_print_regressions(&summaries.total);
-
-        let callgrind_summary = benchmark_summary
-            .callgrind_summary
-            .insert(CallgrindSummary::new(
-                log_path.real_paths()?,
-                out_path.real_paths()?,
-            ));
-
-        callgrind_summary.add_summaries(
-            &bin_bench.command.path,
-            &bin_bench.command.args,
+        let callgrind_summary = bin_bench.add_default_tool_summaries(
+            &mut benchmark_summary,
+            config,
+            &out_path,
             &self.baselines(),
             summaries,
-            regressions,
-        );
+            noise,
+        )?;
 
-        if let Some(flamegraph_config) = bin_bench.flamegraph_config.clone() {
+        if let (Some(callgrind_summary), Some(flamegraph_config)) =
+            (callgrind_summary, bin_bench.flamegraph_config.clone())
+        {
             callgrind_summary.flamegraphs = BaselineFlamegraphGenerator {
                 baseline_kind: self.baseline_kind.clone(),
             }
@@ -298,6 +264,7 @@ impl Benchmark for BaselineBenchmark {
             bin_bench.setup.as_ref(),
             bin_bench.teardown.as_ref(),
             bin_bench.command.delay.as_ref(),
+            bin_bench.regression_config.as_ref(),
             &bin_bench.output_format,
         )?;
 
@@ -337,6 +304,15 @@ impl BinBench {
             meta_callgrind_args,
         ])?;
 
+        let default_tool = ValgrindTool::try_default_tool(config.default_tool, meta)?;
+        let mut cachegrind_args = config.valgrind_args.clone();
+        cachegrind_args.extend_ignore_flag(config.cachegrind_args.0.iter());
+        if let Some(meta_cachegrind_args) = &meta.args.cachegrind_args {
+            cachegrind_args.extend_ignore_flag(meta_cachegrind_args.0.iter());
+        }
+        let cachegrind_args =
+            ToolArgs::try_from_raw_args(ValgrindTool::Cachegrind, cachegrind_args)?;
+
         let mut assistant_envs = config.collect_envs();
         assistant_envs.push((
             OsString::from(defaults::WORKSPACE_ROOT_ENV),
@@ -354,28 +330,16 @@ impl BinBench {
             id: binary_benchmark_bench.id,
             args: binary_benchmark_bench.args,
             function_name: binary_benchmark_bench.function_name,
+            default_tool,
             callgrind_args,
+            cachegrind_args,
             flamegraph_config,
             regression_config: api::update_option(
                 &config.regression_config,
                 &meta.regression_config,
             )
             .map(Into::into),
-            tools: ToolConfigs(
-                config
-                    .tools
-                    .0
-                    .into_iter()
-                    .map(|mut t| {
-                        if !config.valgrind_args.is_empty() {
-                            let mut new_args = config.valgrind_args.clone();
-                            new_args.extend_ignore_flag(t.raw_args.0.iter());
-                            t.raw_args = new_args;
-                        }
-                        t.try_into()
-                    })
-                    .collect::<Result<Vec<_>, _>>()?,
-            ),
+            tools: ToolConfigs::new(config.tools, &config.valgrind_args, default_tool)?,
             setup: binary_benchmark_bench
                 .has_setup
                 .then_some(Assistant::new_bench_assistant(
@@ -415,9 +379,18 @@ impl BinBench {
             module_path,
             command,
             output_format,
+            effective_config: None,
         })
     }
 
+    /// Return true if this benchmark can't be run in parallel with other benchmarks
+    ///
+    /// A `Sandbox` changes the current directory of the whole process and the setup and teardown
+    /// functions usually change some shared state like files or servers.
+    fn is_isolated(&self) -> bool {
+        self.sandbox.is_some() || self.setup.is_some() || self.teardown.is_some()
+    }
+
     fn name(&self) -> String {
         if let Some(bench_id) = &self.id {
             format!("{}.{}", self.function_name, bench_id)
@@ -463,12 +436,151 @@ impl BinBench {
         ))
     }
 
+    /// Return the [`ToolConfig`] of the default tool
+    fn default_tool_config(&self) -> ToolConfig {
+        if self.default_tool == ValgrindTool::Cachegrind {
+            ToolConfig::new(
+                ValgrindTool::Cachegrind,
+                true,
+                self.cachegrind_args.clone(),
+                None,
+            )
+        } else {
+            ToolConfig::new(
+                ValgrindTool::Callgrind,
+                true,
+                self.callgrind_args.clone(),
+                None,
+            )
+        }
+    }
+
+    /// Run the default tool once with the output written to the `out_path`
+    ///
+    /// The sandbox, setup, delay and teardown are applied to each run.
+    fn run_default_tool(&self, config: &Config, out_path: &ToolOutputPath) -> Result<ToolOutput> {
+        // We're implicitly applying the default here: In the absence of a user provided sandbox we
+        // don't run the benchmarks in a sandbox. Everything from here on runs with the current
+        // directory set to the sandbox directory until the sandbox is reset.
+        let sandbox = self
+            .sandbox
+            .as_ref()
+            .map(|sandbox| Sandbox::setup(sandbox, &config.meta))
+            .transpose()?;
+
+        let mut child = self
+            .setup
+            .as_ref()
+            .map_or(Ok(None), |setup| setup.run(config, &self.module_path))?;
+
+        if let Some(delay) = &self.command.delay {
+            if let Err(error) = delay.run() {
+                if let Some(mut child) = child.take() {
+                    // To avoid zombies
+                    child.kill()?;
+                    return Err(error);
+                }
+            }
+        }
+
+        let output = ToolCommand::new(self.default_tool, &config.meta, config.meta.args.nocapture)
+            .run(
+                self.default_tool_config(),
+                &self.command.path,
+                &self.command.args,
+                self.run_options.clone(),
+                out_path,
+                &self.module_path,
+                child,
+            )?;
+
+        if let Some(teardown) = &self.teardown {
+            teardown.run(config, &self.module_path)?;
+        }
+
+        // We print the no capture footer after the teardown to keep the output consistent with
+        // library benchmarks.
+        self.print_nocapture_footer(config.meta.args.nocapture);
+
+        if let Some(sandbox) = sandbox {
+            sandbox.reset()?;
+        }
+
+        Ok(output)
+    }
+
+    /// Check the `summaries` of the default tool for regressions and add them to the
+    /// [`BenchmarkSummary`]
+    ///
+    /// Returns the [`CallgrindSummary`] if callgrind is the default tool, so the flamegraphs can
+    /// be added to it.
+    fn add_default_tool_summaries<'a>(
+        &self,
+        benchmark_summary: &'a mut BenchmarkSummary,
+        config: &Config,
+        out_path: &ToolOutputPath,
+        baselines: &(Option<String>, Option<String>),
+        summaries: Summaries,
+        noise: Option<NoiseSummary>,
+    ) -> Result<Option<&'a mut CallgrindSummary>> {
+        let functions = if self.default_tool == ValgrindTool::Callgrind {
+            if let Some(top) = config.meta.args.annotate {
+                annotate::annotate_and_write(
+                    out_path,
+                    &config.meta.project_root,
+                    top.get(),
+                    self.output_format.sort_functions_by,
+                )?;
+            }
+            functions::summarize_and_print(
+                out_path,
+                &config.meta.project_root,
+                &self.output_format,
+            )?
+        } else {
+            vec![]
+        };
+        let regressions = self.check_and_print_regressions(&summaries.total, noise.as_ref());
+        let log_paths = out_path.to_log_output().real_paths()?;
+        let out_paths = out_path.real_paths()?;
+
+        if self.default_tool == ValgrindTool::Cachegrind {
+            let cachegrind_summary = benchmark_summary
+                .cachegrind_summary
+                .insert(CachegrindSummary::new(log_paths, out_paths));
+            cachegrind_summary.add_summaries(
+                &self.command.path,
+                &self.command.args,
+                baselines,
+                summaries,
+                regressions,
+            );
+            cachegrind_summary.cachegrind_run.total.noise = noise;
+            Ok(None)
+        } else {
+            let callgrind_summary = benchmark_summary
+                .callgrind_summary
+                .insert(CallgrindSummary::new(log_paths, out_paths));
+            callgrind_summary.add_summaries(
+                &self.command.path,
+                &self.command.args,
+                baselines,
+                summaries,
+                regressions,
+            );
+            callgrind_summary.callgrind_run.total.noise = noise;
+            callgrind_summary.functions = functions;
+            Ok(Some(callgrind_summary))
+        }
+    }
+
     fn check_and_print_regressions(
         &self,
         metrics_summary: &MetricsSummary,
+        noise: Option<&NoiseSummary>,
     ) -> Vec<super::summary::CallgrindRegression> {
         if let Some(regression_config) = &self.regression_config {
-            regression_config.check_and_print(metrics_summary)
+            regression_config.check_and_print(metrics_summary, noise)
         } else {
             vec![]
         }
@@ -631,39 +743,9 @@ impl Delay {
 }
 
 impl Group {
-    fn run(
-        &self,
-        benchmark: &dyn Benchmark,
-        is_regressed: &mut bool,
-        config: &Config,
-    ) -> Result<()> {
-        let mut summaries: HashMap<String, Vec<BenchmarkSummary>> =
-            HashMap::with_capacity(self.benches.len());
-        for bench in &self.benches {
-            let fail_fast = bench
-                .regression_config
-                .as_ref()
-                .map_or(defaults::REGRESSION_FAIL_FAST, |r| r.fail_fast);
-
-            let summary = benchmark.run(bench, config, self)?;
-            summary.print_and_save(&config.meta.args.output_format)?;
-            summary.check_regression(is_regressed, fail_fast)?;
-
-            if self.compare_by_id && bench.output_format.is_default() {
-                if let Some(id) = &summary.id {
-                    if let Some(sums) = summaries.get_mut(id) {
-                        for sum in sums.iter() {
-                            sum.compare_and_print(id, &summary, &bench.output_format)?;
-                        }
-                        sums.push(summary);
-                    } else {
-                        summaries.insert(id.clone(), vec![summary]);
-                    }
-                }
-            }
-        }
-
-        Ok(())
+    /// Return true if the benchmarks of this group can't be run in parallel with other benchmarks
+    fn is_isolated(&self) -> bool {
+        self.setup.is_some() || self.teardown.is_some()
     }
 }
 
@@ -677,6 +759,7 @@ impl Groups {
         let meta_callgrind_args = meta.args.callgrind_args.clone().unwrap_or_default();
 
         let mut groups = vec![];
+        let mut filtered_out_dirs = BTreeSet::new();
         for binary_benchmark_group in benchmark_groups.groups {
             let group_module_path = module.join(&binary_benchmark_group.id);
             let group_config = global_config
@@ -720,13 +803,64 @@ impl Groups {
                 for (bench_index, binary_benchmark_bench) in
                     binary_benchmark_benches.benches.into_iter().enumerate()
                 {
+                    let full_name = BenchmarkIdentifiers::full_name(
+                        &group
+                            .module_path
+                            .join(&binary_benchmark_bench.function_name),
+                        binary_benchmark_bench.id.as_deref(),
+                    );
+                    if meta.args.is_filtered_out(&BenchmarkIdentifiers {
+                        bench_name: &meta.bench_name,
+                        full_name: &full_name,
+                        group: &group.name,
+                        function: &binary_benchmark_bench.function_name,
+                        id: binary_benchmark_bench.id.as_deref(),
+                    }) {
+                        debug!("Benchmark '{full_name}' is filtered out");
+                        // The output directory is the same for all tools
+                        filtered_out_dirs.insert(
+                            ToolOutputPath::new(
+                                ToolOutputPathKind::Out,
+                                ValgrindTool::Callgrind,
+                                &BaselineKind::Old,
+                                &meta.target_dir,
+                                &group.module_path,
+                                &binary_benchmark_bench.id.as_ref().map_or_else(
+                                    || binary_benchmark_bench.function_name.clone(),
+                                    |id| format!("{}.{id}", binary_benchmark_bench.function_name),
+                                ),
+                            )
+                            .dir,
+                        );
+                        continue;
+                    }
+
                     let config = group_config.clone().update_from_all([
                         binary_benchmark_benches.config.as_ref(),
                         binary_benchmark_bench.config.as_ref(),
                         Some(&binary_benchmark_bench.command.config),
                     ]);
 
-                    let bin_bench = BinBench::new(
+                    let effective_config = meta
+                        .args
+                        .show_config
+                        .then(|| {
+                            EffectiveConfig::new(
+                                full_name.clone(),
+                                &config,
+                                &[
+                                    (Layer::Main, Some(&global_config)),
+                                    (Layer::Group, binary_benchmark_group.config.as_ref()),
+                                    (Layer::Function, binary_benchmark_benches.config.as_ref()),
+                                    (Layer::Bench, binary_benchmark_bench.config.as_ref()),
+                                    (Layer::Command, Some(&binary_benchmark_bench.command.config)),
+                                ],
+                                meta,
+                            )
+                        })
+                        .transpose()?;
+
+                    let mut bin_bench = BinBench::new(
                         meta,
                         &group,
                         config,
@@ -735,30 +869,205 @@ impl Groups {
                         &meta_callgrind_args,
                         binary_benchmark_bench,
                     )?;
+                    bin_bench.effective_config = effective_config;
                     group.benches.push(bin_bench);
                 }
             }
 
-            groups.push(group);
+            if group.benches.is_empty() {
+                debug!("All benchmarks of group '{}' are filtered out", group.name);
+            } else {
+                groups.push(group);
+            }
         }
-        Ok(Self(groups))
+        Ok(Self {
+            groups,
+            filtered_out_dirs,
+        })
+    }
+
+    /// Return the [`EffectiveConfig`]s of all benchmarks
+    fn effective_configs(&self) -> Vec<EffectiveConfig> {
+        self.groups
+            .iter()
+            .flat_map(|group| group.benches.iter())
+            .filter_map(|bench| bench.effective_config.clone())
+            .collect()
+    }
+
+    /// Return the [`BenchmarkInvocations`] of all benchmarks
+    ///
+    /// The invocations use the output paths of the `benchmark`.
+    fn invocations(
+        &self,
+        benchmark: &dyn Benchmark,
+        config: &Config,
+    ) -> Result<Vec<BenchmarkInvocations>> {
+        let mut benchmarks = vec![];
+        for group in &self.groups {
+            for bin_bench in &group.benches {
+                let out_path = benchmark.output_path(bin_bench, config, group);
+                let mut invocations = vec![ToolCommand::new(
+                    bin_bench.default_tool,
+                    &config.meta,
+                    config.meta.args.nocapture,
+                )
+                .to_invocation(
+                    bin_bench.default_tool_config(),
+                    &bin_bench.command.path,
+                    &bin_bench.command.args,
+                    &bin_bench.run_options,
+                    &out_path,
+                )?];
+                for tool_config in bin_bench.tools.0.iter().filter(|t| t.is_enabled) {
+                    invocations.push(
+                        ToolCommand::new(tool_config.tool, &config.meta, NoCapture::False)
+                            .to_invocation(
+                                tool_config.clone(),
+                                &bin_bench.command.path,
+                                &bin_bench.command.args,
+                                &bin_bench.run_options,
+                                &out_path.to_tool_output(tool_config.tool),
+                            )?,
+                    );
+                }
+
+                benchmarks.push(BenchmarkInvocations {
+                    name: BenchmarkIdentifiers::full_name(
+                        &bin_bench.module_path,
+                        bin_bench.id.as_deref(),
+                    ),
+                    invocations,
+                });
+            }
+        }
+
+        Ok(benchmarks)
+    }
+
+    /// Return the [`BenchmarkListing`] of all benchmarks
+    fn listings(&self) -> Vec<BenchmarkListing> {
+        self.groups
+            .iter()
+            .flat_map(|group| {
+                group.benches.iter().map(|bin_bench| {
+                    BenchmarkListing::new(
+                        BenchmarkKind::BinaryBenchmark,
+                        &bin_bench.module_path,
+                        bin_bench.id.as_deref(),
+                        bin_bench.args.as_deref(),
+                        bin_bench.default_tool,
+                        &bin_bench.tools,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Return the directories of the output files of all benchmarks including the benchmarks which
+    /// are filtered out
+    fn all_benchmark_dirs(&self, config: &Config) -> BTreeSet<PathBuf> {
+        let mut dirs = self.benchmark_dirs(config);
+        dirs.extend(self.filtered_out_dirs.iter().cloned());
+        dirs
+    }
+
+    /// Return the directories of the output files of all benchmarks
+    fn benchmark_dirs(&self, config: &Config) -> BTreeSet<PathBuf> {
+        self.groups
+            .iter()
+            .flat_map(|group| {
+                group.benches.iter().map(|bin_bench| {
+                    ToolOutputPath::new(
+                        ToolOutputPathKind::Out,
+                        bin_bench.default_tool,
+                        &BaselineKind::Old,
+                        &config.meta.target_dir,
+                        &group.module_path,
+                        &bin_bench.name(),
+                    )
+                    .dir
+                })
+            })
+            .collect()
     }
 
     /// Run all [`Group`] benchmarks
     ///
+    /// The [`BenchmarkSummary`] of each benchmark is pushed to the `summaries` as soon as the
+    /// benchmark has finished.
+    ///
+    /// With `--jobs` the benchmarks of consecutive groups without setup and teardown functions are
+    /// run in parallel. Benchmarks with a `Sandbox`, setup or teardown function and the benchmarks
+    /// of groups with a setup or teardown function are run alone.
+    ///
     /// # Errors
     ///
     /// Return an [`anyhow::Error`] with sources:
     ///
     /// * [`Error::RegressionError`] if a regression occurred.
-    fn run(&self, benchmark: &dyn Benchmark, config: &Config) -> Result<()> {
+    fn run(
+        &self,
+        benchmark: &dyn Benchmark,
+        config: &Config,
+        summaries: &mut Vec<BenchmarkSummary>,
+    ) -> Result<()> {
         let mut is_regressed = false;
-        for group in &self.0 {
+        // The indices of the `summaries` with the same group and id
+        let mut compare: HashMap<(&str, String), Vec<usize>> = HashMap::new();
+
+        let mut groups = self.groups.iter().peekable();
+        while let Some(group) = groups.next() {
             if let Some(setup) = &group.setup {
                 setup.run(config, &group.module_path)?;
             }
 
-            group.run(benchmark, &mut is_regressed, config)?;
+            let mut benches = group
+                .benches
+                .iter()
+                .map(|bench| (group, bench))
+                .collect::<Vec<_>>();
+            if !group.is_isolated() {
+                while let Some(next) = groups.next_if(|next| !next.is_isolated()) {
+                    benches.extend(next.benches.iter().map(|bench| (next, bench)));
+                }
+            }
+
+            jobs::run_in_order(
+                &benches,
+                config.meta.jobs(),
+                |(group, bench)| benchmark.run(bench, config, group),
+                |(group, bench)| group.is_isolated() || bench.is_isolated(),
+                |(group, bench), summary| {
+                    let summary = summary?;
+                    let fail_fast = bench
+                        .regression_config
+                        .as_ref()
+                        .map_or(defaults::REGRESSION_FAIL_FAST, |r| r.fail_fast);
+                    summary.print_and_save(&config.meta.args.output_format)?;
+                    if let Err(error) = summary.check_regression(&mut is_regressed, fail_fast) {
+                        summaries.push(summary);
+                        return Err(error);
+                    }
+
+                    if group.compare_by_id && bench.output_format.is_default() {
+                        if let Some(id) = &summary.id {
+                            let indices = compare.entry((&group.name, id.clone())).or_default();
+                            for index in indices.iter() {
+                                summaries[*index].compare_and_print(
+                                    id,
+                                    &summary,
+                                    &bench.output_format,
+                                )?;
+                            }
+                            indices.push(summaries.len());
+                        }
+                    }
+
+                    summaries.push(summary);
+                    Ok(())
+                },
+            )?;
 
             if let Some(teardown) = &group.teardown {
                 teardown.run(config, &group.module_path)?;
@@ -777,7 +1086,7 @@ impl Benchmark for LoadBaselineBenchmark {
     fn output_path(&self, bin_bench: &BinBench, config: &Config, group: &Group) -> ToolOutputPath {
         ToolOutputPath::new(
             ToolOutputPathKind::Base(self.loaded_baseline.to_string()),
-            ValgrindTool::Callgrind,
+            bin_bench.default_tool,
             &BaselineKind::Name(self.baseline.clone()),
             &config.meta.target_dir,
             &group.module_path,
@@ -803,7 +1112,6 @@ impl Benchmark for LoadBaselineBenchmark {
 
         let out_path = self.output_path(bin_bench, config, group);
         let old_path = out_path.to_base_path();
-        let log_path = out_path.to_log_output();
 
         let mut benchmark_summary = bin_bench.create_benchmark_summary(
             config,
@@ -812,8 +1120,10 @@ impl Benchmark for LoadBaselineBenchmark {
             header.description(),
         )?;
 
-        let parsed_new = SummaryParser.parse(&out_path)?;
-        let parsed_old = Some(SummaryParser.parse(&old_path)?);
+        let parser = parser_factory(bin_bench.default_tool);
+        let parsed_new = parser.parse(&out_path)?;
+        let parsed_old = Some(parser.parse(&old_path)?);
+        benchmark_summary.baseline_status = BaselineStatus::Compared;
         let summaries = Summaries::new(parsed_new, parsed_old);
 
         VerticalFormatter::new(bin_bench.output_format).print(
@@ -822,24 +1132,18 @@ impl Benchmark for LoadBaselineBenchmark {
             &ToolRun::from(&summaries),
         )?;
 
-        let regressions = bin_bench.check_and_print_regressions(&summaries.total);
-
-        let callgrind_summary = benchmark_summary
-            .callgrind_summary
-            .insert(CallgrindSummary::new(
-                log_path.real_paths()?,
-                out_path.real_paths()?,
-            ));
-
-        callgrind_summary.add_summaries(
-            &bin_bench.command.path,
-            &bin_bench.command.args,
+        let callgrind_summary = bin_bench.add_default_tool_summaries(
+            &mut benchmark_summary,
+            config,
+            &out_path,
             &self.baselines(),
             summaries,
-            regressions,
-        );
+            None,
+        )?;
 
-        if let Some(flamegraph_config) = bin_bench.flamegraph_config.clone() {
+        if let (Some(callgrind_summary), Some(flamegraph_config)) =
+            (callgrind_summary, bin_bench.flamegraph_config.clone())
+        {
             callgrind_summary.flamegraphs = LoadBaselineFlamegraphGenerator {
                 loaded_baseline: self.loaded_baseline.clone(),
                 baseline: self.baseline.clone(),
@@ -852,10 +1156,12 @@ impl Benchmark for LoadBaselineBenchmark {
             )?;
         }
 
-        benchmark_summary.tool_summaries =
-            bin_bench
-                .tools
-                .run_loaded_vs_base(config, &out_path, &bin_bench.output_format)?;
+        benchmark_summary.tool_summaries = bin_bench.tools.run_loaded_vs_base(
+            config,
+            &out_path,
+            bin_bench.regression_config.as_ref(),
+            &bin_bench.output_format,
+        )?;
 
         Ok(benchmark_summary)
     }
@@ -918,11 +1224,73 @@ impl Runner {
     }
 
     fn run(&self) -> Result<()> {
+        if self.config.meta.args.show_config {
+            return effective_config::print(
+                &self.groups.effective_configs(),
+                self.config.meta.args.output_format,
+            );
+        }
+        if self.config.meta.args.dry_run {
+            return dry_run::print(
+                &self
+                    .groups
+                    .invocations(self.benchmark.as_ref(), &self.config)?,
+                self.config.meta.args.output_format,
+            );
+        }
+        if self.config.meta.args.list {
+            return list::print(
+                &self.groups.listings(),
+                self.config.meta.args.list_format,
+                self.config.meta.args.output_format,
+            );
+        }
+        if let Some(mode) = self.config.meta.args.clean {
+            return clean::run(
+                &self.config,
+                mode,
+                &self.groups.all_benchmark_dirs(&self.config),
+                true,
+            );
+        }
+        if let Some(last) = self.config.meta.args.show_history {
+            return history::show(
+                &self.groups.benchmark_dirs(&self.config),
+                last.get(),
+                self.config.meta.args.history_threshold,
+            );
+        }
+
+        if self.groups.groups.is_empty() {
+            debug!(
+                "All benchmarks of '{}' are filtered out",
+                self.config.bench_file.display()
+            );
+            return Ok(());
+        }
+
         if let Some(setup) = &self.setup {
             setup.run(&self.config, &self.config.module_path)?;
         }
 
-        self.groups.run(self.benchmark.as_ref(), &self.config)?;
+        let mut summaries = vec![];
+        let result = self
+            .groups
+            .run(self.benchmark.as_ref(), &self.config, &mut summaries);
+        report::save_reports(&self.config, &summaries)?;
+        if self.config.meta.args.save_history == Some(true)
+            && self.config.meta.args.load_baseline.is_none()
+        {
+            history::save(
+                &self.config,
+                self.benchmark.baselines().0.as_deref(),
+                &summaries,
+            )?;
+        }
+        if let Some(format) = self.config.meta.args.save_summary {
+            RunSummary::save(&self.config.meta, format, summaries)?;
+        }
+        result?;
 
         if let Some(teardown) = &self.teardown {
             teardown.run(&self.config, &self.config.module_path)?;
@@ -935,7 +1303,7 @@ impl Benchmark for SaveBaselineBenchmark {
     fn output_path(&self, bin_bench: &BinBench, config: &Config, group: &Group) -> ToolOutputPath {
         ToolOutputPath::new(
             ToolOutputPathKind::Base(self.baseline.to_string()),
-            ValgrindTool::Callgrind,
+            bin_bench.default_tool,
             &BaselineKind::Name(self.baseline.clone()),
             &config.meta.target_dir,
             &group.module_path,
@@ -959,26 +1327,14 @@ impl Benchmark for SaveBaselineBenchmark {
         let header = BinaryBenchmarkHeader::new(&config.meta, bin_bench);
         header.print();
 
-        let callgrind_command = ToolCommand::new(
-            ValgrindTool::Callgrind,
-            &config.meta,
-            config.meta.args.nocapture,
-        );
-
-        let tool_config = ToolConfig::new(
-            ValgrindTool::Callgrind,
-            true,
-            bin_bench.callgrind_args.clone(),
-            None,
-        );
-
         let out_path = self.output_path(bin_bench, config, group);
         out_path.init()?;
 
+        let parser = parser_factory(bin_bench.default_tool);
         let parsed_old = out_path
             .exists()
             .then(|| {
-                SummaryParser
+                parser
                     .parse(&out_path)
                     .and_then(|parsed| out_path.clear().map(|()| parsed))
             })
@@ -994,76 +1350,40 @@ impl Benchmark for SaveBaselineBenchmark {
             header.description(),
         )?;
 
-        let sandbox = bin_bench
-            .sandbox
-            .as_ref()
-            .map(|sandbox| Sandbox::setup(sandbox, &config.meta))
-            .transpose()?;
+        let (output, noise) =
+            noise::run_repeated(config.meta.args.repeat, &out_path, parser.as_ref(), || {
+                bin_bench.run_default_tool(config, &out_path)
+            })?;
 
-        let mut child = bin_bench
-            .setup
-            .as_ref()
-            .map_or(Ok(None), |setup| setup.run(config, &bin_bench.module_path))?;
-
-        if let Some(delay) = &bin_bench.command.delay {
-            if let Err(error) = delay.run() {
-                if let Some(mut child) = child.take() {
-                    // To avoid zombies
-                    child.kill()?;
-                    return Err(error);
-                }
-            }
-        }
-
-        let output = callgrind_command.run(
-            tool_config,
-            &bin_bench.command.path,
-            &bin_bench.command.args,
-            bin_bench.run_options.clone(),
-            &out_path,
-            &bin_bench.module_path,
-            child,
-        )?;
-
-        if let Some(teardown) = &bin_bench.teardown {
-            teardown.run(config, &bin_bench.module_path)?;
-        }
-
-        bin_bench.print_nocapture_footer(config.meta.args.nocapture);
-
-        if let Some(sandbox) = sandbox {
-            sandbox.reset()?;
-        }
-
-        let parsed_new = SummaryParser.parse(&out_path)?;
+        let parsed_new = parser.parse(&out_path)?;
+        benchmark_summary.baseline_status = if parsed_old.is_some() {
+            BaselineStatus::Compared
+        } else {
+            BaselineStatus::NotRequested
+        };
         let summaries = Summaries::new(parsed_new, parsed_old);
         VerticalFormatter::new(bin_bench.output_format).print(
             config,
             self.baselines(),
             &ToolRun::from(&summaries),
         )?;
+        noise::print(noise.as_ref(), &bin_bench.output_format);
 
         output.dump_log(log::Level::Info);
         log_path.dump_log(log::Level::Info, &mut stderr())?;
 
-        let regressions = bin_bench.check_and_print_regressions(&summaries.total);
-
-        let callgrind_summary = benchmark_summary
-            .callgrind_summary
-            .insert(CallgrindSummary::new(
-                log_path.real_paths()?,
-                out_path.real_paths()?,
-            ));
-
-        callgrind_summary.add_summaries(
-            &bin_bench.command.path,
-            &bin_bench.command.args,
+        let callgrind_summary = bin_bench.add_default_tool_summaries(
+            &mut benchmark_summary,
+            config,
+            &out_path,
             &self.baselines(),
             summaries,
-            regressions,
-        );
+            noise,
+        )?;
 
-        if let Some(flamegraph_config) = bin_bench.flamegraph_config.clone() {
+        if let (Some(callgrind_summary), Some(flamegraph_config)) =
+            (callgrind_summary, bin_bench.flamegraph_config.clone())
+        {
             callgrind_summary.flamegraphs = SaveBaselineFlamegraphGenerator {
                 baseline: self.baseline.clone(),
             }
@@ -1087,6 +1407,7 @@ impl Benchmark for SaveBaselineBenchmark {
             bin_bench.setup.as_ref(),
             bin_bench.teardown.as_ref(),
             bin_bench.command.delay.as_ref(),
+            bin_bench.regression_config.as_ref(),
             &bin_bench.output_format,
         )?;
 
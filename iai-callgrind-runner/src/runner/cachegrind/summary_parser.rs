@@ -0,0 +1,154 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use log::{debug, trace};
+
+use crate::error::Error;
+use crate::runner::callgrind::model::Metrics;
+use crate::runner::callgrind::parser::{CallgrindParser, CallgrindProperties};
+use crate::runner::tool::logfile_parser;
+
+/// Parse the `summary:` line in the cachegrind output
+///
+/// The cachegrind output format is a subset of the callgrind output format but without a `pid:`
+/// header line. The pid is extracted from the log file which belongs to the cachegrind output
+/// file instead.
+///
+/// The format is described [here](https://valgrind.org/docs/manual/cg-manual.html)
+#[derive(Debug)]
+pub struct SummaryParser;
+
+impl SummaryParser {
+    /// Return the path to the log file which belongs to the cachegrind output file at `path`
+    ///
+    /// The sanitized output file names have the form `cachegrind.<name>[.<pid>].out[.<base>]` and
+    /// the log file names differ only in `log` instead of `out`.
+    fn log_path(path: &Path) -> Result<PathBuf> {
+        let file_name = path
+            .file_name()
+            .and_then(|f| f.to_str())
+            .ok_or_else(|| anyhow!("Invalid cachegrind output file name: '{}'", path.display()))?;
+
+        let index = file_name
+            .rfind(".out")
+            .ok_or_else(|| anyhow!("Invalid cachegrind output file name: '{}'", path.display()))?;
+
+        Ok(path.with_file_name(format!(
+            "{}.log{}",
+            &file_name[..index],
+            &file_name[index + 4..]
+        )))
+    }
+}
+
+impl CallgrindParser for SummaryParser {
+    type Output = Metrics;
+
+    fn parse_single(&self, path: &Path) -> Result<(CallgrindProperties, Self::Output)> {
+        debug!(
+            "Parsing cachegrind output file '{}' for a summary",
+            path.display()
+        );
+
+        let file = File::open(path).with_context(|| {
+            format!("Error opening cachegrind output file '{}'", path.display())
+        })?;
+
+        let mut metrics_prototype: Option<Metrics> = None;
+        let mut desc: Vec<String> = vec![];
+        let mut cmd: Option<String> = None;
+        let mut metrics = None;
+
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+
+            match line.split_once(':').map(|(k, v)| (k.trim(), v.trim())) {
+                Some(("desc", value)) => {
+                    trace!("Using description '{value}' from line: '{line}'");
+                    desc.push(value.to_owned());
+                }
+                Some(("cmd", value)) => {
+                    trace!("Using cmd '{value}' from line: '{line}'");
+                    cmd = Some(value.to_owned());
+                }
+                Some(("events", events)) => {
+                    trace!("Using events '{events}' from line: '{line}'");
+                    metrics_prototype = Some(events.split_ascii_whitespace().collect());
+                }
+                Some(("summary", summary)) => {
+                    trace!("Found line with summary: '{line}'");
+
+                    let mut inner = metrics_prototype.clone().ok_or_else(|| {
+                        Error::ParseError((
+                            path.to_owned(),
+                            "Header field 'events' must be present before the summary".to_owned(),
+                        ))
+                    })?;
+                    inner.add_iter_str(summary.split_ascii_whitespace())?;
+                    metrics = Some(inner);
+                    break;
+                }
+                // The cost lines and the `fl=`, `fn=` lines are not of interest for the summary.
+                // Function names can contain `::`, so these lines can end up here, too.
+                None | Some(_) => {}
+            }
+        }
+
+        let Some(metrics) = metrics else {
+            return Err(
+                Error::ParseError((path.to_owned(), "No summary line found".to_owned())).into(),
+            );
+        };
+
+        let log_path = Self::log_path(path)?;
+        let log_file = File::open(&log_path).with_context(|| {
+            format!(
+                "Error opening log file '{}' of cachegrind output file",
+                log_path.display()
+            )
+        })?;
+        let header = logfile_parser::parse_header(
+            &log_path,
+            BufReader::new(log_file)
+                .lines()
+                .map(std::result::Result::unwrap)
+                .skip_while(|l| l.trim().is_empty()),
+        )?;
+
+        let properties = CallgrindProperties {
+            metrics_prototype: metrics_prototype.unwrap_or_default(),
+            pid: Some(header.pid),
+            desc,
+            cmd: cmd.or(Some(header.command)),
+            ..Default::default()
+        };
+
+        Ok((properties, metrics))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::simple("cachegrind.some.out", "cachegrind.some.log")]
+    #[case::with_pid("cachegrind.some.1234.out", "cachegrind.some.1234.log")]
+    #[case::old("cachegrind.some.out.old", "cachegrind.some.log.old")]
+    #[case::base(
+        "cachegrind.some.1234.out.base@default",
+        "cachegrind.some.1234.log.base@default"
+    )]
+    #[case::name_with_out("cachegrind.some.out.out", "cachegrind.some.out.log")]
+    fn test_cachegrind_log_path(#[case] out_path: &str, #[case] expected: &str) {
+        let dir = PathBuf::from("/some/dir");
+        assert_eq!(
+            SummaryParser::log_path(&dir.join(out_path)).unwrap(),
+            dir.join(expected)
+        );
+    }
+}
@@ -0,0 +1,603 @@
+//! Annotate the top regressed functions of a callgrind run at the source line or instruction level
+//!
+//! Similar to `callgrind_annotate`, the self costs of the cost lines in the callgrind output files
+//! are collected per position. Depending on the `--dump-line` (the default) and `--dump-instr`
+//! options of callgrind, a position is a source line, an instruction address or both. The functions
+//! which gained the most cost compared to the old or base run are written together with the
+//! positions with a changed cost into an annotated text file next to the callgrind output files.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use log::debug;
+
+use super::model::{Metrics, PositionType};
+use super::parser::{parse_header, CallgrindParser, CallgrindProperties};
+use crate::api::EventKind;
+use crate::error::Error;
+use crate::runner::tool::ToolOutputPath;
+use crate::util::{make_absolute, make_relative, percentage_diff, EitherOrBoth};
+
+/// The width of the columns with the costs
+const COST_WIDTH: usize = 14;
+
+/// A function identified by its object file, source file and name
+#[derive(Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct FunctionId {
+    /// The object file (`ob=`)
+    pub obj: Option<String>,
+    /// The source file (`fl=`)
+    pub file: Option<String>,
+    /// The function name (`fn=`)
+    pub func: String,
+}
+
+/// The position of a cost line
+///
+/// The `file` differs from the file of the function if the code was inlined from another file.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Position {
+    /// The source file if known
+    pub file: Option<String>,
+    /// The source line if `--dump-line=yes` and the line is known
+    pub line: Option<u64>,
+    /// The instruction address if `--dump-instr=yes`
+    pub instr: Option<u64>,
+}
+
+/// The self costs of all functions per [`Position`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct AnnotateMap(pub HashMap<FunctionId, BTreeMap<Position, Metrics>>);
+
+/// Parse the self costs of the cost lines of a callgrind output file into an [`AnnotateMap`]
+///
+/// The costs of calls to other functions (the cost line after a `calls=` line) are not part of
+/// the self costs and are skipped.
+#[derive(Debug, Default, Clone, Copy)]
+pub struct AnnotateParser;
+
+/// A function with its self cost and the self costs per [`Position`] of the new and old run
+#[derive(Debug, Clone, PartialEq)]
+pub struct AnnotatedFunction {
+    /// The id of the function
+    pub id: FunctionId,
+    /// The total self cost of the function
+    pub cost: EitherOrBoth<u64>,
+    /// The positions sorted by source line and instruction address
+    pub positions: Vec<(Position, EitherOrBoth<u64>)>,
+}
+
+impl AnnotateMap {
+    /// Add the costs of the `other` map to this map
+    pub fn add_mut(&mut self, other: Self) {
+        for (id, positions) in other.0 {
+            let entry = self.0.entry(id).or_default();
+            for (position, metrics) in positions {
+                entry
+                    .entry(position)
+                    .and_modify(|m| m.add(&metrics))
+                    .or_insert(metrics);
+            }
+        }
+    }
+
+    /// Return the self cost of the `event_kind` of each position of the function with the `id`
+    fn costs(&self, id: &FunctionId, event_kind: EventKind) -> BTreeMap<&Position, u64> {
+        self.0
+            .get(id)
+            .map(|positions| {
+                positions
+                    .iter()
+                    .map(|(p, m)| (p, m.metric_by_kind(&event_kind).unwrap_or_default()))
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+}
+
+impl CallgrindParser for AnnotateParser {
+    type Output = AnnotateMap;
+
+    fn parse_single(&self, path: &Path) -> Result<(CallgrindProperties, Self::Output)> {
+        let mut iter = BufReader::new(File::open(path)?)
+            .lines()
+            .map(Result::unwrap);
+        let config = parse_header(&mut iter)
+            .map_err(|error| Error::ParseError((path.to_owned(), error.to_string())))?;
+
+        let mut map = AnnotateMap::default();
+        let mut obj = None;
+        let mut file = None;
+        let mut inline_file = None;
+        let mut func = None;
+        let mut is_call_cost = false;
+
+        let mut is_header = true;
+        for line in iter {
+            let line = line.trim();
+
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+
+            // The first line which can be split around '=' is a non header line
+            let split = if is_header {
+                if let Some(split) = line.split_once('=') {
+                    is_header = false;
+                    Some(split)
+                } else {
+                    continue;
+                }
+            } else {
+                line.split_once('=')
+            };
+
+            match split {
+                Some(("ob", value)) => obj = Some(value.to_owned()),
+                Some(("fl", value)) => {
+                    file = Some(value.to_owned());
+                    inline_file = None;
+                }
+                Some(("fn", value)) => {
+                    func = Some(value.to_owned());
+                    inline_file = None;
+                }
+                Some(("fi" | "fe", value)) => inline_file = Some(value.to_owned()),
+                Some(("calls", _)) => is_call_cost = true,
+                None if line.starts_with(|c: char| c.is_ascii_digit()) => {
+                    if is_call_cost {
+                        is_call_cost = false;
+                        continue;
+                    }
+
+                    let mut fields = line.split_ascii_whitespace();
+                    let mut positions = config.positions_prototype.clone();
+                    positions.set_iter_str(fields.by_ref().take(positions.len()));
+
+                    let mut metrics = config.metrics_prototype.clone();
+                    metrics.add_iter_str(fields)?;
+
+                    let id = FunctionId {
+                        obj: obj.clone(),
+                        file: file.clone(),
+                        func: func
+                            .clone()
+                            .ok_or_else(|| anyhow!("Missing function for cost line: '{line}'"))?,
+                    };
+                    let position = Position {
+                        file: inline_file.clone().or_else(|| file.clone()),
+                        line: positions.get(PositionType::Line).filter(|l| *l != 0),
+                        instr: positions.get(PositionType::Instr),
+                    };
+
+                    map.0
+                        .entry(id)
+                        .or_default()
+                        .entry(position)
+                        .and_modify(|m| m.add(&metrics))
+                        .or_insert(metrics);
+                }
+                // The called function and jumps are not needed for the self costs
+                Some(("cob" | "cfi" | "cfl" | "cfn" | "jump" | "jcnd" | "jfi" | "jfn", _)) => {}
+                None if line.starts_with("totals:") || line.starts_with("summary:") => {}
+                Some(_) | None => {
+                    return Err(anyhow!("Malformed line: '{line}'"));
+                }
+            }
+        }
+
+        Ok((config, map))
+    }
+}
+
+/// Parse and sum up the self costs of all callgrind output files of the `output_path`
+///
+/// If there are no output files, the returned `AnnotateMap` is empty. If the metrics can be
+/// summarized (`--cache-sim=yes`), the derived metrics like [`EventKind::EstimatedCycles`] are
+/// added.
+pub fn parse(output_path: &ToolOutputPath) -> Result<AnnotateMap> {
+    let mut map = AnnotateMap::default();
+    if !output_path.exists() {
+        return Ok(map);
+    }
+
+    debug!(
+        "Parsing the cost lines of the callgrind output files '{}'",
+        output_path.to_path().display()
+    );
+    for (_, _, other) in AnnotateParser.parse(output_path)? {
+        map.add_mut(other);
+    }
+
+    for metrics in map.0.values_mut().flat_map(BTreeMap::values_mut) {
+        if metrics.can_summarize() {
+            metrics
+                .make_summary()
+                .map_err(|error| anyhow!("Failed calculating summary events: {error}"))?;
+        }
+    }
+
+    Ok(map)
+}
+
+/// Return the `top` functions which gained the most self cost of the `event_kind`
+///
+/// If `old` is empty, the `top` functions with the highest self cost are returned with all their
+/// positions. Otherwise, only functions with a higher self cost than in the `old` run are returned
+/// with the positions whose cost changed.
+pub fn annotate(
+    new: &AnnotateMap,
+    old: &AnnotateMap,
+    event_kind: EventKind,
+    top: usize,
+) -> Vec<AnnotatedFunction> {
+    let has_old = !old.0.is_empty();
+
+    let mut functions = new
+        .0
+        .keys()
+        .map(|id| {
+            let new_cost = new.costs(id, event_kind).values().sum::<u64>();
+            let old_cost = old.costs(id, event_kind).values().sum::<u64>();
+            (id, new_cost, old_cost)
+        })
+        .filter(|(_, new_cost, old_cost)| {
+            if has_old {
+                new_cost > old_cost
+            } else {
+                *new_cost > 0
+            }
+        })
+        .collect::<Vec<_>>();
+
+    functions.sort_by(|(a_id, a_new, a_old), (b_id, b_new, b_old)| {
+        (b_new - b_old)
+            .cmp(&(a_new - a_old))
+            .then_with(|| b_new.cmp(a_new))
+            .then_with(|| a_id.cmp(b_id))
+    });
+
+    functions
+        .into_iter()
+        .take(top)
+        .map(|(id, new_cost, old_cost)| {
+            let new_costs = new.costs(id, event_kind);
+            let old_costs = old.costs(id, event_kind);
+
+            let mut positions = new_costs.keys().chain(old_costs.keys()).collect::<Vec<_>>();
+            positions.sort();
+            positions.dedup();
+
+            let positions = positions
+                .into_iter()
+                .filter_map(|position| {
+                    let new_cost = new_costs.get(position).copied().unwrap_or_default();
+                    if has_old {
+                        let old_cost = old_costs.get(position).copied().unwrap_or_default();
+                        (new_cost != old_cost)
+                            .then(|| ((*position).clone(), EitherOrBoth::Both(new_cost, old_cost)))
+                    } else {
+                        (new_cost != 0).then(|| ((*position).clone(), EitherOrBoth::Left(new_cost)))
+                    }
+                })
+                .collect();
+
+            AnnotatedFunction {
+                id: id.clone(),
+                cost: if has_old {
+                    EitherOrBoth::Both(new_cost, old_cost)
+                } else {
+                    EitherOrBoth::Left(new_cost)
+                },
+                positions,
+            }
+        })
+        .collect()
+}
+
+/// The source files of the project read on demand
+#[derive(Debug, Default)]
+struct Sources {
+    project_root: PathBuf,
+    files: HashMap<String, Option<Vec<String>>>,
+}
+
+impl Sources {
+    /// Return the source line at the `position` if the source file can be read
+    fn get(&mut self, position: &Position) -> Option<&str> {
+        let (file, line) = (position.file.as_ref()?, position.line?);
+        let lines = self
+            .files
+            .entry(file.clone())
+            .or_insert_with(|| {
+                let path = make_absolute(&self.project_root, file);
+                std::fs::read_to_string(path)
+                    .ok()
+                    .map(|content| content.lines().map(ToOwned::to_owned).collect())
+            })
+            .as_ref()?;
+
+        lines
+            .get(usize::try_from(line).ok()?.checked_sub(1)?)
+            .map(|l| l.trim())
+    }
+}
+
+/// Format the `position` with the file relative to the `project_root`
+fn format_position(project_root: &Path, position: &Position) -> String {
+    let file = position.file.as_ref().map_or_else(
+        || "???".to_owned(),
+        |file| make_relative(project_root, file).display().to_string(),
+    );
+    match (position.instr, position.line) {
+        (Some(instr), Some(line)) => format!("{instr:#x} {file}:{line}"),
+        (Some(instr), None) => format!("{instr:#x} {file}"),
+        (None, Some(line)) => format!("{file}:{line}"),
+        (None, None) => file,
+    }
+}
+
+/// Format the signed difference between `new` and `old`
+fn format_diff(new: u64, old: u64) -> String {
+    if new >= old {
+        format!("+{}", new - old)
+    } else {
+        format!("-{}", old - new)
+    }
+}
+
+/// Write the annotated `functions` to the `writer`
+pub fn write<W: Write>(
+    mut writer: W,
+    functions: &[AnnotatedFunction],
+    event_kind: EventKind,
+    paths: &EitherOrBoth<PathBuf>,
+    project_root: &Path,
+) -> Result<()> {
+    let separator = "-".repeat(80);
+    writeln!(writer, "{separator}")?;
+    writeln!(writer, "Annotated self costs of the event '{event_kind}'")?;
+    if let Some(new) = paths.left() {
+        writeln!(
+            writer,
+            "New: {}",
+            make_relative(project_root, new).display()
+        )?;
+    }
+    if let Some(old) = paths.right() {
+        writeln!(
+            writer,
+            "Old: {}",
+            make_relative(project_root, old).display()
+        )?;
+    }
+    writeln!(writer, "{separator}")?;
+
+    if functions.is_empty() {
+        writeln!(writer)?;
+        writeln!(writer, "No function gained cost")?;
+        return Ok(());
+    }
+
+    let mut sources = Sources {
+        project_root: project_root.to_owned(),
+        ..Default::default()
+    };
+    for function in functions {
+        writeln!(writer)?;
+        writeln!(writer, "Function: {}", function.id.func)?;
+        if let Some(file) = &function.id.file {
+            writeln!(
+                writer,
+                "File:     {}",
+                make_relative(project_root, file).display()
+            )?;
+        }
+        if let Some(obj) = &function.id.obj {
+            writeln!(
+                writer,
+                "Object:   {}",
+                make_relative(project_root, obj).display()
+            )?;
+        }
+        match function.cost {
+            EitherOrBoth::Both(new, old) => writeln!(
+                writer,
+                "{event_kind}: {new}|{old} ({} {:+.5}%)",
+                format_diff(new, old),
+                percentage_diff(new, old)
+            )?,
+            EitherOrBoth::Left(new) | EitherOrBoth::Right(new) => {
+                writeln!(writer, "{event_kind}: {new}")?;
+            }
+        }
+        writeln!(writer, "{separator}")?;
+
+        match function.cost {
+            EitherOrBoth::Both(..) => writeln!(
+                writer,
+                "{:>COST_WIDTH$}{:>COST_WIDTH$}{:>COST_WIDTH$}  Position",
+                "New", "Old", "Diff"
+            )?,
+            _ => writeln!(writer, "{:>COST_WIDTH$}  Position", "Cost")?,
+        }
+        for (position, cost) in &function.positions {
+            let costs = match cost {
+                EitherOrBoth::Both(new, old) => format!(
+                    "{new:>COST_WIDTH$}{old:>COST_WIDTH$}{:>COST_WIDTH$}",
+                    format_diff(*new, *old)
+                ),
+                EitherOrBoth::Left(cost) | EitherOrBoth::Right(cost) => {
+                    format!("{cost:>COST_WIDTH$}")
+                }
+            };
+            let position_string = format_position(project_root, position);
+            match sources.get(position) {
+                Some(source) => writeln!(writer, "{costs}  {position_string}: {source}")?,
+                None => writeln!(writer, "{costs}  {position_string}")?,
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Annotate the `top` functions of the `output_path` and write them into the annotate file
+///
+/// The functions are compared with the base of the `output_path` and ranked by the self cost of
+/// the `event_kind`. Returns the path to the written file.
+pub fn annotate_and_write(
+    output_path: &ToolOutputPath,
+    project_root: &Path,
+    top: usize,
+    event_kind: EventKind,
+) -> Result<PathBuf> {
+    let base_path = output_path.to_base_path();
+    let functions = annotate(&parse(output_path)?, &parse(&base_path)?, event_kind, top);
+
+    let paths = if base_path.exists() {
+        EitherOrBoth::Both(output_path.to_path(), base_path.to_path())
+    } else {
+        EitherOrBoth::Left(output_path.to_path())
+    };
+
+    let path = output_path.to_annotate_path();
+    debug!("Writing the annotated functions to '{}'", path.display());
+    let file = File::create(&path)
+        .with_context(|| format!("Error creating annotate file '{}'", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write(&mut writer, &functions, event_kind, &paths, project_root)
+        .and_then(|()| writer.flush().map_err(Into::into))
+        .with_context(|| format!("Error writing annotate file '{}'", path.display()))?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn position(line: Option<u64>, instr: Option<u64>) -> Position {
+        Position {
+            file: Some("src/lib.rs".to_owned()),
+            line,
+            instr,
+        }
+    }
+
+    fn map_fixture(functions: &[(&str, &[(u64, u64)])]) -> AnnotateMap {
+        AnnotateMap(
+            functions
+                .iter()
+                .map(|(func, lines)| {
+                    (
+                        FunctionId {
+                            obj: None,
+                            file: Some("src/lib.rs".to_owned()),
+                            func: (*func).to_owned(),
+                        },
+                        lines
+                            .iter()
+                            .map(|(line, cost)| {
+                                (
+                                    position(Some(*line), None),
+                                    Metrics::with_metric_kinds([(EventKind::Ir, *cost)]),
+                                )
+                            })
+                            .collect(),
+                    )
+                })
+                .collect(),
+        )
+    }
+
+    #[rstest]
+    #[case::line_and_instr(Some(12), Some(0x1a2b), "0x1a2b src/lib.rs:12")]
+    #[case::only_instr(None, Some(0x1a2b), "0x1a2b src/lib.rs")]
+    #[case::only_line(Some(12), None, "src/lib.rs:12")]
+    #[case::nothing(None, None, "src/lib.rs")]
+    fn test_format_position(
+        #[case] line: Option<u64>,
+        #[case] instr: Option<u64>,
+        #[case] expected: &str,
+    ) {
+        assert_eq!(
+            format_position(Path::new("/root"), &position(line, instr)),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_annotate_without_old() {
+        let new = map_fixture(&[
+            ("a", &[(1, 10), (2, 0)]),
+            ("b", &[(1, 30)]),
+            ("c", &[(1, 0)]),
+        ]);
+        let actual = annotate(&new, &AnnotateMap::default(), EventKind::Ir, 5);
+
+        assert_eq!(
+            actual
+                .iter()
+                .map(|f| (f.id.func.as_str(), f.cost.clone(), f.positions.len()))
+                .collect::<Vec<_>>(),
+            [
+                ("b", EitherOrBoth::Left(30), 1),
+                ("a", EitherOrBoth::Left(10), 1)
+            ]
+        );
+    }
+
+    #[test]
+    fn test_annotate_with_old() {
+        let new = map_fixture(&[
+            ("a", &[(1, 10), (2, 20), (3, 5)]),
+            ("b", &[(1, 100)]),
+            ("c", &[(1, 5)]),
+        ]);
+        let old = map_fixture(&[("a", &[(1, 10), (2, 5)]), ("b", &[(1, 200)])]);
+        let actual = annotate(&new, &old, EventKind::Ir, 5);
+
+        assert_eq!(
+            actual,
+            vec![
+                AnnotatedFunction {
+                    id: new.0.keys().find(|id| id.func == "a").unwrap().clone(),
+                    cost: EitherOrBoth::Both(35, 15),
+                    positions: vec![
+                        (position(Some(2), None), EitherOrBoth::Both(20, 5)),
+                        (position(Some(3), None), EitherOrBoth::Both(5, 0)),
+                    ]
+                },
+                AnnotatedFunction {
+                    id: new.0.keys().find(|id| id.func == "c").unwrap().clone(),
+                    cost: EitherOrBoth::Both(5, 0),
+                    positions: vec![(position(Some(1), None), EitherOrBoth::Both(5, 0))]
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_annotate_when_top_is_smaller() {
+        let new = map_fixture(&[("a", &[(1, 10)]), ("b", &[(1, 30)])]);
+        let actual = annotate(&new, &AnnotateMap::default(), EventKind::Ir, 1);
+
+        assert_eq!(actual.len(), 1);
+        assert_eq!(actual[0].id.func, "b");
+    }
+
+    #[rstest]
+    #[case::gain(10, 5, "+5")]
+    #[case::loss(5, 10, "-5")]
+    #[case::equal(5, 5, "+0")]
+    fn test_format_diff(#[case] new: u64, #[case] old: u64, #[case] expected: &str) {
+        assert_eq!(format_diff(new, old), expected);
+    }
+}
@@ -198,6 +198,7 @@ impl From<Args> for tool::args::ToolArgs {
             verbose: value.verbose,
             trace_children: value.trace_children,
             fair_sched: value.fair_sched,
+            xml: false,
             other,
         }
     }
@@ -52,6 +52,7 @@ impl FlamegraphMap {
             // a heavy id which needs to be cloned, although it is already present in the map.
             if let Some(value) = self.0.map.get_mut(other_id) {
                 value.metrics.add(&other_value.metrics);
+                value.exclusive.add(&other_value.exclusive);
             } else {
                 self.0.map.insert(other_id.clone(), other_value.clone());
             }
@@ -0,0 +1,236 @@
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Result};
+use log::debug;
+
+use super::hashmap_parser::{CallgrindMap, HashMapParser, Id, SourcePath, Value};
+use super::parser::CallgrindParser;
+use crate::api::EventKind;
+use crate::runner::format::{OutputFormat, VerticalFormatter};
+use crate::runner::summary::{FunctionSummary, MetricsSummary};
+use crate::runner::tool::ToolOutputPath;
+use crate::util::EitherOrBoth;
+
+/// Parse the per-function metrics of all callgrind output files of the `output_path`
+///
+/// The functions of all output files (processes, threads and parts) with the same name, source
+/// file and object file are summed up. If there are no output files, the returned `CallgrindMap`
+/// is empty. If the metrics can be summarized (`--cache-sim=yes`), the derived metrics like
+/// [`EventKind::EstimatedCycles`] are added.
+pub fn parse_functions(output_path: &ToolOutputPath, project_root: &Path) -> Result<CallgrindMap> {
+    let mut map = CallgrindMap::default();
+    if !output_path.exists() {
+        return Ok(map);
+    }
+
+    debug!(
+        "Parsing the functions of the callgrind output files '{}'",
+        output_path.to_path().display()
+    );
+
+    let parser = HashMapParser {
+        sentinel: None,
+        project_root: project_root.to_owned(),
+    };
+    for (_, _, other) in parser.parse(output_path)? {
+        map.add_mut(&other);
+    }
+
+    for value in map.map.values_mut() {
+        if value.metrics.can_summarize() {
+            value
+                .metrics
+                .make_summary()
+                .and_then(|()| value.exclusive.make_summary())
+                .map_err(|error| anyhow!("Failed calculating summary events: {error}"))?;
+        }
+    }
+
+    Ok(map)
+}
+
+/// Return the `top` [`FunctionSummary`]s of the `new` and `old` functions
+///
+/// The functions are sorted by the absolute difference in the exclusive metric of the `event_kind`
+/// between the new and old function. A missing function counts as zero, so without `old`
+/// functions the `new` functions are sorted by their exclusive metric.
+pub fn summarize_functions(
+    new: &CallgrindMap,
+    old: &CallgrindMap,
+    event_kind: EventKind,
+    top: usize,
+) -> Vec<FunctionSummary> {
+    let mut functions = new
+        .map
+        .keys()
+        .chain(old.map.keys().filter(|id| !new.map.contains_key(*id)))
+        .map(|id| {
+            let values = match (new.map.get(id), old.map.get(id)) {
+                (Some(new), Some(old)) => EitherOrBoth::Both(new, old),
+                (Some(new), None) => EitherOrBoth::Left(new),
+                (None, Some(old)) => EitherOrBoth::Right(old),
+                (None, None) => unreachable!("The id is either in new, old or both"),
+            };
+
+            let costs = values.as_ref().map(|value| {
+                value
+                    .exclusive
+                    .metric_by_kind(&event_kind)
+                    .unwrap_or_default()
+            });
+            let (new_cost, old_cost) = match costs {
+                EitherOrBoth::Left(new) => (new, 0),
+                EitherOrBoth::Right(old) => (0, old),
+                EitherOrBoth::Both(new, old) => (new, old),
+            };
+
+            (new_cost.abs_diff(old_cost), new_cost, id, values)
+        })
+        .collect::<Vec<_>>();
+
+    functions.sort_by(|(a_diff, a_cost, a_id, _), (b_diff, b_cost, b_id, _)| {
+        b_diff
+            .cmp(a_diff)
+            .then_with(|| b_cost.cmp(a_cost))
+            .then_with(|| a_id.func.cmp(&b_id.func))
+            .then_with(|| a_id.file.cmp(&b_id.file))
+            .then_with(|| a_id.obj.cmp(&b_id.obj))
+    });
+
+    functions
+        .into_iter()
+        .take(top)
+        .map(|(_, _, id, values)| to_function_summary(id, &values))
+        .collect()
+}
+
+/// Summarize the top functions of the `output_path` and its base and print them
+///
+/// The amount of functions and the [`EventKind`] to sort the functions by are taken from the
+/// `output_format`. If no functions are requested, nothing is parsed and the returned
+/// `FunctionSummary`s are empty.
+pub fn summarize_and_print(
+    output_path: &ToolOutputPath,
+    project_root: &Path,
+    output_format: &OutputFormat,
+) -> Result<Vec<FunctionSummary>> {
+    if output_format.show_functions == 0 {
+        return Ok(vec![]);
+    }
+
+    let functions = summarize_functions(
+        &parse_functions(output_path, project_root)?,
+        &parse_functions(&output_path.to_base_path(), project_root)?,
+        output_format.sort_functions_by,
+        output_format.show_functions,
+    );
+
+    if output_format.is_default() && !functions.is_empty() {
+        let mut formatter = VerticalFormatter::new(*output_format);
+        formatter.format_functions(&functions);
+        formatter.print_buffer();
+    }
+
+    Ok(functions)
+}
+
+fn to_function_summary(id: &Id, values: &EitherOrBoth<&Value>) -> FunctionSummary {
+    FunctionSummary {
+        function: id.func.clone(),
+        file: id.file.as_ref().and_then(to_path),
+        object: id.obj.as_ref().and_then(to_path),
+        inclusive: MetricsSummary::new(values.as_ref().map(|value| value.metrics.clone())),
+        exclusive: MetricsSummary::new(values.as_ref().map(|value| value.exclusive.clone())),
+    }
+}
+
+fn to_path(source_path: &SourcePath) -> Option<PathBuf> {
+    match source_path {
+        SourcePath::Unknown => None,
+        SourcePath::Rust(path) | SourcePath::Relative(path) | SourcePath::Absolute(path) => {
+            Some(path.clone())
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+    use crate::runner::callgrind::model::Metrics;
+
+    fn map_fixture(functions: &[(&str, u64, u64)]) -> CallgrindMap {
+        CallgrindMap {
+            map: functions
+                .iter()
+                .map(|(func, inclusive, exclusive)| {
+                    (
+                        Id {
+                            obj: None,
+                            file: None,
+                            func: (*func).to_owned(),
+                        },
+                        Value {
+                            metrics: Metrics::with_metric_kinds([(EventKind::Ir, *inclusive)]),
+                            exclusive: Metrics::with_metric_kinds([(EventKind::Ir, *exclusive)]),
+                        },
+                    )
+                })
+                .collect(),
+            sentinel: None,
+            sentinel_key: None,
+        }
+    }
+
+    #[rstest]
+    #[case::new_only(&[("a", 10, 10), ("b", 50, 30), ("c", 20, 20)], &[], 3, &["b", "c", "a"])]
+    #[case::new_only_top(&[("a", 10, 10), ("b", 50, 30), ("c", 20, 20)], &[], 2, &["b", "c"])]
+    #[case::top_zero(&[("a", 10, 10)], &[("a", 20, 20)], 0, &[])]
+    #[case::by_exclusive_diff(
+        &[("a", 100, 100), ("b", 300, 30), ("c", 20, 20)],
+        &[("a", 100, 100), ("b", 100, 10), ("c", 25, 25)],
+        3,
+        &["b", "c", "a"]
+    )]
+    #[case::old_only(&[("a", 10, 10)], &[("a", 10, 10), ("b", 20, 20)], 2, &["b", "a"])]
+    #[case::equal_diff_by_cost(
+        &[("a", 10, 10), ("b", 30, 30)],
+        &[("a", 0, 0), ("b", 20, 20)],
+        2,
+        &["b", "a"]
+    )]
+    #[case::equal_by_name(&[("b", 10, 10), ("a", 10, 10)], &[], 2, &["a", "b"])]
+    fn test_summarize_functions(
+        #[case] new: &[(&str, u64, u64)],
+        #[case] old: &[(&str, u64, u64)],
+        #[case] top: usize,
+        #[case] expected: &[&str],
+    ) {
+        let actual = summarize_functions(&map_fixture(new), &map_fixture(old), EventKind::Ir, top);
+        assert_eq!(
+            actual
+                .iter()
+                .map(|f| f.function.as_str())
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[test]
+    fn test_summarize_functions_when_event_kind_missing() {
+        let actual = summarize_functions(
+            &map_fixture(&[("a", 10, 10), ("b", 20, 20)]),
+            &CallgrindMap::default(),
+            EventKind::Dr,
+            2,
+        );
+        assert_eq!(
+            actual
+                .iter()
+                .map(|f| f.function.as_str())
+                .collect::<Vec<_>>(),
+            ["a", "b"]
+        );
+    }
+}
@@ -62,7 +62,10 @@ pub enum SourcePath {
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
 pub struct Value {
+    /// The inclusive metrics of the function
     pub metrics: Metrics,
+    /// The exclusive (self) metrics of the function without the metrics of the called functions
+    pub exclusive: Metrics,
 }
 
 impl CallgrindMap {
@@ -82,6 +85,7 @@ impl CallgrindMap {
         for (other_key, other_value) in &other.map {
             if let Some(value) = self.map.get_mut(other_key) {
                 value.metrics.add(&other_value.metrics);
+                value.exclusive.add(&other_value.exclusive);
             } else {
                 self.map.insert(other_key.clone(), other_value.clone());
             }
@@ -139,6 +143,7 @@ impl CallgrindParser for HashMapParser {
 
         let mut cfn_totals = HashMap::<Id, Value>::new();
         let mut fn_totals = HashMap::<Id, Value>::new();
+        let mut fn_exclusive = HashMap::<Id, Metrics>::new();
 
         // FIXME: This should be a vec. The sentinel can match many functions. This is only ok,
         // since we currently use the sentinel for the benchmark function exclusively. The benchmark
@@ -219,20 +224,36 @@ impl CallgrindParser for HashMapParser {
                             .skip(config.positions_prototype.len()),
                     )?;
 
+                    let id: Id = current_id.try_into().expect("A valid id");
                     if let Some(cfn_record) = cfn_record.take() {
                         cfn_totals
                             .entry(cfn_record.id.expect("cfn record id must be present"))
                             .and_modify(|value| value.metrics.add(&metrics))
                             .or_insert(Value {
                                 metrics: metrics.clone(),
+                                exclusive: config.metrics_prototype.clone(),
                             });
+                    } else {
+                        // Cost lines which don't follow a `calls` line are the costs of the
+                        // function itself
+                        match fn_exclusive.get_mut(&id) {
+                            Some(exclusive) => exclusive.add(&metrics),
+                            None => {
+                                fn_exclusive.insert(id.clone(), metrics.clone());
+                            }
+                        }
                     }
 
-                    let id = current_id.try_into().expect("A valid id");
                     match fn_totals.get_mut(&id) {
                         Some(value) => value.metrics.add(&metrics),
                         None => {
-                            fn_totals.insert(id.clone(), Value { metrics });
+                            fn_totals.insert(
+                                id.clone(),
+                                Value {
+                                    metrics,
+                                    exclusive: config.metrics_prototype.clone(),
+                                },
+                            );
                         }
                     }
                     current_id = id.into();
@@ -252,6 +273,12 @@ impl CallgrindParser for HashMapParser {
             fn_totals.insert(key, value);
         }
 
+        for (key, exclusive) in fn_exclusive {
+            if let Some(value) = fn_totals.get_mut(&key) {
+                value.exclusive = exclusive;
+            }
+        }
+
         Ok((
             config,
             CallgrindMap {
@@ -1,12 +1,17 @@
+pub mod annotate;
 pub mod args;
 pub mod flamegraph;
 pub mod flamegraph_parser;
+pub mod functions;
 pub mod hashmap_parser;
 pub mod model;
 pub mod parser;
 pub mod summary_parser;
 
 use std::convert::Into;
+use std::fmt::Display;
+use std::hash::Hash;
+use std::io::Write;
 use std::path::PathBuf;
 
 use colored::Colorize;
@@ -14,10 +19,14 @@ use itertools::Itertools;
 use parser::{CallgrindProperties, ParserOutput};
 
 use self::model::Metrics;
+use super::metrics::Summarize;
+use super::output::stderr;
 use super::summary::{
-    CallgrindRegression, MetricsSummary, ToolMetricSummary, ToolRun, ToolRunSegment,
+    CallgrindLimit, CallgrindRegression, DhatRegression, ErrorRegression, MetricsSummary,
+    NoiseSummary, ToolMetricSummary, ToolRegression, ToolRun, ToolRunSegment,
 };
-use crate::api::{self, EventKind};
+use super::tool::ValgrindTool;
+use crate::api::{self, DhatMetricKind, ErrorMetricKind, EventKind};
 use crate::util::{to_string_signed_short, EitherOrBoth};
 
 #[derive(Debug, Clone)]
@@ -44,6 +53,11 @@ pub struct CacheSummary {
 #[derive(Debug, Clone)]
 pub struct RegressionConfig {
     pub limits: Vec<(EventKind, f64)>,
+    pub hard_limits: Vec<(EventKind, u64)>,
+    pub absolute_limits: Vec<(EventKind, i64)>,
+    pub dhat_limits: Vec<(DhatMetricKind, f64)>,
+    pub error_limits: Vec<(ErrorMetricKind, u64)>,
+    pub fail_on_error_increase: bool,
     pub fail_fast: bool,
 }
 
@@ -95,12 +109,23 @@ impl RegressionConfig {
     /// If the old `Metrics` is None then no regression checks are performed and this method returns
     /// [`Ok`].
     ///
+    /// If the benchmark was run repeatedly, the new metrics are the metrics of the kept run, the
+    /// same as displayed and saved, and changes within the noise band are not considered a
+    /// regression.
+    ///
     /// # Errors
     ///
     /// Returns an [`anyhow::Error`] with the only source [`crate::error::Error::RegressionError`]
     /// if a regression error occurred
-    pub fn check_and_print(&self, metrics_summary: &MetricsSummary) -> Vec<CallgrindRegression> {
-        let regression = self.check(metrics_summary);
+    pub fn check_and_print(
+        &self,
+        metrics_summary: &MetricsSummary,
+        noise: Option<&NoiseSummary>,
+    ) -> Vec<CallgrindRegression> {
+        let mut regression = self.check(metrics_summary);
+        if let Some(noise) = noise {
+            regression.retain(|regression| !noise.is_noise(regression));
+        }
 
         for CallgrindRegression {
             event_kind,
@@ -110,26 +135,41 @@ impl RegressionConfig {
             limit,
         } in &regression
         {
-            if limit.is_sign_positive() {
-                eprintln!(
-                    "Performance has {0}: {1} ({new} > {old}) regressed by {2:>+6} (>{3:>+6})",
-                    "regressed".bold().bright_red(),
-                    event_kind.to_string().bold(),
-                    format!("{}%", to_string_signed_short(*diff_pct))
-                        .bold()
-                        .bright_red(),
-                    to_string_signed_short(*limit).bright_black()
-                );
-            } else {
-                eprintln!(
-                    "Performance has {0}: {1} ({new} < {old}) regressed by {2:>+6} (<{3:>+6})",
-                    "regressed".bold().bright_red(),
-                    event_kind.to_string().bold(),
-                    format!("{}%", to_string_signed_short(*diff_pct))
-                        .bold()
-                        .bright_red(),
-                    to_string_signed_short(*limit).bright_black()
-                );
+            match (limit, old, diff_pct) {
+                (CallgrindLimit::Percentage(limit), Some(old), Some(diff_pct)) => {
+                    print_regression(event_kind, *new, *old, *diff_pct, *limit);
+                }
+                (CallgrindLimit::Hard(limit), ..) => {
+                    writeln!(
+                        stderr(),
+                        "Performance has {0}: {1} ({new} > {limit}) exceeded the hard limit",
+                        "regressed".bold().bright_red(),
+                        event_kind.to_string().bold(),
+                    )
+                    .unwrap();
+                }
+                (CallgrindLimit::Absolute(limit), Some(old), _) => {
+                    let (sign, diff) = if limit.is_negative() {
+                        ('<', old - new)
+                    } else {
+                        ('>', new - old)
+                    };
+                    writeln!(
+                        stderr(),
+                        "Performance has {0}: {1} ({new} {sign} {old}) regressed by {2} \
+                         ({sign}{3})",
+                        "regressed".bold().bright_red(),
+                        event_kind.to_string().bold(),
+                        format!("{}{diff}", if limit.is_negative() { '-' } else { '+' })
+                            .bold()
+                            .bright_red(),
+                        format!("{limit:+}").bright_black()
+                    )
+                    .unwrap();
+                }
+                _ => unreachable!(
+                    "Regressions of percentage and absolute limits always have an old value"
+                ),
             }
         }
 
@@ -138,59 +178,251 @@ impl RegressionConfig {
 
     // Check the `MetricsSummary` for regressions.
     //
-    // The limits for event kinds which are not present in the `MetricsSummary` are ignored.
+    // The limits for event kinds which are not present in the `MetricsSummary` are ignored. The
+    // percentage and absolute limits are only checked if there is an old value to compare with,
+    // the hard limits are checked in any case.
     pub fn check(&self, metrics_summary: &MetricsSummary) -> Vec<CallgrindRegression> {
-        let mut regressions = vec![];
-        for (event_kind, new_cost, old_cost, pct, limit) in
-            self.limits.iter().filter_map(|(event_kind, limit)| {
-                metrics_summary.diff_by_kind(event_kind).and_then(|d| {
-                    if let EitherOrBoth::Both(new, old) = d.metrics {
-                        // This unwrap is safe since the diffs are calculated if both costs are
-                        // present
-                        Some((event_kind, new, old, d.diffs.unwrap().diff_pct, limit))
+        let mut regressions = check_limits(&self.limits, metrics_summary)
+            .into_iter()
+            .map(
+                |(event_kind, new, old, diff_pct, limit)| CallgrindRegression {
+                    event_kind,
+                    new,
+                    old: Some(old),
+                    diff_pct: Some(diff_pct),
+                    limit: CallgrindLimit::Percentage(limit),
+                },
+            )
+            .collect::<Vec<_>>();
+
+        for (event_kind, limit) in &self.hard_limits {
+            if let Some(diff) = metrics_summary.diff_by_kind(event_kind) {
+                let (new, old) = match diff.metrics {
+                    EitherOrBoth::Left(new) => (new, None),
+                    EitherOrBoth::Both(new, old) => (new, Some(old)),
+                    EitherOrBoth::Right(_) => continue,
+                };
+                if new > *limit {
+                    regressions.push(CallgrindRegression {
+                        event_kind: *event_kind,
+                        new,
+                        old,
+                        diff_pct: diff.diffs.map(|d| d.diff_pct),
+                        limit: CallgrindLimit::Hard(*limit),
+                    });
+                }
+            }
+        }
+
+        for (event_kind, limit) in &self.absolute_limits {
+            if let Some(diff) = metrics_summary.diff_by_kind(event_kind) {
+                if let EitherOrBoth::Both(new, old) = diff.metrics {
+                    let abs_diff = i128::from(new) - i128::from(old);
+                    let is_regressed = if limit.is_negative() {
+                        abs_diff < i128::from(*limit)
                     } else {
-                        None
+                        abs_diff > i128::from(*limit)
+                    };
+                    if is_regressed {
+                        regressions.push(CallgrindRegression {
+                            event_kind: *event_kind,
+                            new,
+                            old: Some(old),
+                            diff_pct: diff.diffs.map(|d| d.diff_pct),
+                            limit: CallgrindLimit::Absolute(*limit),
+                        });
                     }
-                })
+                }
+            }
+        }
+
+        regressions
+    }
+
+    /// Check regression of the dhat [`MetricsSummary`] for the configured [`DhatMetricKind`]s and
+    /// print it
+    pub fn check_and_print_dhat(
+        &self,
+        metrics_summary: &MetricsSummary<DhatMetricKind>,
+    ) -> Vec<ToolRegression> {
+        let regressions = self.check_dhat(metrics_summary);
+
+        for DhatRegression {
+            metric_kind,
+            new,
+            old,
+            diff_pct,
+            limit,
+        } in &regressions
+        {
+            print_regression(metric_kind, *new, *old, *diff_pct, *limit);
+        }
+
+        regressions.into_iter().map(ToolRegression::Dhat).collect()
+    }
+
+    // Check the dhat `MetricsSummary` for regressions.
+    //
+    // The limits for metric kinds which are not present in the `MetricsSummary` are ignored.
+    pub fn check_dhat(
+        &self,
+        metrics_summary: &MetricsSummary<DhatMetricKind>,
+    ) -> Vec<DhatRegression> {
+        check_limits(&self.dhat_limits, metrics_summary)
+            .into_iter()
+            .map(|(metric_kind, new, old, diff_pct, limit)| DhatRegression {
+                metric_kind,
+                new,
+                old,
+                diff_pct,
+                limit,
             })
+            .collect()
+    }
+
+    /// Check the error [`MetricsSummary`] against the configured error budgets and print the
+    /// exceeded budgets
+    pub fn check_and_print_errors(
+        &self,
+        metrics_summary: &MetricsSummary<ErrorMetricKind>,
+    ) -> Vec<ToolRegression> {
+        let regressions = self.check_errors(metrics_summary);
+
+        for ErrorRegression {
+            metric_kind,
+            new,
+            old,
+            limit,
+        } in &regressions
         {
-            if limit.is_sign_positive() {
-                if pct > *limit {
-                    let regression = CallgrindRegression {
-                        event_kind: *event_kind,
-                        new: new_cost,
-                        old: old_cost,
-                        diff_pct: pct,
-                        limit: *limit,
-                    };
-                    regressions.push(regression);
+            match (limit, old) {
+                (Some(limit), _) => writeln!(
+                    stderr(),
+                    "Errors have {0}: {1} ({new} > {limit}) exceeded the limit",
+                    "regressed".bold().bright_red(),
+                    metric_kind.to_string().bold(),
+                )
+                .unwrap(),
+                (None, Some(old)) => writeln!(
+                    stderr(),
+                    "Errors have {0}: {1} ({new} > {old}) increased compared to the baseline",
+                    "regressed".bold().bright_red(),
+                    metric_kind.to_string().bold(),
+                )
+                .unwrap(),
+                (None, None) => {
+                    unreachable!("An increase can only be detected if there is an old value")
                 }
-            } else if pct < *limit {
-                let regression = CallgrindRegression {
-                    event_kind: *event_kind,
-                    new: new_cost,
-                    old: old_cost,
-                    diff_pct: pct,
-                    limit: *limit,
+            }
+        }
+
+        regressions.into_iter().map(ToolRegression::Error).collect()
+    }
+
+    // Check the error `MetricsSummary` against the error budgets.
+    //
+    // An absolute limit is checked even if there is no baseline. If `fail_on_error_increase` is
+    // true, an increase of `Errors` or `Contexts` compared to the baseline is a regression. Only a
+    // single regression per metric kind is reported and an exceeded limit takes precedence.
+    pub fn check_errors(
+        &self,
+        metrics_summary: &MetricsSummary<ErrorMetricKind>,
+    ) -> Vec<ErrorRegression> {
+        let mut regressions: Vec<ErrorRegression> = vec![];
+        for (metric_kind, limit) in &self.error_limits {
+            if let Some(diff) = metrics_summary.diff_by_kind(metric_kind) {
+                let (new, old) = match diff.metrics {
+                    EitherOrBoth::Left(new) => (new, None),
+                    EitherOrBoth::Both(new, old) => (new, Some(old)),
+                    EitherOrBoth::Right(_) => continue,
                 };
-                regressions.push(regression);
-            } else {
-                // no regression
+                if new > *limit {
+                    regressions.push(ErrorRegression {
+                        metric_kind: *metric_kind,
+                        new,
+                        old,
+                        limit: Some(*limit),
+                    });
+                }
+            }
+        }
+
+        if self.fail_on_error_increase {
+            for metric_kind in [ErrorMetricKind::Errors, ErrorMetricKind::Contexts] {
+                if regressions.iter().any(|r| r.metric_kind == metric_kind) {
+                    continue;
+                }
+                if let Some(EitherOrBoth::Both(new, old)) = metrics_summary
+                    .diff_by_kind(&metric_kind)
+                    .map(|d| &d.metrics)
+                {
+                    if new > old {
+                        regressions.push(ErrorRegression {
+                            metric_kind,
+                            new: *new,
+                            old: Some(*old),
+                            limit: None,
+                        });
+                    }
+                }
             }
         }
+
         regressions
     }
+
+    /// Check the total of the [`ToolRun`] of `tool` for regressions and print them
+    ///
+    /// Currently, [`ValgrindTool::DHAT`] and the tools which report errors
+    /// ([`ValgrindTool::Memcheck`], [`ValgrindTool::Helgrind`] and [`ValgrindTool::DRD`]) support
+    /// regression checks. For all other tools no regressions are returned.
+    pub fn check_and_print_tool(
+        &self,
+        tool: ValgrindTool,
+        tool_run: &ToolRun,
+    ) -> Vec<ToolRegression> {
+        match (tool, &tool_run.total) {
+            (ValgrindTool::DHAT, ToolMetricSummary::DhatSummary(summary)) => {
+                self.check_and_print_dhat(summary)
+            }
+            (
+                ValgrindTool::Memcheck | ValgrindTool::Helgrind | ValgrindTool::DRD,
+                ToolMetricSummary::ErrorSummary(summary),
+            ) => self.check_and_print_errors(summary),
+            _ => vec![],
+        }
+    }
 }
 
 impl From<api::RegressionConfig> for RegressionConfig {
     fn from(value: api::RegressionConfig) -> Self {
-        let api::RegressionConfig { limits, fail_fast } = value;
+        let api::RegressionConfig {
+            limits,
+            hard_limits,
+            absolute_limits,
+            dhat_limits,
+            error_limits,
+            fail_on_error_increase,
+            fail_fast,
+        } = value;
+        let fail_on_error_increase = fail_on_error_increase.unwrap_or(false);
         RegressionConfig {
-            limits: if limits.is_empty() {
+            limits: if limits.is_empty()
+                && hard_limits.is_empty()
+                && absolute_limits.is_empty()
+                && dhat_limits.is_empty()
+                && error_limits.is_empty()
+                && !fail_on_error_increase
+            {
                 vec![(EventKind::Ir, 10f64)]
             } else {
                 limits
             },
+            hard_limits,
+            absolute_limits,
+            dhat_limits,
+            error_limits,
+            fail_on_error_increase,
             fail_fast: fail_fast.unwrap_or(false),
         }
     }
@@ -200,6 +432,11 @@ impl Default for RegressionConfig {
     fn default() -> Self {
         Self {
             limits: vec![(EventKind::Ir, 10f64)],
+            hard_limits: Vec::default(),
+            absolute_limits: Vec::default(),
+            dhat_limits: Vec::default(),
+            error_limits: Vec::default(),
+            fail_on_error_increase: Default::default(),
             fail_fast: Default::default(),
         }
     }
@@ -424,12 +661,82 @@ impl From<Summary> for ToolRunSegment {
     }
 }
 
+/// Check the `limits` against the metrics in the `MetricsSummary`
+///
+/// Returns the metric kind, the new and old metric, the difference in percent and the exceeded
+/// limit of each detected regression. The limits for metric kinds which are not present in the
+/// `MetricsSummary` are ignored.
+fn check_limits<K>(
+    limits: &[(K, f64)],
+    metrics_summary: &MetricsSummary<K>,
+) -> Vec<(K, u64, u64, f64, f64)>
+where
+    K: Hash + Eq + Summarize + Display + Clone,
+{
+    let mut regressions = vec![];
+    for (metric_kind, new_cost, old_cost, pct, limit) in
+        limits.iter().filter_map(|(metric_kind, limit)| {
+            metrics_summary.diff_by_kind(metric_kind).and_then(|d| {
+                if let EitherOrBoth::Both(new, old) = d.metrics {
+                    // This unwrap is safe since the diffs are calculated if both costs are
+                    // present
+                    Some((metric_kind, new, old, d.diffs.unwrap().diff_pct, limit))
+                } else {
+                    None
+                }
+            })
+        })
+    {
+        if limit.is_sign_positive() {
+            if pct > *limit {
+                regressions.push((metric_kind.clone(), new_cost, old_cost, pct, *limit));
+            }
+        } else if pct < *limit {
+            regressions.push((metric_kind.clone(), new_cost, old_cost, pct, *limit));
+        } else {
+            // no regression
+        }
+    }
+    regressions
+}
+
+fn print_regression<K: Display>(metric_kind: &K, new: u64, old: u64, diff_pct: f64, limit: f64) {
+    if limit.is_sign_positive() {
+        writeln!(
+            stderr(),
+            "Performance has {0}: {1} ({new} > {old}) regressed by {2:>+6} (>{3:>+6})",
+            "regressed".bold().bright_red(),
+            metric_kind.to_string().bold(),
+            format!("{}%", to_string_signed_short(diff_pct))
+                .bold()
+                .bright_red(),
+            to_string_signed_short(limit).bright_black()
+        )
+        .unwrap();
+    } else {
+        writeln!(
+            stderr(),
+            "Performance has {0}: {1} ({new} < {old}) regressed by {2:>+6} (<{3:>+6})",
+            "regressed".bold().bright_red(),
+            metric_kind.to_string().bold(),
+            format!("{}%", to_string_signed_short(diff_pct))
+                .bold()
+                .bright_red(),
+            to_string_signed_short(limit).bright_black()
+        )
+        .unwrap();
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rstest::rstest;
     use EventKind::*;
 
     use super::*;
+    use crate::runner::metrics;
+
+    type ExpectedRegression = (EventKind, u64, Option<u64>, Option<f64>, CallgrindLimit);
 
     fn cachesim_costs(costs: [u64; 9]) -> Metrics {
         Metrics::with_metric_kinds([
@@ -528,12 +835,239 @@ mod tests {
             .map(|(e, n, o, d, l)| CallgrindRegression {
                 event_kind: *e,
                 new: *n,
+                old: Some(*o),
+                diff_pct: Some(*d),
+                limit: CallgrindLimit::Percentage(*l),
+            })
+            .collect::<Vec<CallgrindRegression>>();
+
+        assert_eq!(regression.check(&summary), expected);
+    }
+
+    #[rstest]
+    #[case::hard_not_exceeded(vec![(Ir, 2)], vec![], [2, 0, 0, 0, 0, 0, 0, 0, 0], None, vec![])]
+    #[case::hard_without_baseline(
+        vec![(Ir, 1)],
+        vec![],
+        [2, 0, 0, 0, 0, 0, 0, 0, 0],
+        None,
+        vec![(Ir, 2, None, None, CallgrindLimit::Hard(1))]
+    )]
+    #[case::hard_with_baseline(
+        vec![(Ir, 1)],
+        vec![],
+        [2, 0, 0, 0, 0, 0, 0, 0, 0],
+        Some([2, 0, 0, 0, 0, 0, 0, 0, 0]),
+        vec![(Ir, 2, Some(2), Some(0f64), CallgrindLimit::Hard(1))]
+    )]
+    #[case::absolute_without_baseline(
+        vec![],
+        vec![(Ir, 0)],
+        [2, 0, 0, 0, 0, 0, 0, 0, 0],
+        None,
+        vec![]
+    )]
+    #[case::absolute_not_exceeded(
+        vec![],
+        vec![(Ir, 10)],
+        [12, 0, 0, 0, 0, 0, 0, 0, 0],
+        Some([2, 0, 0, 0, 0, 0, 0, 0, 0]),
+        vec![]
+    )]
+    #[case::absolute_exceeded(
+        vec![],
+        vec![(Ir, 10)],
+        [13, 0, 0, 0, 0, 0, 0, 0, 0],
+        Some([2, 0, 0, 0, 0, 0, 0, 0, 0]),
+        vec![(Ir, 13, Some(2), Some(550f64), CallgrindLimit::Absolute(10))]
+    )]
+    #[case::absolute_negative(
+        vec![],
+        vec![(Ir, -1)],
+        [2, 0, 0, 0, 0, 0, 0, 0, 0],
+        Some([4, 0, 0, 0, 0, 0, 0, 0, 0]),
+        vec![(Ir, 2, Some(4), Some(-50f64), CallgrindLimit::Absolute(-1))]
+    )]
+    #[case::derived_hard(
+        vec![(EstimatedCycles, 409)],
+        vec![],
+        [96, 24, 18, 6, 0, 2, 6, 0, 2],
+        None,
+        vec![(EstimatedCycles, 410, None, None, CallgrindLimit::Hard(409))]
+    )]
+    fn test_regression_check_hard_and_absolute_limits(
+        #[case] hard_limits: Vec<(EventKind, u64)>,
+        #[case] absolute_limits: Vec<(EventKind, i64)>,
+        #[case] new: [u64; 9],
+        #[case] old: Option<[u64; 9]>,
+        #[case] expected: Vec<ExpectedRegression>,
+    ) {
+        let regression = RegressionConfig {
+            limits: vec![],
+            hard_limits,
+            absolute_limits,
+            ..Default::default()
+        };
+
+        let new = cachesim_costs(new);
+        let summary = MetricsSummary::new(match old {
+            Some(old) => EitherOrBoth::Both(new, cachesim_costs(old)),
+            None => EitherOrBoth::Left(new),
+        });
+        let expected = expected
+            .into_iter()
+            .map(
+                |(event_kind, new, old, diff_pct, limit)| CallgrindRegression {
+                    event_kind,
+                    new,
+                    old,
+                    diff_pct,
+                    limit,
+                },
+            )
+            .collect::<Vec<CallgrindRegression>>();
+
+        assert_eq!(regression.check(&summary), expected);
+    }
+
+    #[rstest]
+    #[case::no_limits(vec![], 2, 1, vec![])]
+    #[case::when_regression(
+        vec![(DhatMetricKind::TotalBytes, 10f64)],
+        2,
+        1,
+        vec![(DhatMetricKind::TotalBytes, 2, 1, 100f64, 10f64)]
+    )]
+    #[case::when_improved(vec![(DhatMetricKind::TotalBytes, 0f64)], 1, 2, vec![])]
+    #[case::when_negative_limit(
+        vec![(DhatMetricKind::TotalBytes, -49f64)],
+        1,
+        2,
+        vec![(DhatMetricKind::TotalBytes, 1, 2, -50f64, -49f64)]
+    )]
+    #[case::when_metric_kind_not_present(vec![(DhatMetricKind::AtTGmaxBytes, 0f64)], 2, 1, vec![])]
+    fn test_regression_check_dhat(
+        #[case] dhat_limits: Vec<(DhatMetricKind, f64)>,
+        #[case] new: u64,
+        #[case] old: u64,
+        #[case] expected: Vec<(DhatMetricKind, u64, u64, f64, f64)>,
+    ) {
+        let regression = RegressionConfig {
+            dhat_limits,
+            ..Default::default()
+        };
+
+        let new = metrics::Metrics::with_metric_kinds([(DhatMetricKind::TotalBytes, new)]);
+        let old = metrics::Metrics::with_metric_kinds([(DhatMetricKind::TotalBytes, old)]);
+        let summary = MetricsSummary::new(EitherOrBoth::Both(new, old));
+        let expected = expected
+            .iter()
+            .map(|(m, n, o, d, l)| DhatRegression {
+                metric_kind: *m,
+                new: *n,
                 old: *o,
                 diff_pct: *d,
                 limit: *l,
             })
-            .collect::<Vec<CallgrindRegression>>();
+            .collect::<Vec<DhatRegression>>();
 
-        assert_eq!(regression.check(&summary), expected);
+        assert_eq!(regression.check_dhat(&summary), expected);
+    }
+
+    #[rstest]
+    #[case::no_budgets(vec![], false, 1, None, vec![])]
+    #[case::limit_without_baseline(
+        vec![(ErrorMetricKind::Errors, 0)],
+        false,
+        1,
+        None,
+        vec![(ErrorMetricKind::Errors, 1, None, Some(0))]
+    )]
+    #[case::limit_not_exceeded(vec![(ErrorMetricKind::Errors, 1)], false, 1, Some(0), vec![])]
+    #[case::limit_with_baseline(
+        vec![(ErrorMetricKind::Errors, 0)],
+        false,
+        1,
+        Some(1),
+        vec![(ErrorMetricKind::Errors, 1, Some(1), Some(0))]
+    )]
+    #[case::increase_without_baseline(vec![], true, 1, None, vec![])]
+    #[case::increase(
+        vec![],
+        true,
+        2,
+        Some(1),
+        vec![
+            (ErrorMetricKind::Errors, 2, Some(1), None),
+            (ErrorMetricKind::Contexts, 2, Some(1), None)
+        ]
+    )]
+    #[case::decrease(vec![], true, 1, Some(2), vec![])]
+    #[case::limit_takes_precedence(
+        vec![(ErrorMetricKind::Errors, 0)],
+        true,
+        2,
+        Some(1),
+        vec![
+            (ErrorMetricKind::Errors, 2, Some(1), Some(0)),
+            (ErrorMetricKind::Contexts, 2, Some(1), None)
+        ]
+    )]
+    fn test_regression_check_errors(
+        #[case] error_limits: Vec<(ErrorMetricKind, u64)>,
+        #[case] fail_on_error_increase: bool,
+        #[case] new: u64,
+        #[case] old: Option<u64>,
+        #[case] expected: Vec<(ErrorMetricKind, u64, Option<u64>, Option<u64>)>,
+    ) {
+        let regression = RegressionConfig {
+            error_limits,
+            fail_on_error_increase,
+            ..Default::default()
+        };
+
+        let error_metrics = |value| {
+            metrics::Metrics::with_metric_kinds([
+                (ErrorMetricKind::Errors, value),
+                (ErrorMetricKind::Contexts, value),
+                (ErrorMetricKind::SuppressedErrors, 0),
+                (ErrorMetricKind::SuppressedContexts, 0),
+            ])
+        };
+        let summary = MetricsSummary::new(match old {
+            Some(old) => EitherOrBoth::Both(error_metrics(new), error_metrics(old)),
+            None => EitherOrBoth::Left(error_metrics(new)),
+        });
+        let expected = expected
+            .into_iter()
+            .map(|(metric_kind, new, old, limit)| ErrorRegression {
+                metric_kind,
+                new,
+                old,
+                limit,
+            })
+            .collect::<Vec<ErrorRegression>>();
+
+        assert_eq!(regression.check_errors(&summary), expected);
+    }
+
+    #[rstest]
+    #[case::default(vec![], vec![], vec![(Ir, 10f64)])]
+    #[case::only_limits(vec![(Ir, 5f64)], vec![], vec![(Ir, 5f64)])]
+    #[case::only_dhat_limits(vec![], vec![(DhatMetricKind::TotalBytes, 5f64)], vec![])]
+    fn test_regression_config_from_api(
+        #[case] limits: Vec<(EventKind, f64)>,
+        #[case] dhat_limits: Vec<(DhatMetricKind, f64)>,
+        #[case] expected_limits: Vec<(EventKind, f64)>,
+    ) {
+        let actual: RegressionConfig = api::RegressionConfig {
+            limits,
+            dhat_limits: dhat_limits.clone(),
+            ..Default::default()
+        }
+        .into();
+
+        assert_eq!(actual.limits, expected_limits);
+        assert_eq!(actual.dhat_limits, dhat_limits);
     }
 }
@@ -112,6 +112,11 @@ impl Positions {
         }
     }
 
+    /// Return the value of the `position_type` if present
+    pub fn get(&self, position_type: PositionType) -> Option<u64> {
+        self.0.get(&position_type).copied()
+    }
+
     pub fn len(&self) -> usize {
         self.0.len()
     }
@@ -28,7 +28,14 @@ pub trait CallgrindParser {
         &self,
         output: &ToolOutputPath,
     ) -> Result<Vec<(PathBuf, CallgrindProperties, Self::Output)>> {
-        let paths = output.real_paths()?;
+        self.parse_paths(output.real_paths()?)
+    }
+
+    /// Parse all `paths` sorted by the target ids (`pid`, `part` and `thread`)
+    fn parse_paths(
+        &self,
+        paths: Vec<PathBuf>,
+    ) -> Result<Vec<(PathBuf, CallgrindProperties, Self::Output)>> {
         let mut results: Vec<(PathBuf, CallgrindProperties, Self::Output)> =
             Vec::with_capacity(paths.len());
         for path in paths {
@@ -8,6 +8,8 @@ use log::{debug, trace};
 use super::model::Metrics;
 use super::parser::{parse_header, CallgrindParser, CallgrindProperties};
 use crate::error::Error;
+use crate::runner::cachegrind;
+use crate::runner::tool::ValgrindTool;
 
 /// Parse the `total:` line in the callgrind output or `summary:` if total is not present
 ///
@@ -80,3 +82,14 @@ impl CallgrindParser for SummaryParser {
         }
     }
 }
+
+/// Return the summary parser of the default `tool`
+///
+/// The output files of the default tools (callgrind and cachegrind) have a similar format and both
+/// tools produce event based [`Metrics`].
+pub fn parser_factory(tool: ValgrindTool) -> Box<dyn CallgrindParser<Output = Metrics>> {
+    match tool {
+        ValgrindTool::Cachegrind => Box::new(cachegrind::summary_parser::SummaryParser),
+        _ => Box::new(SummaryParser),
+    }
+}
@@ -0,0 +1,419 @@
+//! Remove stale output files and prune old baselines of a benchmark target (`--clean`)
+//!
+//! A benchmark directory is stale if there's no benchmark with this directory in the current
+//! benchmark groups anymore, for example after renaming a benchmark function or removing an id.
+//! Named baselines in the directories of existing benchmarks can additionally be pruned by their
+//! age or number.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::fmt::Display;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime};
+
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use log::{debug, warn};
+use regex::Regex;
+
+use super::common::Config;
+use super::output::stdout;
+use crate::util::make_relative;
+
+lazy_static! {
+    static ref BASELINE_RE: Regex =
+        Regex::new(r"[.]base@(?<name>[a-zA-Z0-9_]+)(?:[.]|$)").expect("Regex should compile");
+}
+
+/// What to do with the files found by `--clean`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum CleanMode {
+    /// Only list the files which would be removed
+    List,
+    /// Remove the files and list the removed files
+    Delete,
+}
+
+/// The reason why a file is removed
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Reason {
+    /// The benchmark of the file doesn't exist anymore
+    Stale,
+    /// The file belongs to a pruned baseline
+    Baseline(String),
+}
+
+/// The options to prune named baselines
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct PruneOptions {
+    /// Keep at most this number of the newest baselines of each benchmark
+    pub keep: Option<usize>,
+    /// Remove baselines older than this age
+    pub max_age: Option<Duration>,
+}
+
+impl Display for Reason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Reason::Stale => f.write_str("stale benchmark"),
+            Reason::Baseline(name) => write!(f, "baseline '{name}'"),
+        }
+    }
+}
+
+impl PruneOptions {
+    /// Return true if any baselines should be pruned
+    pub fn is_enabled(&self) -> bool {
+        self.keep.is_some() || self.max_age.is_some()
+    }
+
+    /// Return the names of the baselines to prune
+    ///
+    /// The `baselines` are the names of the baselines of a benchmark with the time of their last
+    /// modification.
+    fn select(&self, baselines: &BTreeMap<String, SystemTime>, now: SystemTime) -> Vec<String> {
+        let mut sorted = baselines.iter().collect::<Vec<_>>();
+        // Newest first
+        sorted.sort_by(|a, b| b.1.cmp(a.1).then_with(|| a.0.cmp(b.0)));
+
+        sorted
+            .into_iter()
+            .enumerate()
+            .filter(|(index, (_, modified))| {
+                self.keep.map_or(false, |keep| *index >= keep)
+                    || self.max_age.map_or(false, |max_age| {
+                        now.duration_since(**modified)
+                            .map_or(false, |age| age > max_age)
+                    })
+            })
+            .map(|(_, (name, _))| name.clone())
+            .collect()
+    }
+}
+
+/// Find all stale files and directories in `dir` and the existing benchmark directories
+///
+/// Directories which are neither a benchmark directory nor a parent of a benchmark directory are
+/// stale. Files directly in the `dir` of the benchmark target like reports are never stale.
+fn find_stale(
+    dir: &Path,
+    benchmark_dirs: &BTreeSet<PathBuf>,
+    stale_dirs: &mut Vec<PathBuf>,
+    existing: &mut Vec<PathBuf>,
+) -> Result<()> {
+    for entry in read_dir(dir)? {
+        let path = entry.path();
+        if !path.is_dir() {
+            continue;
+        }
+
+        if benchmark_dirs.contains(&path) {
+            existing.push(path);
+        } else if benchmark_dirs.iter().any(|d| d.starts_with(&path)) {
+            find_stale(&path, benchmark_dirs, stale_dirs, existing)?;
+        } else {
+            stale_dirs.push(path);
+        }
+    }
+
+    Ok(())
+}
+
+/// Find the files of the baselines to prune in the benchmark directory `dir`
+///
+/// The age of a baseline is the time of the last modification of its newest file.
+fn find_pruned(dir: &Path, options: &PruneOptions, now: SystemTime) -> Result<Vec<PathBuf>> {
+    let mut files = vec![];
+    let mut baselines: BTreeMap<String, SystemTime> = BTreeMap::new();
+    for entry in read_dir(dir)? {
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        let names = baseline_names(&file_name);
+        if names.is_empty() {
+            continue;
+        }
+
+        let modified = entry
+            .metadata()
+            .and_then(|m| m.modified())
+            .with_context(|| format!("Failed to read metadata of '{}'", entry.path().display()))?;
+        for name in &names {
+            let newest = baselines.entry(name.clone()).or_insert(modified);
+            *newest = (*newest).max(modified);
+        }
+        files.push((entry.path(), names));
+    }
+
+    let pruned = options.select(&baselines, now);
+    let mut result = files
+        .into_iter()
+        .filter(|(_, names)| names.iter().any(|name| pruned.contains(name)))
+        .map(|(path, _)| path)
+        .collect::<Vec<_>>();
+    result.sort();
+    Ok(result)
+}
+
+/// Return the names of all baselines in a `file_name`
+///
+/// A diff flamegraph like `Ir.flamegraph.base@new.diff.base@old.svg` belongs to two baselines.
+fn baseline_names(file_name: &str) -> Vec<String> {
+    BASELINE_RE
+        .captures_iter(file_name)
+        .map(|caps| caps["name"].to_owned())
+        .collect()
+}
+
+/// Return all files in `dir` and its subdirectories
+fn collect_files(dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    for entry in read_dir(dir)? {
+        let path = entry.path();
+        if path.is_dir() {
+            collect_files(&path, files)?;
+        } else {
+            files.push(path);
+        }
+    }
+    Ok(())
+}
+
+fn read_dir(dir: &Path) -> Result<impl Iterator<Item = std::fs::DirEntry>> {
+    std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory '{}'", dir.display()))
+        .map(|entries| entries.filter_map(Result::ok))
+}
+
+/// Return the [`CleanMode`] which is safe to use with the `benchmark_dirs`
+///
+/// If the benchmark directories are not complete, the directories of the missing benchmarks would
+/// be removed as stale. So, the files are only listed in this case.
+fn safe_mode(mode: CleanMode, is_complete: bool) -> CleanMode {
+    if mode == CleanMode::Delete && !is_complete {
+        warn!(
+            "Not all benchmarks are known, for example because of a filter: Only listing the \
+             files instead of removing them"
+        );
+        CleanMode::List
+    } else {
+        mode
+    }
+}
+
+/// Remove the stale files and the files of pruned baselines of the benchmark target
+///
+/// The `benchmark_dirs` are the directories of all benchmarks of the current benchmark groups. If
+/// `is_complete` is false, some benchmarks are missing in the `benchmark_dirs` and nothing is
+/// removed even if the `mode` is [`CleanMode::Delete`].
+pub fn run(
+    config: &Config,
+    mode: CleanMode,
+    benchmark_dirs: &BTreeSet<PathBuf>,
+    is_complete: bool,
+) -> Result<()> {
+    let dir = config.meta.target_dir.join(config.module_path.to_string());
+    if !dir.exists() {
+        debug!("Nothing to clean: '{}' doesn't exist", dir.display());
+        return Ok(());
+    }
+    let mode = safe_mode(mode, is_complete);
+
+    let options = PruneOptions {
+        keep: config.meta.args.keep_baselines,
+        max_age: config.meta.args.max_baseline_age,
+    };
+
+    let (mut stale_dirs, mut existing) = (vec![], vec![]);
+    find_stale(&dir, benchmark_dirs, &mut stale_dirs, &mut existing)?;
+    stale_dirs.sort();
+    existing.sort();
+
+    let mut found = vec![];
+    for stale_dir in &stale_dirs {
+        let mut files = vec![];
+        collect_files(stale_dir, &mut files)?;
+        files.sort();
+        found.extend(files.into_iter().map(|path| (path, Reason::Stale)));
+    }
+    if options.is_enabled() {
+        let now = SystemTime::now();
+        for dir in &existing {
+            for path in find_pruned(dir, &options, now)? {
+                let names = baseline_names(&path.file_name().unwrap_or_default().to_string_lossy());
+                found.push((path, Reason::Baseline(names.join(", "))));
+            }
+        }
+    }
+
+    let mut writer = stdout();
+    for (path, reason) in &found {
+        writeln!(
+            writer,
+            "{} ({reason})",
+            make_relative(&config.meta.project_root, path).display()
+        )?;
+    }
+
+    match mode {
+        CleanMode::List => {
+            writeln!(
+                writer,
+                "Found {} files to remove in '{}'",
+                found.len(),
+                config.meta.bench_name
+            )?;
+        }
+        CleanMode::Delete => {
+            for stale_dir in &stale_dirs {
+                std::fs::remove_dir_all(stale_dir).with_context(|| {
+                    format!("Failed to remove directory '{}'", stale_dir.display())
+                })?;
+            }
+            for (path, reason) in &found {
+                if let Reason::Baseline(_) = reason {
+                    std::fs::remove_file(path)
+                        .with_context(|| format!("Failed to remove file '{}'", path.display()))?;
+                }
+            }
+            writeln!(
+                writer,
+                "Removed {} files in '{}'",
+                found.len(),
+                config.meta.bench_name
+            )?;
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    const HOUR: Duration = Duration::from_secs(3600);
+
+    #[rstest]
+    #[case::no_baseline("callgrind.bench.out", &[])]
+    #[case::old("callgrind.bench.out.old", &[])]
+    #[case::out("callgrind.bench.out.base@main", &["main"])]
+    #[case::log("callgrind.bench.log.base@my_base", &["my_base"])]
+    #[case::with_modifiers("callgrind.bench.t1.p2.out.base@main", &["main"])]
+    #[case::flamegraph("callgrind.bench.Ir.flamegraph.base@main.svg", &["main"])]
+    #[case::diff_flamegraph(
+        "callgrind.bench.Ir.flamegraph.base@new.diff.base@old.svg",
+        &["new", "old"]
+    )]
+    #[case::invalid_name("callgrind.bench.out.base@in-valid", &[])]
+    fn test_baseline_names(#[case] file_name: &str, #[case] expected: &[&str]) {
+        assert_eq!(baseline_names(file_name), expected);
+    }
+
+    #[rstest]
+    #[case::disabled(None, None, &[])]
+    #[case::keep_all(Some(3), None, &[])]
+    #[case::keep_newest(Some(1), None, &["b", "c"])]
+    #[case::keep_none(Some(0), None, &["a", "b", "c"])]
+    #[case::max_age(None, Some(HOUR * 2), &["c"])]
+    #[case::keep_and_max_age(Some(2), Some(HOUR * 3), &["c"])]
+    fn test_prune_options_select(
+        #[case] keep: Option<usize>,
+        #[case] max_age: Option<Duration>,
+        #[case] expected: &[&str],
+    ) {
+        let now = SystemTime::now();
+        let baselines = BTreeMap::from([
+            ("a".to_owned(), now - HOUR),
+            ("b".to_owned(), now - HOUR * 2),
+            ("c".to_owned(), now - HOUR * 4),
+        ]);
+
+        let mut actual = PruneOptions { keep, max_age }.select(&baselines, now);
+        actual.sort();
+        assert_eq!(actual, expected);
+    }
+
+    #[rstest]
+    #[case::list_complete(CleanMode::List, true, CleanMode::List)]
+    #[case::list_incomplete(CleanMode::List, false, CleanMode::List)]
+    #[case::delete_complete(CleanMode::Delete, true, CleanMode::Delete)]
+    #[case::delete_incomplete(CleanMode::Delete, false, CleanMode::List)]
+    fn test_safe_mode(
+        #[case] mode: CleanMode,
+        #[case] is_complete: bool,
+        #[case] expected: CleanMode,
+    ) {
+        assert_eq!(safe_mode(mode, is_complete), expected);
+    }
+
+    #[test]
+    fn test_find_stale() {
+        let root = tempfile::tempdir().unwrap();
+        let root = root.path();
+        for dir in [
+            "group/bench_a",
+            "group/bench_b.old_id",
+            "group/bench_b.id",
+            "old_group/bench_c",
+        ] {
+            std::fs::create_dir_all(root.join(dir)).unwrap();
+        }
+        File::create(root.join("report.md")).unwrap();
+
+        let benchmark_dirs = BTreeSet::from([
+            root.join("group/bench_a"),
+            root.join("group/bench_b.id"),
+            root.join("group/bench_new"),
+        ]);
+        let (mut stale_dirs, mut existing) = (vec![], vec![]);
+        find_stale(root, &benchmark_dirs, &mut stale_dirs, &mut existing).unwrap();
+        stale_dirs.sort();
+        existing.sort();
+
+        assert_eq!(
+            stale_dirs,
+            vec![root.join("group/bench_b.old_id"), root.join("old_group")]
+        );
+        assert_eq!(
+            existing,
+            vec![root.join("group/bench_a"), root.join("group/bench_b.id")]
+        );
+    }
+
+    #[test]
+    fn test_find_pruned() {
+        let dir = tempfile::tempdir().unwrap();
+        let dir = dir.path();
+        for name in [
+            "callgrind.bench.out",
+            "callgrind.bench.out.old",
+            "callgrind.bench.out.base@main",
+            "callgrind.bench.log.base@main",
+            "callgrind.bench.Ir.flamegraph.base@main.svg",
+            "summary.json",
+        ] {
+            File::create(dir.join(name)).unwrap();
+        }
+
+        let options = PruneOptions {
+            keep: None,
+            max_age: Some(HOUR),
+        };
+        assert!(find_pruned(dir, &options, SystemTime::now())
+            .unwrap()
+            .is_empty());
+
+        let expected = vec![
+            dir.join("callgrind.bench.Ir.flamegraph.base@main.svg"),
+            dir.join("callgrind.bench.log.base@main"),
+            dir.join("callgrind.bench.out.base@main"),
+        ];
+        assert_eq!(
+            find_pruned(dir, &options, SystemTime::now() + HOUR * 2).unwrap(),
+            expected
+        );
+    }
+}
@@ -0,0 +1,583 @@
+//! The `compare` subcommand of the `iai-callgrind-runner` binary
+//!
+//! Compare two named baselines of all benchmarks in the iai home directory or two arbitrary
+//! callgrind (or cachegrind) output files without building and running the benchmarks. Contrary to
+//! `--load-baseline`, neither the benchmark source nor the cargo metadata is needed.
+
+use std::collections::BTreeMap;
+use std::ffi::OsString;
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use clap::Parser;
+use log::{debug, warn};
+
+use super::args::MissingBaseline;
+use super::callgrind::summary_parser::parser_factory;
+use super::callgrind::Summaries;
+use super::common::ModulePath;
+use super::envs;
+use super::format::{Formatter, Header, OutputFormat, OutputFormatKind, VerticalFormatter};
+use super::summary::{
+    BaselineKind, BaselineName, BaselineStatus, BenchmarkKind, BenchmarkSummary, CachegrindSummary,
+    CallgrindSummary, SummaryFormat, SummaryOutput, ToolRun,
+};
+use super::tool::{ToolOutputPath, ToolOutputPathKind, ValgrindTool};
+
+/// The name of the subcommand as first argument to the `iai-callgrind-runner` binary
+pub const SUBCOMMAND: &str = "compare";
+
+/// The command line arguments of the `compare` subcommand
+#[derive(Parser, Debug, Clone)]
+#[command(
+    version,
+    about = "Compare two baselines or two callgrind output files without running the benchmarks
+
+If OLD and NEW are existing files, these files are compared. Otherwise, OLD and NEW are the names
+of baselines (as in `--save-baseline=NAME`) and all benchmarks in the home directory which have a
+NEW baseline are compared with their OLD baseline.",
+    long_about = None,
+    no_binary_name = true,
+    override_usage = "iai-callgrind-runner compare [OPTIONS] <OLD> <NEW>"
+)]
+pub struct CompareArgs {
+    /// The name of the old baseline or the path to the old callgrind output file
+    #[arg(name = "OLD")]
+    pub old: String,
+
+    /// The name of the new baseline or the path to the new callgrind output file
+    #[arg(name = "NEW")]
+    pub new: String,
+
+    /// The home directory of the iai-callgrind benchmark output files
+    ///
+    /// The default is `$CARGO_TARGET_DIR/iai` or `target/iai` if `CARGO_TARGET_DIR` is not set.
+    /// If the benchmarks were run with `--separate-targets`, the target triple has to be part of
+    /// the home directory, for example `target/iai/x86_64-unknown-linux-gnu`.
+    #[arg(long = "home", num_args = 1, env = "IAI_CALLGRIND_HOME")]
+    pub home: Option<PathBuf>,
+
+    /// Save a machine-readable summary of each comparison in a `summary.json` file
+    ///
+    /// When comparing baselines, the summary is stored in the directory of each benchmark. When
+    /// comparing files, the summary is stored in the directory of the NEW file.
+    #[arg(
+        long = "save-summary",
+        value_enum,
+        num_args = 0..=1,
+        require_equals = true,
+        default_missing_value = "json"
+    )]
+    pub save_summary: Option<SummaryFormat>,
+
+    /// What to do if the OLD baseline of a benchmark with a NEW baseline is missing
+    ///
+    /// With `fail` the comparison fails, with `warn` a warning is printed and with `ignore` the
+    /// NEW baseline is shown silently without comparison. The `baseline_status` of the benchmark
+    /// summary of such a benchmark is `New`.
+    #[arg(
+        long = "missing-baseline",
+        value_enum,
+        num_args = 1,
+        require_equals = true,
+        default_value = "ignore",
+        env = "IAI_CALLGRIND_MISSING_BASELINE"
+    )]
+    pub missing_baseline: MissingBaseline,
+}
+
+/// The output files of a single benchmark (or two arbitrary files) to compare
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Comparison {
+    tool: ValgrindTool,
+    /// The directory in which the summary is saved
+    dir: PathBuf,
+    module_path: ModulePath,
+    function_name: String,
+    id: Option<String>,
+    baselines: (Option<String>, Option<String>),
+    /// The name of the old baseline if baselines and not files are compared
+    old_baseline: Option<BaselineName>,
+    new: Vec<PathBuf>,
+    old: Vec<PathBuf>,
+}
+
+impl Comparison {
+    /// Create a new `Comparison` of two arbitrary files
+    ///
+    /// The tool is determined by the file name of the `new` file and defaults to callgrind.
+    fn from_files(old: &Path, new: &Path) -> Self {
+        let file_name = new
+            .file_name()
+            .map_or_else(String::new, |f| f.to_string_lossy().to_string());
+        let tool = match file_name.split_once('.') {
+            Some(("cachegrind", _)) => ValgrindTool::Cachegrind,
+            _ => ValgrindTool::Callgrind,
+        };
+
+        Self {
+            tool,
+            dir: new.parent().map_or_else(PathBuf::new, ToOwned::to_owned),
+            module_path: ModulePath::new(&new.display().to_string()),
+            function_name: file_name,
+            id: None,
+            baselines: (None, None),
+            old_baseline: None,
+            new: vec![new.to_owned()],
+            old: vec![old.to_owned()],
+        }
+    }
+
+    /// Create a new `Comparison` of the `old` and `new` baseline of a single benchmark
+    ///
+    /// The benchmark directory `dir` is expected to be a subdirectory of `home` with the usual
+    /// layout `$PACKAGE/$BENCHMARK_FILE/$GROUP/$FUNCTION.$ID`.
+    fn from_baselines(
+        home: &Path,
+        dir: &Path,
+        tool: ValgrindTool,
+        name: &str,
+        old: &BaselineName,
+        new: &BaselineName,
+    ) -> Result<Self> {
+        let output_path = |baseline: &BaselineName| ToolOutputPath {
+            kind: ToolOutputPathKind::Base(baseline.to_string()),
+            tool,
+            baseline_kind: BaselineKind::Name(baseline.clone()),
+            dir: dir.to_owned(),
+            name: name.to_owned(),
+            modifiers: vec![],
+        };
+
+        // The first component is the package name which is not part of the module path
+        let module_path = dir
+            .strip_prefix(home)
+            .unwrap_or(dir)
+            .parent()
+            .map(|parent| {
+                parent
+                    .iter()
+                    .skip(1)
+                    .map(|c| c.to_string_lossy())
+                    .collect::<Vec<_>>()
+            })
+            .unwrap_or_default();
+        let (function_name, id) = match name.split_once('.') {
+            Some((function_name, id)) => (function_name, Some(id.to_owned())),
+            None => (name, None),
+        };
+        let module_path = if module_path.is_empty() {
+            ModulePath::new(function_name)
+        } else {
+            ModulePath::new(&module_path.join("::")).join(function_name)
+        };
+
+        Ok(Self {
+            tool,
+            dir: dir.to_owned(),
+            module_path,
+            function_name: function_name.to_owned(),
+            id,
+            baselines: (Some(new.to_string()), Some(old.to_string())),
+            old_baseline: Some(old.clone()),
+            new: output_path(new).real_paths()?,
+            old: output_path(old).real_paths()?,
+        })
+    }
+
+    /// Return the [`BaselineStatus`] of this comparison
+    ///
+    /// If the files of the old baseline are missing, the benchmark is new and the
+    /// `missing_baseline` check decides if this is an error.
+    fn baseline_status(&self, missing_baseline: MissingBaseline) -> Result<BaselineStatus> {
+        if !self.old.is_empty() {
+            return Ok(BaselineStatus::Compared);
+        }
+        if let Some(old) = &self.old_baseline {
+            missing_baseline.check(old, self.tool, &self.module_path)?;
+        }
+        Ok(BaselineStatus::New)
+    }
+
+    /// Compare the files, print the comparison and return the [`BenchmarkSummary`]
+    fn run(
+        &self,
+        save_summary: Option<SummaryFormat>,
+        missing_baseline: MissingBaseline,
+    ) -> Result<BenchmarkSummary> {
+        let output_format = OutputFormat::default();
+        Header::new(&self.module_path, self.id.clone(), None, &output_format).print();
+
+        let baseline_status = self.baseline_status(missing_baseline)?;
+
+        let parser = parser_factory(self.tool);
+        let parsed_new = parser.parse_paths(self.new.clone())?;
+        let parsed_old = (baseline_status == BaselineStatus::Compared)
+            .then(|| parser.parse_paths(self.old.clone()))
+            .transpose()?;
+
+        // Information about the benchmark executable is only available in the output files
+        let command = parsed_new
+            .first()
+            .or_else(|| parsed_old.as_ref().and_then(|old| old.first()))
+            .and_then(|(_, properties, _)| properties.cmd.clone())
+            .unwrap_or_default();
+        let (bench_bin, bench_args) = split_command(&command);
+        let kind = if bench_args.iter().any(|arg| arg == "--iai-run") {
+            BenchmarkKind::LibraryBenchmark
+        } else {
+            BenchmarkKind::BinaryBenchmark
+        };
+
+        let summaries = Summaries::new(parsed_new, parsed_old);
+        let tool_run = ToolRun::from(&summaries);
+        let mut formatter = VerticalFormatter::new(output_format);
+        formatter.format_single(self.baselines.clone(), None, &tool_run.total)?;
+        formatter.print_buffer();
+
+        let project_root =
+            std::env::current_dir().with_context(|| "Failed to detect the current directory")?;
+        let output = save_summary.map(|format| SummaryOutput::new(format, &self.dir));
+        if let Some(output) = &output {
+            output.init()?;
+        }
+
+        let mut summary = BenchmarkSummary::new(
+            kind,
+            project_root.clone(),
+            project_root,
+            PathBuf::new(),
+            bench_bin.clone(),
+            &self.module_path,
+            &self.function_name,
+            self.id.clone(),
+            None,
+            output,
+        );
+        // The benchmark file cannot be known without the cargo metadata
+        summary.benchmark_file = PathBuf::new();
+        summary.baseline_status = baseline_status;
+
+        if self.tool == ValgrindTool::Cachegrind {
+            summary
+                .cachegrind_summary
+                .insert(CachegrindSummary::new(vec![], self.new.clone()))
+                .add_summaries(&bench_bin, &bench_args, &self.baselines, summaries, vec![]);
+        } else {
+            summary
+                .callgrind_summary
+                .insert(CallgrindSummary::new(vec![], self.new.clone()))
+                .add_summaries(&bench_bin, &bench_args, &self.baselines, summaries, vec![]);
+        }
+
+        summary.print_and_save(&OutputFormatKind::Default)?;
+        Ok(summary)
+    }
+}
+
+/// Find all benchmarks in the `home` directory with a `new` baseline
+///
+/// Only the output files of callgrind and cachegrind are considered. The benchmarks are sorted by
+/// their directory and name.
+fn find_comparisons(
+    home: &Path,
+    old: &BaselineName,
+    new: &BaselineName,
+) -> Result<Vec<Comparison>> {
+    let mut found = BTreeMap::new();
+    find_baselines(home, new, &mut found)?;
+
+    found
+        .into_iter()
+        .map(|((dir, name), tool)| Comparison::from_baselines(home, &dir, tool, &name, old, new))
+        .collect()
+}
+
+/// Recursively collect the directory, name and tool of all output files of the `baseline`
+///
+/// The name of a benchmark directory is the name of the benchmark, and the output files in this
+/// directory follow the naming scheme `$TOOL.$NAME[.$MODIFIERS].out.base@$BASELINE`.
+#[allow(clippy::case_sensitive_file_extension_comparisons)]
+fn find_baselines(
+    dir: &Path,
+    baseline: &BaselineName,
+    found: &mut BTreeMap<(PathBuf, String), ValgrindTool>,
+) -> Result<()> {
+    let suffix = format!(".out.base@{baseline}");
+    let name = dir
+        .file_name()
+        .map_or_else(String::new, |n| n.to_string_lossy().to_string());
+    let entries = std::fs::read_dir(dir)
+        .with_context(|| format!("Failed to read directory: '{}'", dir.display()))?;
+
+    for entry in entries.filter_map(Result::ok) {
+        let path = entry.path();
+        if path.is_dir() {
+            find_baselines(&path, baseline, found)?;
+            continue;
+        }
+
+        let file_name = entry.file_name().to_string_lossy().to_string();
+        if !file_name.ends_with(&suffix) {
+            continue;
+        }
+
+        for tool in [ValgrindTool::Callgrind, ValgrindTool::Cachegrind] {
+            if file_name.starts_with(&format!("{}.{name}.", tool.id())) {
+                found.insert((dir.to_owned(), name.clone()), tool);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Split the `command` of an output file into the benchmark executable and its arguments
+///
+/// Valgrind writes the command without any shell quoting, so the command is split at whitespace.
+fn split_command(command: &str) -> (PathBuf, Vec<OsString>) {
+    let mut words = command.split_whitespace();
+    let bench_bin = PathBuf::from(words.next().unwrap_or_default());
+    let bench_args = words.map(OsString::from).collect();
+    (bench_bin, bench_args)
+}
+
+/// Run the `compare` subcommand with the command line `args` following the subcommand
+pub fn run<I>(args: I) -> Result<()>
+where
+    I: IntoIterator<Item = OsString>,
+{
+    let args = CompareArgs::parse_from(args);
+    debug!("Compare arguments: {args:?}");
+
+    let (old, new) = (Path::new(&args.old), Path::new(&args.new));
+    let comparisons = if old.is_file() && new.is_file() {
+        vec![Comparison::from_files(old, new)]
+    } else {
+        let parse = |value: &str| {
+            value.parse::<BaselineName>().map_err(|error| {
+                anyhow!("'{value}' is neither an existing file nor a valid baseline name: {error}")
+            })
+        };
+        let (old, new) = (parse(&args.old)?, parse(&args.new)?);
+
+        let home = args.home.clone().unwrap_or_else(|| {
+            std::env::var_os(envs::CARGO_TARGET_DIR)
+                .map_or_else(|| PathBuf::from("target"), PathBuf::from)
+                .join("iai")
+        });
+        debug!("Searching for baselines in '{}'", home.display());
+
+        let comparisons = find_comparisons(&home, &old, &new)?;
+        if comparisons.is_empty() {
+            warn!(
+                "No benchmarks with a baseline '{new}' found in '{}'",
+                home.display()
+            );
+        }
+        comparisons
+    };
+
+    for comparison in comparisons {
+        comparison.run(args.save_summary, args.missing_baseline)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs::File;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::without_id("bench_fib", "my_bench::my_group::bench_fib", "bench_fib", None)]
+    #[case::with_id(
+        "bench_fib.short",
+        "my_bench::my_group::bench_fib",
+        "bench_fib",
+        Some("short")
+    )]
+    #[case::with_dots_in_id(
+        "bench_fib.1.0",
+        "my_bench::my_group::bench_fib",
+        "bench_fib",
+        Some("1.0")
+    )]
+    fn test_find_comparisons(
+        #[case] name: &str,
+        #[case] expected_module_path: &str,
+        #[case] expected_function_name: &str,
+        #[case] expected_id: Option<&str>,
+    ) {
+        let home = tempfile::tempdir().unwrap();
+        let dir = home
+            .path()
+            .join("my_package")
+            .join("my_bench")
+            .join("my_group")
+            .join(name);
+        std::fs::create_dir_all(&dir).unwrap();
+        for file_name in [
+            format!("callgrind.{name}.out"),
+            format!("callgrind.{name}.out.base@old"),
+            format!("callgrind.{name}.out.base@new"),
+            format!("callgrind.{name}.out.base@new_too"),
+            format!("callgrind.{name}.log.base@new"),
+            format!("dhat.{name}.out.base@new"),
+            "summary.json".to_owned(),
+        ] {
+            File::create(dir.join(file_name)).unwrap();
+        }
+
+        let old = "old".parse::<BaselineName>().unwrap();
+        let new = "new".parse::<BaselineName>().unwrap();
+        let expected = vec![Comparison {
+            tool: ValgrindTool::Callgrind,
+            dir: dir.clone(),
+            module_path: ModulePath::new(expected_module_path),
+            function_name: expected_function_name.to_owned(),
+            id: expected_id.map(ToOwned::to_owned),
+            baselines: (Some("new".to_owned()), Some("old".to_owned())),
+            old_baseline: Some(old.clone()),
+            new: vec![dir.join(format!("callgrind.{name}.out.base@new"))],
+            old: vec![dir.join(format!("callgrind.{name}.out.base@old"))],
+        }];
+
+        assert_eq!(find_comparisons(home.path(), &old, &new).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_find_comparisons_with_modifiers() {
+        let home = tempfile::tempdir().unwrap();
+        let dir = home
+            .path()
+            .join("my_package")
+            .join("my_bench")
+            .join("bench");
+        std::fs::create_dir_all(&dir).unwrap();
+        for file_name in [
+            "cachegrind.bench.2.out.base@new",
+            "cachegrind.bench.1.out.base@new",
+            "cachegrind.bench.1.out.base@old",
+        ] {
+            File::create(dir.join(file_name)).unwrap();
+        }
+
+        let actual = find_comparisons(
+            home.path(),
+            &"old".parse::<BaselineName>().unwrap(),
+            &"new".parse::<BaselineName>().unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(actual.len(), 1);
+        let mut comparison = actual[0].clone();
+        comparison.new.sort();
+        assert_eq!(comparison.tool, ValgrindTool::Cachegrind);
+        assert_eq!(comparison.module_path, ModulePath::new("my_bench::bench"));
+        assert_eq!(
+            comparison.new,
+            vec![
+                dir.join("cachegrind.bench.1.out.base@new"),
+                dir.join("cachegrind.bench.2.out.base@new")
+            ]
+        );
+        assert_eq!(
+            comparison.old,
+            vec![dir.join("cachegrind.bench.1.out.base@old")]
+        );
+    }
+
+    #[rstest]
+    #[case::ignore(MissingBaseline::Ignore, Some(BaselineStatus::New))]
+    #[case::warn(MissingBaseline::Warn, Some(BaselineStatus::New))]
+    #[case::fail(MissingBaseline::Fail, None)]
+    fn test_baseline_status_when_old_baseline_missing(
+        #[case] missing_baseline: MissingBaseline,
+        #[case] expected: Option<BaselineStatus>,
+    ) {
+        let home = tempfile::tempdir().unwrap();
+        let dir = home
+            .path()
+            .join("my_package")
+            .join("my_bench")
+            .join("bench");
+        std::fs::create_dir_all(&dir).unwrap();
+        File::create(dir.join("callgrind.bench.out.base@new")).unwrap();
+
+        let actual = find_comparisons(
+            home.path(),
+            &"old".parse::<BaselineName>().unwrap(),
+            &"new".parse::<BaselineName>().unwrap(),
+        )
+        .unwrap();
+
+        assert_eq!(actual.len(), 1);
+        assert!(actual[0].old.is_empty());
+        assert_eq!(actual[0].baseline_status(missing_baseline).ok(), expected);
+    }
+
+    #[test]
+    fn test_baseline_status_when_old_baseline_present() {
+        let comparison =
+            Comparison::from_files(Path::new("/other/old.out"), Path::new("/some/new.out"));
+        assert_eq!(
+            comparison.baseline_status(MissingBaseline::Fail).unwrap(),
+            BaselineStatus::Compared
+        );
+    }
+
+    #[rstest]
+    #[case::callgrind("callgrind.bench.out", ValgrindTool::Callgrind)]
+    #[case::cachegrind("cachegrind.bench.out", ValgrindTool::Cachegrind)]
+    #[case::unknown("some.out", ValgrindTool::Callgrind)]
+    fn test_comparison_from_files(#[case] file_name: &str, #[case] expected_tool: ValgrindTool) {
+        let new = PathBuf::from("/some/dir").join(file_name);
+        let actual = Comparison::from_files(Path::new("/other/old.out"), &new);
+
+        assert_eq!(actual.tool, expected_tool);
+        assert_eq!(actual.dir, PathBuf::from("/some/dir"));
+        assert_eq!(actual.function_name, file_name);
+        assert_eq!(actual.new, vec![new]);
+        assert_eq!(actual.old, vec![PathBuf::from("/other/old.out")]);
+    }
+
+    #[rstest]
+    #[case::empty("", "", &[])]
+    #[case::simple(
+        "/bench/bin --iai-run my_group",
+        "/bench/bin",
+        &["--iai-run", "my_group"]
+    )]
+    #[case::single_quote(
+        "/bench/bin --iai-run my_group it's",
+        "/bench/bin",
+        &["--iai-run", "my_group", "it's"]
+    )]
+    #[case::backslash(
+        r"/bench/bin --iai-run my_group C:\path\file",
+        "/bench/bin",
+        &["--iai-run", "my_group", r"C:\path\file"]
+    )]
+    fn test_split_command(
+        #[case] command: &str,
+        #[case] expected_bin: &str,
+        #[case] expected_args: &[&str],
+    ) {
+        let (bench_bin, bench_args) = split_command(command);
+
+        assert_eq!(bench_bin, PathBuf::from(expected_bin));
+        assert_eq!(
+            bench_args,
+            expected_args
+                .iter()
+                .map(OsString::from)
+                .collect::<Vec<OsString>>()
+        );
+    }
+}
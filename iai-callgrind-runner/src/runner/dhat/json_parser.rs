@@ -0,0 +1,223 @@
+use std::fs::File;
+use std::io::BufReader;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use lazy_static::lazy_static;
+use log::debug;
+use regex::Regex;
+
+use super::model::{DhatData, ProgramPoint};
+use crate::api::DhatMetricKind;
+use crate::runner::metrics::Metrics;
+use crate::runner::summary::{AllocationSiteSummary, MetricsSummary};
+use crate::runner::tool::ToolOutputPath;
+use crate::util::EitherOrBoth;
+
+lazy_static! {
+    static ref STRIP_ADDRESS_RE: Regex =
+        regex::Regex::new(r"^0x[0-9a-fA-F]+:\s*").expect("Regex should compile");
+}
+
+/// The allocation sites identified by their frames
+pub type AllocationSites = IndexMap<Vec<String>, Metrics<DhatMetricKind>>;
+
+/// Parse a single DHAT json output file
+pub fn parse(path: &Path) -> Result<DhatData> {
+    let file = File::open(path)
+        .with_context(|| format!("Error opening dhat output file '{}'", path.display()))?;
+
+    serde_json::from_reader(BufReader::new(file))
+        .with_context(|| format!("Error parsing dhat output file '{}'", path.display()))
+}
+
+/// Parse the allocation sites of all DHAT output files of the `output_path`
+///
+/// The frames of the allocation sites are stored without the addresses, so allocation sites can be
+/// compared between different builds of the benchmark. Allocation sites with the same frames are
+/// summed up, also if they come from different processes. If there are no output files, the
+/// returned `AllocationSites` are empty.
+pub fn parse_allocation_sites(output_path: &ToolOutputPath) -> Result<AllocationSites> {
+    let mut sites = AllocationSites::new();
+
+    let Ok(paths) = output_path.real_paths() else {
+        return Ok(sites);
+    };
+
+    for path in paths {
+        debug!("Parsing dhat output file '{}'", path.display());
+        let data = parse(&path)?;
+
+        for program_point in &data.program_points {
+            let frames = program_point
+                .frames
+                .iter()
+                .filter_map(|index| data.frame_table.get(*index))
+                .filter(|frame| frame.as_str() != "[root]")
+                .map(|frame| STRIP_ADDRESS_RE.replace(frame, "").into_owned())
+                .collect::<Vec<String>>();
+
+            let metrics = program_point_metrics(program_point);
+            if let Some(entry) = sites.get_mut(&frames) {
+                for (metric_kind, metric) in &metrics {
+                    *entry.0.entry(*metric_kind).or_insert(0) += metric;
+                }
+            } else {
+                sites.insert(frames, metrics);
+            }
+        }
+    }
+
+    Ok(sites)
+}
+
+/// Return the `top` [`AllocationSiteSummary`]s of the `new` and `old` allocation sites
+///
+/// The allocation sites are sorted by the absolute difference in the [`DhatMetricKind::TotalBytes`]
+/// between the new and old allocation site. A missing allocation site counts as zero bytes, so
+/// without `old` allocation sites the `new` allocation sites are sorted by their total bytes.
+pub fn summarize_allocation_sites(
+    new: &AllocationSites,
+    old: &AllocationSites,
+    top: usize,
+) -> Vec<AllocationSiteSummary> {
+    let mut sites = new
+        .keys()
+        .chain(old.keys().filter(|frames| !new.contains_key(*frames)))
+        .map(|frames| {
+            let metrics = match (new.get(frames), old.get(frames)) {
+                (Some(new), Some(old)) => EitherOrBoth::Both(new.clone(), old.clone()),
+                (Some(new), None) => EitherOrBoth::Left(new.clone()),
+                (None, Some(old)) => EitherOrBoth::Right(old.clone()),
+                (None, None) => unreachable!("The frames are either in new, old or both"),
+            };
+
+            let total_bytes = metrics.as_ref().map(|m| {
+                m.metric_by_kind(&DhatMetricKind::TotalBytes)
+                    .unwrap_or_default()
+            });
+            let (new_bytes, old_bytes) = match total_bytes {
+                EitherOrBoth::Left(new) => (new, 0),
+                EitherOrBoth::Right(old) => (0, old),
+                EitherOrBoth::Both(new, old) => (new, old),
+            };
+
+            (
+                new_bytes.abs_diff(old_bytes),
+                new_bytes,
+                AllocationSiteSummary {
+                    frames: frames.clone(),
+                    metrics_summary: MetricsSummary::new(metrics),
+                },
+            )
+        })
+        .collect::<Vec<_>>();
+
+    // The sort is stable, so equal allocation sites keep the order of the dhat output file
+    sites.sort_by(|(a_diff, a_bytes, _), (b_diff, b_bytes, _)| {
+        b_diff.cmp(a_diff).then_with(|| b_bytes.cmp(a_bytes))
+    });
+
+    sites
+        .into_iter()
+        .take(top)
+        .map(|(_, _, summary)| summary)
+        .collect()
+}
+
+/// Convert the fields of a [`ProgramPoint`] to [`Metrics`]
+///
+/// Only the total bytes and total blocks are always present. All other metrics are only present
+/// depending on the mode of DHAT.
+fn program_point_metrics(program_point: &ProgramPoint) -> Metrics<DhatMetricKind> {
+    let mut metrics = Metrics::with_metric_kinds([
+        (DhatMetricKind::TotalBytes, program_point.total_bytes),
+        (DhatMetricKind::TotalBlocks, program_point.total_blocks),
+    ]);
+
+    for (metric_kind, metric) in [
+        (DhatMetricKind::AtTGmaxBytes, program_point.at_t_gmax_bytes),
+        (
+            DhatMetricKind::AtTGmaxBlocks,
+            program_point.at_t_gmax_blocks,
+        ),
+        (DhatMetricKind::AtTEndBytes, program_point.at_t_end_bytes),
+        (DhatMetricKind::AtTEndBlocks, program_point.at_t_end_blocks),
+        (DhatMetricKind::ReadsBytes, program_point.reads_bytes),
+        (DhatMetricKind::WritesBytes, program_point.writes_bytes),
+        (
+            DhatMetricKind::TotalLifetimes,
+            program_point.total_lifetimes,
+        ),
+        (DhatMetricKind::MaximumBytes, program_point.maximum_bytes),
+        (DhatMetricKind::MaximumBlocks, program_point.maximum_blocks),
+    ] {
+        if let Some(metric) = metric {
+            metrics.insert(metric_kind, metric);
+        }
+    }
+
+    metrics
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    fn sites_fixture(sites: &[(&str, u64)]) -> AllocationSites {
+        sites
+            .iter()
+            .map(|(frame, bytes)| {
+                (
+                    vec![(*frame).to_owned()],
+                    Metrics::with_metric_kinds([
+                        (DhatMetricKind::TotalBytes, *bytes),
+                        (DhatMetricKind::TotalBlocks, 1),
+                    ]),
+                )
+            })
+            .collect()
+    }
+
+    #[rstest]
+    #[case::new_only(&[("a", 10), ("b", 30), ("c", 20)], &[], 3, &["b", "c", "a"])]
+    #[case::new_only_top(&[("a", 10), ("b", 30), ("c", 20)], &[], 2, &["b", "c"])]
+    #[case::top_zero(&[("a", 10)], &[("a", 20)], 0, &[])]
+    #[case::by_diff(
+        &[("a", 100), ("b", 30), ("c", 20)],
+        &[("a", 100), ("b", 10), ("c", 25)],
+        3,
+        &["b", "c", "a"]
+    )]
+    #[case::old_only(&[("a", 10)], &[("a", 10), ("b", 20)], 2, &["b", "a"])]
+    #[case::equal_diff_by_bytes(&[("a", 10), ("b", 30)], &[("a", 0), ("b", 20)], 2, &["b", "a"])]
+    fn test_summarize_allocation_sites(
+        #[case] new: &[(&str, u64)],
+        #[case] old: &[(&str, u64)],
+        #[case] top: usize,
+        #[case] expected: &[&str],
+    ) {
+        let actual = summarize_allocation_sites(&sites_fixture(new), &sites_fixture(old), top);
+        assert_eq!(
+            actual
+                .iter()
+                .map(|s| s.frames[0].as_str())
+                .collect::<Vec<_>>(),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case::address("0x10A3F4: main (main.rs:10)", "main (main.rs:10)")]
+    #[case::address_upper(
+        "0x4C2DB8F: malloc (in /usr/lib/vgpreload.so)",
+        "malloc (in /usr/lib/vgpreload.so)"
+    )]
+    #[case::no_address("main (main.rs:10)", "main (main.rs:10)")]
+    fn test_strip_address_re(#[case] frame: &str, #[case] expected: &str) {
+        assert_eq!(STRIP_ADDRESS_RE.replace(frame, ""), expected);
+    }
+}
@@ -1 +1,3 @@
+pub mod json_parser;
 pub mod logfile_parser;
+pub mod model;
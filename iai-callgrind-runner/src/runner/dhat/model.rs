@@ -0,0 +1,71 @@
+//! The model of the json output files of DHAT (`dhatFileVersion` 2)
+//!
+//! See also the [DHAT
+//! sources](https://sourceware.org/git/?p=valgrind.git;a=blob;f=dhat/dh_main.c) for a description
+//! of the fields.
+use serde::{Deserialize, Serialize};
+
+/// The top-level data of a DHAT output file
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct DhatData {
+    /// The version of the DHAT output file format
+    #[serde(rename = "dhatFileVersion")]
+    pub dhat_file_version: usize,
+    /// The mode of DHAT (`heap`, `copy` or `ad-hoc`)
+    pub mode: String,
+    /// The executed command
+    #[serde(rename = "cmd")]
+    pub command: String,
+    /// The pid of the process
+    pub pid: i32,
+    /// The program points (the allocation sites in `heap` mode)
+    #[serde(rename = "pps")]
+    pub program_points: Vec<ProgramPoint>,
+    /// The frame table. The frames of a [`ProgramPoint`] are indices into this table.
+    #[serde(rename = "ftbl")]
+    pub frame_table: Vec<String>,
+}
+
+/// A single program point
+///
+/// Only the total bytes and blocks are always present. The other metrics depend on the mode of
+/// DHAT.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ProgramPoint {
+    /// The total bytes
+    #[serde(rename = "tb")]
+    pub total_bytes: u64,
+    /// The total blocks
+    #[serde(rename = "tbk")]
+    pub total_blocks: u64,
+    /// The total lifetimes of all blocks
+    #[serde(rename = "tl")]
+    pub total_lifetimes: Option<u64>,
+    /// The maximum bytes
+    #[serde(rename = "mb")]
+    pub maximum_bytes: Option<u64>,
+    /// The maximum blocks
+    #[serde(rename = "mbk")]
+    pub maximum_blocks: Option<u64>,
+    /// The bytes at t-gmax
+    #[serde(rename = "gb")]
+    pub at_t_gmax_bytes: Option<u64>,
+    /// The blocks at t-gmax
+    #[serde(rename = "gbk")]
+    pub at_t_gmax_blocks: Option<u64>,
+    /// The bytes at t-end
+    #[serde(rename = "eb")]
+    pub at_t_end_bytes: Option<u64>,
+    /// The blocks at t-end
+    #[serde(rename = "ebk")]
+    pub at_t_end_blocks: Option<u64>,
+    /// The bytes read
+    #[serde(rename = "rb")]
+    pub reads_bytes: Option<u64>,
+    /// The bytes written
+    #[serde(rename = "wb")]
+    pub writes_bytes: Option<u64>,
+    /// The indices of the frames into the frame table
+    #[serde(rename = "fs")]
+    pub frames: Vec<usize>,
+}
@@ -0,0 +1,238 @@
+//! Show the valgrind invocations of the benchmarks without running them (`--dry-run`)
+//!
+//! The configuration of each benchmark is resolved like in a real run and the command of the
+//! default tool and all enabled tools is built by the same [`super::tool::ToolCommand`], so the
+//! shown command line is exactly what would be executed. The setup and teardown functions, the
+//! sandbox and the redirection of stdin, stdout and stderr are not part of the command line.
+
+use std::collections::HashSet;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process::Command;
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use serde::Serialize;
+
+use super::format::OutputFormatKind;
+use super::output::stdout;
+use super::tool::ValgrindTool;
+
+/// The valgrind invocation of a single tool
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Invocation {
+    /// The valgrind tool
+    pub tool: ValgrindTool,
+    /// The current directory of the command if it is changed
+    pub current_dir: Option<PathBuf>,
+    /// True if the environment is cleared before the `envs` are set
+    pub env_clear: bool,
+    /// The environment variables which are set for the command
+    ///
+    /// If the environment is cleared, these are all environment variables of the command.
+    pub envs: IndexMap<String, String>,
+    /// The valgrind executable (or the valgrind wrapper)
+    pub program: String,
+    /// The arguments to valgrind including the benchmark executable and its arguments
+    pub args: Vec<String>,
+    /// The complete shell command line to replay the invocation
+    pub command_line: String,
+}
+
+/// All [`Invocation`]s of a single benchmark
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct BenchmarkInvocations {
+    /// The full name of the benchmark `<module>::<group>::<function>[.<id>]`
+    pub name: String,
+    /// The invocation of the default tool followed by the invocations of all enabled tools
+    pub invocations: Vec<Invocation>,
+}
+
+impl Invocation {
+    /// Create a new `Invocation` from the fully configured `command`
+    ///
+    /// If `env_clear` is true, the environment variables which are not removed from the `command`
+    /// are part of the `envs`.
+    pub fn from_command(tool: ValgrindTool, command: &Command, env_clear: bool) -> Self {
+        let mut envs = IndexMap::new();
+        if env_clear {
+            let removed = command
+                .get_envs()
+                .filter(|(_, value)| value.is_none())
+                .map(|(key, _)| key.to_string_lossy().to_string())
+                .collect::<HashSet<_>>();
+            envs.extend(std::env::vars().filter(|(key, _)| !removed.contains(key)));
+        }
+        for (key, value) in command.get_envs() {
+            if let Some(value) = value {
+                envs.insert(
+                    key.to_string_lossy().to_string(),
+                    value.to_string_lossy().to_string(),
+                );
+            }
+        }
+
+        let mut invocation = Self {
+            tool,
+            current_dir: command.get_current_dir().map(PathBuf::from),
+            env_clear,
+            envs,
+            program: command.get_program().to_string_lossy().to_string(),
+            args: command
+                .get_args()
+                .map(|arg| arg.to_string_lossy().to_string())
+                .collect(),
+            command_line: String::new(),
+        };
+        invocation.command_line = invocation.to_command_line();
+        invocation
+    }
+
+    /// Return the shell command line of this invocation
+    pub fn to_command_line(&self) -> String {
+        let mut words = vec![];
+        if self.env_clear || !self.envs.is_empty() {
+            words.push("env".to_owned());
+        }
+        if self.env_clear {
+            words.push("-i".to_owned());
+        }
+        words.extend(
+            self.envs
+                .iter()
+                .map(|(key, value)| format!("{key}={value}")),
+        );
+        words.push(self.program.clone());
+        words.extend(self.args.iter().cloned());
+
+        let command_line = join(&words);
+        match &self.current_dir {
+            Some(dir) => format!(
+                "cd {} && {command_line}",
+                join(&[dir.to_string_lossy().to_string()])
+            ),
+            None => command_line,
+        }
+    }
+}
+
+/// Join and quote the `words` for the shell
+fn join(words: &[String]) -> String {
+    shlex::try_join(words.iter().map(String::as_str)).unwrap_or_else(|_| words.join(" "))
+}
+
+/// Write the `benchmarks` to the `writer` in human-readable form or in json if `output_format` is
+/// one of the json formats
+pub fn write<W: Write>(
+    mut writer: W,
+    benchmarks: &[BenchmarkInvocations],
+    output_format: OutputFormatKind,
+) -> Result<()> {
+    for benchmark in benchmarks {
+        match output_format {
+            OutputFormatKind::Json => {
+                serde_json::to_writer(&mut writer, benchmark)?;
+                writeln!(writer)?;
+            }
+            OutputFormatKind::PrettyJson => {
+                serde_json::to_writer_pretty(&mut writer, benchmark)?;
+                writeln!(writer)?;
+            }
+            OutputFormatKind::Default => {
+                writeln!(writer, "{}", benchmark.name)?;
+                for invocation in &benchmark.invocations {
+                    writeln!(writer, "  {}: {}", invocation.tool, invocation.command_line)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the `benchmarks` to stdout
+pub fn print(benchmarks: &[BenchmarkInvocations], output_format: OutputFormatKind) -> Result<()> {
+    let mut writer = stdout();
+    write(&mut writer, benchmarks, output_format)
+        .and_then(|()| writer.flush().map_err(Into::into))
+        .context("Failed to print the valgrind invocations to stdout")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    fn command() -> Command {
+        let mut command = Command::new("valgrind");
+        command
+            .args([
+                "--tool=callgrind",
+                "--callgrind-out-file=/some dir/callgrind.out",
+            ])
+            .arg("/bench/bin")
+            .args(["--iai-run", "my_group"])
+            .env("MY_VAR", "some value");
+        command
+    }
+
+    #[test]
+    fn test_invocation_from_command() {
+        let mut command = command();
+        command.current_dir("/tmp");
+
+        let invocation = Invocation::from_command(ValgrindTool::Callgrind, &command, false);
+
+        assert_eq!(invocation.current_dir, Some(PathBuf::from("/tmp")));
+        assert_eq!(
+            invocation.envs,
+            IndexMap::from([("MY_VAR".to_owned(), "some value".to_owned())])
+        );
+        assert_eq!(invocation.program, "valgrind");
+        assert_eq!(
+            invocation.command_line,
+            "cd /tmp && env 'MY_VAR=some value' valgrind '--tool=callgrind' \
+             '--callgrind-out-file=/some dir/callgrind.out' /bench/bin --iai-run my_group"
+        );
+    }
+
+    #[test]
+    fn test_invocation_from_command_when_env_clear() {
+        let mut command = command();
+        for (key, _) in std::env::vars() {
+            command.env_remove(key);
+        }
+        command.env("MY_VAR", "some value");
+
+        let invocation = Invocation::from_command(ValgrindTool::Callgrind, &command, true);
+
+        assert_eq!(
+            invocation.envs,
+            IndexMap::from([("MY_VAR".to_owned(), "some value".to_owned())])
+        );
+        assert!(invocation
+            .command_line
+            .starts_with("env -i 'MY_VAR=some value' valgrind "));
+    }
+
+    #[test]
+    fn test_write() {
+        let benchmarks = vec![BenchmarkInvocations {
+            name: "my_bench::my_group::bench_fn".to_owned(),
+            invocations: vec![Invocation::from_command(
+                ValgrindTool::Callgrind,
+                &command(),
+                false,
+            )],
+        }];
+
+        let mut actual = vec![];
+        write(&mut actual, &benchmarks, OutputFormatKind::Default).unwrap();
+
+        let expected = "my_bench::my_group::bench_fn
+  callgrind: env 'MY_VAR=some value' valgrind '--tool=callgrind' '--callgrind-out-file=/some \
+                        dir/callgrind.out' /bench/bin --iai-run my_group\n";
+        assert_eq!(String::from_utf8(actual).unwrap(), expected);
+    }
+}
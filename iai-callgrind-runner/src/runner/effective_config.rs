@@ -0,0 +1,346 @@
+//! Show the effective configuration of benchmarks without running them (`--show-config`)
+//!
+//! The configuration of a benchmark is layered from the `main!` macro, the benchmark group, the
+//! benchmark function, the bench attribute (and for binary benchmarks the `Command`) and finally
+//! the command-line arguments and environment variables. Each field of the resolved configuration
+//! is shown together with the layers the value came from:
+//!
+//! * Fields with a single value like the `regression_config` come from the last layer which sets
+//!   them or are `default` if no layer sets them.
+//! * Fields which accumulate like the `callgrind_args` or `envs` come from all layers which add
+//!   values to them.
+//! * The `tools` come from the last layer with a `tools_override` and all following layers which
+//!   add tools.
+
+use std::fmt::Display;
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+use serde_json::{Map, Value};
+
+use super::format::OutputFormatKind;
+use super::meta::Metadata;
+use super::output::stdout;
+
+/// A layer of the configuration of a benchmark
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub enum Layer {
+    /// The configuration of the `main!` macro
+    Main,
+    /// The configuration of the benchmark group
+    Group,
+    /// The configuration of the `#[library_benchmark]` or `#[binary_benchmark]` function
+    Function,
+    /// The configuration of the `#[bench]` or `#[benches]` attribute
+    Bench,
+    /// The configuration of the `Command` of a binary benchmark
+    Command,
+    /// The command-line arguments and environment variables
+    CommandLine,
+}
+
+/// The resolved value of a single configuration field and the layers it came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct ConfigField {
+    /// The name of the field
+    pub name: String,
+    /// The resolved value of the field (`null` if the default is used)
+    pub value: Value,
+    /// The layers the value came from (empty if the default is used)
+    pub layers: Vec<Layer>,
+}
+
+/// The effective configuration of a single benchmark
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct EffectiveConfig {
+    /// The full name of the benchmark `<module>::<group>::<function>[.<id>]`
+    pub name: String,
+    /// The resolved fields of the configuration
+    pub fields: Vec<ConfigField>,
+}
+
+impl Display for Layer {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Layer::Main => "main",
+            Layer::Group => "group",
+            Layer::Function => "function",
+            Layer::Bench => "bench",
+            Layer::Command => "command",
+            Layer::CommandLine => "command-line",
+        })
+    }
+}
+
+impl EffectiveConfig {
+    /// Create a new `EffectiveConfig` from the `effective` config and its configuration `layers`
+    ///
+    /// The `effective` config is the result of `update_from_all` of all `layers`. The command-line
+    /// layer is added from the `meta` data with the same precedence as in a real benchmark run.
+    pub fn new<T: Serialize>(
+        name: String,
+        effective: &T,
+        layers: &[(Layer, Option<&T>)],
+        meta: &Metadata,
+    ) -> Result<Self> {
+        let mut effective = to_object(effective)?;
+        let mut layers = layers
+            .iter()
+            .filter_map(|(layer, config)| {
+                config.map(|config| to_object(config).map(|o| (*layer, o)))
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        let command_line = to_object(&serde_json::json!({
+            "default_tool": meta.args.default_tool,
+            "callgrind_args": meta.args.callgrind_args,
+            "cachegrind_args": meta.args.cachegrind_args,
+            "regression_config": meta.regression_config,
+        }))?;
+        for (key, value) in &command_line {
+            match (value, effective.get_mut(key)) {
+                (Value::Array(values), Some(Value::Array(effective_values))) => {
+                    effective_values.extend(values.iter().cloned());
+                }
+                (value, Some(effective_value)) if !value.is_null() => {
+                    *effective_value = value.clone();
+                }
+                _ => {}
+            }
+        }
+        layers.push((Layer::CommandLine, command_line));
+
+        let fields = effective
+            .into_iter()
+            .filter(|(key, _)| key != "tools_override")
+            .map(|(key, value)| {
+                let layers = origins(&key, &value, &layers);
+                ConfigField {
+                    name: key,
+                    value,
+                    layers,
+                }
+            })
+            .collect();
+
+        Ok(Self { name, fields })
+    }
+}
+
+/// Serialize the `value` into a json object with `OsString`s converted to plain strings
+fn to_object<T: Serialize>(value: &T) -> Result<Map<String, Value>> {
+    match serde_json::to_value(value).context("Failed to serialize the configuration")? {
+        Value::Object(mut map) => {
+            map.values_mut().for_each(os_strings_to_strings);
+            Ok(map)
+        }
+        _ => Ok(Map::new()),
+    }
+}
+
+/// Replace the serialized form of an `OsString` (`{"Unix": [bytes]}`) with a plain string
+fn os_strings_to_strings(value: &mut Value) {
+    match value {
+        Value::Object(map) if map.len() == 1 && map.contains_key("Unix") => {
+            if let Some(Value::Array(bytes)) = map.get("Unix") {
+                let bytes = bytes
+                    .iter()
+                    .filter_map(|b| b.as_u64().and_then(|b| u8::try_from(b).ok()))
+                    .collect::<Vec<u8>>();
+                *value = Value::String(String::from_utf8_lossy(&bytes).to_string());
+            }
+        }
+        Value::Object(map) => map.values_mut().for_each(os_strings_to_strings),
+        Value::Array(values) => values.iter_mut().for_each(os_strings_to_strings),
+        _ => {}
+    }
+}
+
+/// Return true if the `value` of a layer doesn't contribute to the effective configuration
+fn is_unset(value: Option<&Value>) -> bool {
+    match value {
+        None | Some(Value::Null) => true,
+        Some(Value::Array(values)) => values.is_empty(),
+        Some(_) => false,
+    }
+}
+
+/// Return the layers the `value` of the field `key` came from
+fn origins(key: &str, value: &Value, layers: &[(Layer, Map<String, Value>)]) -> Vec<Layer> {
+    if key == "tools" {
+        // An empty `tools_override` overrides the tools, too
+        let is_override = |config: &Map<String, Value>| {
+            config.get("tools_override").map_or(false, |v| !v.is_null())
+        };
+        let start = layers
+            .iter()
+            .rposition(|(_, config)| is_override(config))
+            .unwrap_or(0);
+        return layers[start..]
+            .iter()
+            .filter(|(_, config)| !is_unset(config.get("tools")) || is_override(config))
+            .map(|(layer, _)| *layer)
+            .collect();
+    }
+
+    let mut contributing = layers
+        .iter()
+        .filter(|(_, config)| !is_unset(config.get(key)))
+        .map(|(layer, _)| *layer);
+    if value.is_array() {
+        contributing.collect()
+    } else {
+        contributing.next_back().into_iter().collect()
+    }
+}
+
+/// Write the `configs` to the `writer` in human-readable form or in json if `output_format` is
+/// one of the json formats
+pub fn write<W: Write>(
+    mut writer: W,
+    configs: &[EffectiveConfig],
+    output_format: OutputFormatKind,
+) -> Result<()> {
+    for config in configs {
+        match output_format {
+            OutputFormatKind::Json => {
+                serde_json::to_writer(&mut writer, config)?;
+                writeln!(writer)?;
+            }
+            OutputFormatKind::PrettyJson => {
+                serde_json::to_writer_pretty(&mut writer, config)?;
+                writeln!(writer)?;
+            }
+            OutputFormatKind::Default => {
+                writeln!(writer, "{}", config.name)?;
+                for field in &config.fields {
+                    let layers = if field.layers.is_empty() {
+                        "default".to_owned()
+                    } else {
+                        field
+                            .layers
+                            .iter()
+                            .map(ToString::to_string)
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    };
+                    writeln!(writer, "  {}: {} ({layers})", field.name, field.value)?;
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Print the `configs` to stdout
+pub fn print(configs: &[EffectiveConfig], output_format: OutputFormatKind) -> Result<()> {
+    let mut writer = stdout();
+    write(&mut writer, configs, output_format)
+        .and_then(|()| writer.flush().map_err(Into::into))
+        .context("Failed to print the effective configuration to stdout")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::ffi::OsString;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+    use serde_json::json;
+
+    use super::*;
+
+    fn layers(values: &[(Layer, Value)]) -> Vec<(Layer, Map<String, Value>)> {
+        values
+            .iter()
+            .map(|(layer, value)| (*layer, value.as_object().unwrap().clone()))
+            .collect()
+    }
+
+    #[rstest]
+    #[case::default("env_clear", json!(null), &[], &[])]
+    #[case::single(
+        "env_clear",
+        json!(false),
+        &[(Layer::Main, json!({"env_clear": true})), (Layer::Bench, json!({"env_clear": false}))],
+        &[Layer::Bench]
+    )]
+    #[case::accumulate(
+        "callgrind_args",
+        json!(["--a", "--b"]),
+        &[
+            (Layer::Main, json!({"callgrind_args": ["--a"]})),
+            (Layer::Group, json!({"callgrind_args": []})),
+            (Layer::CommandLine, json!({"callgrind_args": ["--b"]}))
+        ],
+        &[Layer::Main, Layer::CommandLine]
+    )]
+    #[case::tools(
+        "tools",
+        json!(["dhat"]),
+        &[
+            (Layer::Main, json!({"tools": ["memcheck"], "tools_override": null})),
+            (Layer::Group, json!({"tools": [], "tools_override": []})),
+            (Layer::Bench, json!({"tools": ["dhat"], "tools_override": null})),
+        ],
+        &[Layer::Group, Layer::Bench]
+    )]
+    #[case::tools_without_override(
+        "tools",
+        json!(["memcheck", "dhat"]),
+        &[
+            (Layer::Main, json!({"tools": ["memcheck"], "tools_override": null})),
+            (Layer::Bench, json!({"tools": ["dhat"], "tools_override": null})),
+        ],
+        &[Layer::Main, Layer::Bench]
+    )]
+    fn test_origins(
+        #[case] key: &str,
+        #[case] value: Value,
+        #[case] values: &[(Layer, Value)],
+        #[case] expected: &[Layer],
+    ) {
+        assert_eq!(origins(key, &value, &layers(values)), expected);
+    }
+
+    #[test]
+    fn test_os_strings_to_strings() {
+        let envs: Vec<(OsString, Option<OsString>)> = vec![
+            (OsString::from("FOO"), Some(OsString::from("bar"))),
+            (OsString::from("BAZ"), None),
+        ];
+        let mut value = serde_json::to_value(envs).unwrap();
+        os_strings_to_strings(&mut value);
+        assert_eq!(value, json!([["FOO", "bar"], ["BAZ", null]]));
+    }
+
+    #[test]
+    fn test_write() {
+        let configs = vec![EffectiveConfig {
+            name: "my_bench::my_group::bench_fn".to_owned(),
+            fields: vec![
+                ConfigField {
+                    name: "env_clear".to_owned(),
+                    value: json!(null),
+                    layers: vec![],
+                },
+                ConfigField {
+                    name: "callgrind_args".to_owned(),
+                    value: json!(["--a", "--b"]),
+                    layers: vec![Layer::Main, Layer::CommandLine],
+                },
+            ],
+        }];
+
+        let mut actual = vec![];
+        write(&mut actual, &configs, OutputFormatKind::Default).unwrap();
+
+        let expected = "my_bench::my_group::bench_fn
+  env_clear: null (default)
+  callgrind_args: [\"--a\",\"--b\"] (main, command-line)
+";
+        assert_eq!(String::from_utf8(actual).unwrap(), expected);
+    }
+}
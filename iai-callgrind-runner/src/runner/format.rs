@@ -1,6 +1,7 @@
 use std::borrow::Cow;
 use std::fmt::{Display, Write};
-use std::path::PathBuf;
+use std::io::Write as IoWrite;
+use std::path::{Path, PathBuf};
 
 use anyhow::Result;
 use colored::{Color, ColoredString, Colorize};
@@ -10,9 +11,14 @@ use super::bin_bench::BinBench;
 use super::common::{Config, ModulePath};
 use super::lib_bench::LibBench;
 use super::meta::Metadata;
-use super::summary::{Diffs, MetricsDiff, SegmentDetails, ToolMetricSummary, ToolRun};
+use super::output::stdout;
+use super::summary::{
+    AllocationSiteSummary, Diffs, ErrorContextSummary, FunctionSummary, MetricsDiff, NoiseSummary,
+    SegmentDetails, ToolMetricSummary, ToolRun,
+};
+use super::sweep::SweepSummary;
 use super::tool::ValgrindTool;
-use crate::api::{self, DhatMetricKind, ErrorMetricKind, EventKind};
+use crate::api::{self, DhatMetricKind, ErrorMetricKind, EventKind, MassifMetricKind};
 use crate::util::{make_relative, to_string_signed_short, truncate_str_utf8, EitherOrBoth};
 
 /// The subset of callgrind metrics to format in the given order
@@ -60,6 +66,14 @@ pub const DHAT_DEFAULT: [DhatMetricKind; 8] = [
     DhatMetricKind::WritesBytes,
 ];
 
+/// The massif metrics to format in the given order
+pub const MASSIF_DEFAULT: [MassifMetricKind; 4] = [
+    MassifMetricKind::PeakHeapBytes,
+    MassifMetricKind::PeakExtraHeapBytes,
+    MassifMetricKind::PeakStacksBytes,
+    MassifMetricKind::Snapshots,
+];
+
 /// The string used to signal that a value is not available
 pub const NOT_AVAILABLE: &str = "N/A";
 pub const UNKNOWN: &str = "*********";
@@ -106,7 +120,7 @@ pub trait Formatter {
     {
         if self.get_output_format().is_default() {
             self.format(config, baselines, tool_run)?;
-            print!("{self}");
+            write!(stdout(), "{self}")?;
             self.clear();
         }
         Ok(())
@@ -127,6 +141,7 @@ pub trait Formatter {
 
 pub struct BinaryBenchmarkHeader {
     inner: Header,
+    default_tool: ValgrindTool,
     has_tools_enabled: bool,
     output_format: OutputFormat,
 }
@@ -138,7 +153,7 @@ pub struct ComparisonHeader {
     pub indent: String,
 }
 
-struct Header {
+pub struct Header {
     module_path: String,
     id: Option<String>,
     description: Option<String>,
@@ -146,6 +161,7 @@ struct Header {
 
 pub struct LibraryBenchmarkHeader {
     inner: Header,
+    default_tool: ValgrindTool,
     has_tools_enabled: bool,
     output_format: OutputFormat,
 }
@@ -164,6 +180,9 @@ pub struct OutputFormat {
     pub truncate_description: Option<usize>,
     pub show_intermediate: bool,
     pub show_grid: bool,
+    pub show_allocation_sites: usize,
+    pub show_functions: usize,
+    pub sort_functions_by: EventKind,
 }
 
 #[derive(Debug, Clone)]
@@ -209,6 +228,7 @@ impl BinaryBenchmarkHeader {
                 Some(description),
                 &bin_bench.output_format,
             ),
+            default_tool: bin_bench.default_tool,
             has_tools_enabled: bin_bench.tools.has_tools_enabled(),
             output_format: bin_bench.output_format,
         }
@@ -219,7 +239,7 @@ impl BinaryBenchmarkHeader {
             self.inner.print();
             if self.has_tools_enabled {
                 let mut formatter = VerticalFormatter::new(self.output_format);
-                formatter.format_tool_headline(ValgrindTool::Callgrind);
+                formatter.format_tool_headline(self.default_tool);
                 formatter.print_buffer();
             }
         }
@@ -259,7 +279,7 @@ impl ComparisonHeader {
     }
 
     pub fn print(&self) {
-        println!("{self}");
+        writeln!(stdout(), "{self}").unwrap();
     }
 }
 
@@ -303,7 +323,7 @@ impl Header {
     }
 
     pub fn print(&self) {
-        println!("{self}");
+        writeln!(stdout(), "{self}").unwrap();
     }
 
     pub fn to_title(&self) -> String {
@@ -365,6 +385,7 @@ impl LibraryBenchmarkHeader {
 
         Self {
             inner: header,
+            default_tool: lib_bench.default_tool,
             has_tools_enabled: lib_bench.tools.has_tools_enabled(),
             output_format: lib_bench.output_format,
         }
@@ -375,7 +396,7 @@ impl LibraryBenchmarkHeader {
             self.inner.print();
             if self.has_tools_enabled {
                 let mut formatter = VerticalFormatter::new(self.output_format);
-                formatter.format_tool_headline(ValgrindTool::Callgrind);
+                formatter.format_tool_headline(self.default_tool);
                 formatter.print_buffer();
             }
         }
@@ -407,6 +428,11 @@ impl From<api::OutputFormat> for OutputFormat {
             truncate_description: value.truncate_description.unwrap_or(Some(50)),
             show_intermediate: value.show_intermediate.unwrap_or(false),
             show_grid: value.show_grid.unwrap_or(false),
+            show_allocation_sites: value.show_allocation_sites.unwrap_or(0),
+            show_functions: value.show_functions.map_or(0, |(top, _)| top),
+            sort_functions_by: value
+                .show_functions
+                .map_or(EventKind::Ir, |(_, event_kind)| event_kind),
         }
     }
 }
@@ -418,6 +444,9 @@ impl Default for OutputFormat {
             truncate_description: Some(50),
             show_intermediate: false,
             show_grid: false,
+            show_allocation_sites: 0,
+            show_functions: 0,
+            sort_functions_by: EventKind::Ir,
         }
     }
 }
@@ -452,7 +481,7 @@ impl VerticalFormatter {
 
     /// Print the internal buffer as is and clear it afterwards
     pub fn print_buffer(&mut self) {
-        print!("{}", self.buffer);
+        write!(stdout(), "{}", self.buffer).unwrap();
         self.clear();
     }
 
@@ -777,6 +806,205 @@ impl VerticalFormatter {
         );
     }
 
+    /// Format the top allocation sites of DHAT with their frames and metrics
+    pub fn format_allocation_sites(&mut self, sites: &[AllocationSiteSummary]) {
+        for (index, site) in sites.iter().enumerate() {
+            self.write_indent(&IndentKind::ToolSubHeadline);
+            writeln!(
+                self,
+                "{} {}",
+                "##".yellow(),
+                format!("Allocation site #{}", index + 1).bold()
+            )
+            .unwrap();
+
+            let mut frames = site.frames.iter();
+            if let Some(first) = frames.next() {
+                self.write_indent(&IndentKind::Normal);
+                writeln!(self, "{:<FIELD_WIDTH$}{first}", "Frames:").unwrap();
+                for frame in frames {
+                    self.write_left_indented(frame);
+                }
+            }
+
+            self.format_metrics(
+                DHAT_DEFAULT
+                    .iter()
+                    .filter_map(|e| site.metrics_summary.diff_by_kind(e).map(|d| (e, d))),
+            );
+        }
+    }
+
+    /// Format the distinct errors of memcheck, helgrind or drd
+    ///
+    /// If there are `old` errors to compare with, only the new and fixed errors are shown.
+    pub fn format_error_contexts(&mut self, errors: &[ErrorContextSummary], has_old: bool) {
+        for error in errors {
+            let (headline, color) = match (&error.counts, has_old) {
+                (EitherOrBoth::Left(_), true) => ("New error", Color::Red),
+                (EitherOrBoth::Right(_), true) => ("Fixed error", Color::Green),
+                (EitherOrBoth::Both(..), true) => continue,
+                (_, false) => ("Error", Color::Yellow),
+            };
+
+            self.write_indent(&IndentKind::ToolSubHeadline);
+            writeln!(
+                self,
+                "{} {}",
+                "##".yellow(),
+                format!("{headline}: {}", error.kind).color(color).bold()
+            )
+            .unwrap();
+
+            self.write_indent(&IndentKind::Normal);
+            writeln!(self, "{:<FIELD_WIDTH$}{}", "What:", error.what).unwrap();
+
+            let mut frames = error.stack.iter();
+            if let Some(first) = frames.next() {
+                self.write_indent(&IndentKind::Normal);
+                writeln!(self, "{:<FIELD_WIDTH$}{first}", "Stack:").unwrap();
+                for frame in frames {
+                    self.write_left_indented(frame);
+                }
+            }
+
+            self.write_field(
+                "Count:",
+                &error.counts.as_ref().map(u64::to_string),
+                None,
+                false,
+            );
+            if let Some(suppression) = &error.suppression {
+                self.write_indent(&IndentKind::Normal);
+                writeln!(self, "{:<FIELD_WIDTH$}{suppression}", "Suppression:").unwrap();
+            }
+        }
+    }
+
+    /// Format the path to a generated suppression file and the number of suppressions in it
+    pub fn format_suppressions(&mut self, path: &Path, count: usize) {
+        self.write_indent(&IndentKind::Normal);
+        writeln!(
+            self,
+            "{:<FIELD_WIDTH$}{} in {}",
+            "Suppressions:",
+            count.to_string().bold(),
+            path.display()
+        )
+        .unwrap();
+    }
+
+    /// Format the top functions with their location and the inclusive and exclusive metric of the
+    /// [`EventKind`] the functions are sorted by
+    pub fn format_functions(&mut self, functions: &[FunctionSummary]) {
+        let event_kind = self.output_format.sort_functions_by;
+        for (index, function) in functions.iter().enumerate() {
+            self.write_indent(&IndentKind::ToolSubHeadline);
+            writeln!(
+                self,
+                "{} {}",
+                "##".yellow(),
+                format!("Function #{}", index + 1).bold()
+            )
+            .unwrap();
+
+            self.write_indent(&IndentKind::Normal);
+            writeln!(self, "{:<FIELD_WIDTH$}{}", "Function:", function.function).unwrap();
+            if let Some(file) = &function.file {
+                self.write_indent(&IndentKind::Normal);
+                writeln!(self, "{:<FIELD_WIDTH$}{}", "File:", file.display()).unwrap();
+            }
+
+            self.format_metrics(
+                [
+                    (format!("{event_kind} (self)"), &function.exclusive),
+                    (format!("{event_kind} (incl.)"), &function.inclusive),
+                ]
+                .into_iter()
+                .filter_map(|(description, summary)| {
+                    summary.diff_by_kind(&event_kind).map(|d| (description, d))
+                }),
+            );
+        }
+    }
+
+    /// Format the minimum, median and maximum of the metrics of repeated runs
+    pub fn format_noise(&mut self, noise: &NoiseSummary) {
+        self.write_indent(&IndentKind::ToolSubHeadline);
+        writeln!(
+            self,
+            "{} {} {}",
+            "##".yellow(),
+            format!("Noise over {} runs", noise.runs).bold(),
+            "(median|min..max (spread))".bright_black()
+        )
+        .unwrap();
+
+        for (event_kind, metric) in &noise.metrics {
+            let spread = to_string_signed_short(metric.spread_pct());
+            let right = format!(
+                "{}..{} ({}%)",
+                metric.min,
+                metric.max,
+                spread.trim_start_matches('+')
+            );
+            self.write_field(
+                &format!("{event_kind}:"),
+                &EitherOrBoth::Both(&metric.median.to_string(), &right),
+                None,
+                false,
+            );
+        }
+    }
+
+    /// Format the metric per parameter of a sweep and the estimated complexity
+    pub fn format_sweep(&mut self, sweep: &SweepSummary) {
+        write!(self, "{}", self.indent_sub_header.clone()).unwrap();
+        writeln!(
+            self,
+            "{} {} {}",
+            "Sweep of".yellow().bold(),
+            sweep.function_name.green(),
+            format!("({})", sweep.event_kind).bright_black()
+        )
+        .unwrap();
+
+        for point in &sweep.points {
+            let parameter = if point.parameter.fract() == 0.0f64 {
+                format!("{:.0}", point.parameter)
+            } else {
+                point.parameter.to_string()
+            };
+            self.write_field(
+                &format!("n = {parameter}:"),
+                &point.metrics.clone().map(|v| v.to_string()),
+                None,
+                false,
+            );
+        }
+
+        let complexity = match (sweep.new, sweep.old) {
+            (Some(new), Some(old)) => EitherOrBoth::Both(new.to_string(), old.to_string()),
+            (Some(new), None) => EitherOrBoth::Left(new.to_string()),
+            (None, Some(old)) => EitherOrBoth::Both("N/A".to_owned(), old.to_string()),
+            (None, None) => EitherOrBoth::Left("N/A".to_owned()),
+        };
+        let color = sweep.is_changed().then_some(Color::BrightRed);
+        self.write_field("Complexity:", &complexity, color, false);
+
+        if let (true, Some(new), Some(old)) = (sweep.is_changed(), sweep.new, sweep.old) {
+            self.write_indent(&IndentKind::Normal);
+            writeln!(
+                self,
+                "{}",
+                format!("Growth class changed from {old} to {new}")
+                    .bright_red()
+                    .bold()
+            )
+            .unwrap();
+        }
+    }
+
     pub fn format_tool_headline(&mut self, tool: ValgrindTool) {
         self.write_indent(&IndentKind::ToolHeadline);
 
@@ -849,6 +1077,11 @@ impl Formatter for VerticalFormatter {
                     .iter()
                     .filter_map(|e| summary.diff_by_kind(e).map(|d| (e, d))),
             ),
+            ToolMetricSummary::MassifSummary(summary) => self.format_metrics(
+                MASSIF_DEFAULT
+                    .iter()
+                    .filter_map(|e| summary.diff_by_kind(e).map(|d| (e, d))),
+            ),
             ToolMetricSummary::CallgrindSummary(summary) => {
                 self.format_baseline(baselines);
                 self.format_metrics(
@@ -898,7 +1131,7 @@ impl Formatter for VerticalFormatter {
         } else if tool_run.total.is_none() && !tool_run.segments.is_empty() {
             // Since there is no total, show_all is partly ignored, and we show all data in a little
             // bit more aggregated form without the multiple files headlines. This affects currently
-            // the output of `Massif` and `BBV`.
+            // the output of `BBV`.
             for segment in &tool_run.segments {
                 self.format_command(config, &segment.details.as_ref().map(|i| &i.command));
 
@@ -996,13 +1229,28 @@ pub fn print_no_capture_footer(
     match (stdout_is_pipe, stderr_is_pipe) {
         (true, true) => {}
         (true, false) => {
-            println!("{}", no_capture_footer(NoCapture::Stderr).unwrap());
+            writeln!(
+                super::output::stdout(),
+                "{}",
+                no_capture_footer(NoCapture::Stderr).unwrap()
+            )
+            .unwrap();
         }
         (false, true) => {
-            println!("{}", no_capture_footer(NoCapture::Stdout).unwrap());
+            writeln!(
+                super::output::stdout(),
+                "{}",
+                no_capture_footer(NoCapture::Stdout).unwrap()
+            )
+            .unwrap();
         }
         (false, false) => {
-            println!("{}", no_capture_footer(NoCapture::True).unwrap());
+            writeln!(
+                super::output::stdout(),
+                "{}",
+                no_capture_footer(NoCapture::True).unwrap()
+            )
+            .unwrap();
         }
     }
 }
@@ -0,0 +1,422 @@
+//! Track the metrics of the benchmarks over many runs (`--save-history`, `--show-history`)
+//!
+//! The history of a benchmark is an append-only file in the json lines format in the directory of
+//! the benchmark. Each line is a [`HistoryEntry`] with the total metrics of the default tool
+//! (callgrind or cachegrind) of a single benchmark run. Unlike the `*.old` files, the history
+//! keeps all runs, so a slow creep of the metrics over many commits becomes visible.
+
+use std::collections::BTreeSet;
+use std::fs::{File, OpenOptions};
+use std::io::{BufRead, BufReader, Write};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use anyhow::{Context, Result};
+use colored::Colorize;
+use log::debug;
+use serde::{Deserialize, Serialize};
+
+use super::common::{Config, ModulePath};
+use super::format::{Header, OutputFormat};
+use super::output::stdout;
+use super::summary::{BenchmarkSummary, MetricsSummary};
+use crate::api::EventKind;
+use crate::util::{percentage_diff, to_string_signed_short};
+
+/// The file name of the history in the directory of a benchmark
+pub const FILE_NAME: &str = "history.jsonl";
+
+/// A single benchmark run in the history of a benchmark
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct HistoryEntry {
+    /// The time of the benchmark run in seconds since the unix epoch
+    pub timestamp: u64,
+    /// The git commit of the project if the project is a git repository
+    pub commit: Option<String>,
+    /// The name of the baseline if the run was saved with `--save-baseline`
+    pub baseline: Option<String>,
+    /// The rust path in the form `bench_file::group::bench`
+    pub module_path: String,
+    /// The user provided id of the benchmark
+    pub id: Option<String>,
+    /// The total metrics of the default tool
+    pub metrics: MetricsSummary,
+}
+
+/// A change of an [`EventKind`] between two consecutive [`HistoryEntry`]s
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct StepChange {
+    /// The `EventKind` which changed
+    pub event_kind: EventKind,
+    /// The difference to the previous entry in percent
+    pub diff_pct: f64,
+}
+
+impl HistoryEntry {
+    /// Return the new value of the `event_kind` if present
+    pub fn value(&self, event_kind: EventKind) -> Option<u64> {
+        self.metrics
+            .diff_by_kind(&event_kind)
+            .and_then(|diff| diff.metrics.left().copied())
+    }
+
+    /// Return all `EventKind`s which changed by at least `threshold` percent compared to `previous`
+    pub fn step_changes(&self, previous: &HistoryEntry, threshold: f64) -> Vec<StepChange> {
+        self.metrics
+            .all_diffs()
+            .filter_map(|(event_kind, diff)| {
+                let new = *diff.metrics.left()?;
+                let old = previous.value(*event_kind)?;
+                let diff_pct = percentage_diff(new, old);
+                (diff_pct.abs() >= threshold).then_some(StepChange {
+                    event_kind: *event_kind,
+                    diff_pct,
+                })
+            })
+            .collect()
+    }
+}
+
+/// Return the directory of the benchmark of the `summary`
+fn benchmark_dir(summary: &BenchmarkSummary) -> Option<&Path> {
+    summary
+        .callgrind_summary
+        .as_ref()
+        .map(|summary| &summary.out_paths)
+        .or_else(|| {
+            summary
+                .cachegrind_summary
+                .as_ref()
+                .map(|summary| &summary.out_paths)
+        })
+        .and_then(|paths| paths.first())
+        .and_then(|path| path.parent())
+}
+
+/// Return the current git commit of the project or `None` if it can't be determined
+fn git_commit(project_root: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["rev-parse", "HEAD"])
+        .current_dir(project_root)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+
+    let commit = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+    (!commit.is_empty()).then_some(commit)
+}
+
+/// Append a [`HistoryEntry`] for each of the `summaries` to the history of its benchmark
+///
+/// The `baseline` is the name of the baseline the benchmarks were saved to, if any.
+pub fn save(config: &Config, baseline: Option<&str>, summaries: &[BenchmarkSummary]) -> Result<()> {
+    let timestamp = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |duration| duration.as_secs());
+    let commit = git_commit(&config.meta.project_root);
+
+    for summary in summaries {
+        let (Some(run), Some(dir)) = (summary.default_tool_run(), benchmark_dir(summary)) else {
+            continue;
+        };
+
+        let entry = HistoryEntry {
+            timestamp,
+            commit: commit.clone(),
+            baseline: baseline.map(ToOwned::to_owned),
+            module_path: summary.module_path.clone(),
+            id: summary.id.clone(),
+            metrics: run.total.summary.clone(),
+        };
+
+        let path = dir.join(FILE_NAME);
+        debug!("Appending to history '{}'", path.display());
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&path)
+            .with_context(|| format!("Failed to open history file '{}'", path.display()))?;
+        let mut line = serde_json::to_string(&entry)?;
+        line.push('\n');
+        file.write_all(line.as_bytes())
+            .with_context(|| format!("Failed to write history file '{}'", path.display()))?;
+    }
+
+    Ok(())
+}
+
+/// Load all [`HistoryEntry`]s from the history file at `path`
+pub fn load(path: &Path) -> Result<Vec<HistoryEntry>> {
+    let file = File::open(path)
+        .with_context(|| format!("Failed to open history file '{}'", path.display()))?;
+
+    let mut entries = vec![];
+    for (index, line) in BufReader::new(file).lines().enumerate() {
+        let line =
+            line.with_context(|| format!("Failed to read history file '{}'", path.display()))?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let entry = serde_json::from_str(&line).with_context(|| {
+            format!(
+                "Failed to parse line {} of history file '{}'",
+                index + 1,
+                path.display()
+            )
+        })?;
+        entries.push(entry);
+    }
+
+    Ok(entries)
+}
+
+/// Format the `timestamp` in seconds since the unix epoch as UTC date and time
+fn format_timestamp(timestamp: u64) -> String {
+    let (days, seconds) = (timestamp / 86_400, timestamp % 86_400);
+
+    // The conversion from days to the civil date by Howard Hinnant
+    let z = days + 719_468;
+    let era = z / 146_097;
+    let doe = z - era * 146_097;
+    let yoe = (doe - doe / 1460 + doe / 36_524 - doe / 146_096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + u64::from(month <= 2);
+
+    format!(
+        "{year:04}-{month:02}-{day:02} {:02}:{:02}:{:02}",
+        seconds / 3600,
+        seconds % 3600 / 60,
+        seconds % 60
+    )
+}
+
+/// Print the last `last` entries of the `entries` of a single benchmark
+///
+/// The values of the first `EventKind` of the latest entry are shown for each entry. Changes of any
+/// `EventKind` of at least `threshold` percent between two consecutive entries are flagged as step
+/// change. A total change of at least `threshold` percent without any step change is flagged as
+/// slow creep.
+fn print_entries<W: Write>(
+    writer: &mut W,
+    entries: &[HistoryEntry],
+    last: usize,
+    threshold: f64,
+) -> Result<()> {
+    let entries = &entries[entries.len().saturating_sub(last)..];
+    let (Some(first), Some(latest)) = (entries.first(), entries.last()) else {
+        return Ok(());
+    };
+    let Some((event_kind, _)) = latest.metrics.all_diffs().next() else {
+        return Ok(());
+    };
+    let event_kind = *event_kind;
+
+    let mut has_step_change = false;
+    let mut previous: Option<&HistoryEntry> = None;
+    for entry in entries {
+        let commit = entry
+            .commit
+            .as_deref()
+            .map_or("-", |commit| &commit[..commit.len().min(7)]);
+        let baseline = entry.baseline.as_deref().unwrap_or("-");
+        let value = entry
+            .value(event_kind)
+            .map_or_else(|| "-".to_owned(), |value| value.to_string());
+        write!(
+            writer,
+            "  {} {commit:<7} {baseline:<10} {event_kind}: {value}",
+            format_timestamp(entry.timestamp)
+        )?;
+
+        if let Some(previous) = previous {
+            if let (Some(new), Some(old)) = (entry.value(event_kind), previous.value(event_kind)) {
+                write!(
+                    writer,
+                    " ({}%)",
+                    to_string_signed_short(percentage_diff(new, old))
+                )?;
+            }
+
+            let changes = entry.step_changes(previous, threshold);
+            if !changes.is_empty() {
+                has_step_change = true;
+                let changes = changes
+                    .iter()
+                    .map(|change| {
+                        format!(
+                            "{} {}%",
+                            change.event_kind,
+                            to_string_signed_short(change.diff_pct)
+                        )
+                    })
+                    .collect::<Vec<_>>()
+                    .join(", ");
+                write!(writer, " {} {changes}", "step change:".yellow().bold())?;
+            }
+        }
+        writeln!(writer)?;
+        previous = Some(entry);
+    }
+
+    if let (Some(new), Some(old)) = (latest.value(event_kind), first.value(event_kind)) {
+        let diff_pct = percentage_diff(new, old);
+        write!(
+            writer,
+            "  Change over {} runs: {event_kind} {}%",
+            entries.len(),
+            to_string_signed_short(diff_pct)
+        )?;
+        if !has_step_change && diff_pct.abs() >= threshold {
+            write!(writer, " {}", "slow creep".yellow().bold())?;
+        }
+        writeln!(writer)?;
+    }
+
+    Ok(())
+}
+
+/// Print the last `last` entries of the history of all benchmarks in the `benchmark_dirs`
+///
+/// Benchmarks without a history are skipped.
+pub fn show(benchmark_dirs: &BTreeSet<PathBuf>, last: usize, threshold: f64) -> Result<()> {
+    let output_format = OutputFormat::default();
+    let mut writer = stdout();
+    for dir in benchmark_dirs {
+        let path = dir.join(FILE_NAME);
+        if !path.exists() {
+            debug!("No history found at '{}'", path.display());
+            continue;
+        }
+
+        let entries = load(&path)?;
+        let Some(latest) = entries.last() else {
+            continue;
+        };
+
+        Header::new(
+            &ModulePath::new(&latest.module_path),
+            latest.id.clone(),
+            None,
+            &output_format,
+        )
+        .print();
+        print_entries(&mut writer, &entries, last, threshold)?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use indexmap::indexmap;
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::runner::metrics::Metrics;
+    use crate::util::EitherOrBoth;
+
+    fn entry(timestamp: u64, ir: u64, dr: u64) -> HistoryEntry {
+        HistoryEntry {
+            timestamp,
+            commit: Some("0123456789abcdef".to_owned()),
+            baseline: None,
+            module_path: "bench::group::function".to_owned(),
+            id: Some("id".to_owned()),
+            metrics: MetricsSummary::new(EitherOrBoth::Left(Metrics(indexmap! {
+                EventKind::Ir => ir,
+                EventKind::Dr => dr,
+            }))),
+        }
+    }
+
+    #[rstest]
+    #[case::epoch(0, "1970-01-01 00:00:00")]
+    #[case::leap_day(951_782_400, "2000-02-29 00:00:00")]
+    #[case::time(1_729_164_225, "2024-10-17 11:23:45")]
+    fn test_format_timestamp(#[case] timestamp: u64, #[case] expected: &str) {
+        assert_eq!(format_timestamp(timestamp), expected);
+    }
+
+    #[rstest]
+    #[case::no_change(1000, 1000, &[])]
+    #[case::below_threshold(1040, 1000, &[])]
+    #[case::at_threshold(1050, 1000, &[5.0f64])]
+    #[case::decrease(900, 1000, &[-10.0f64])]
+    fn test_step_changes(#[case] new: u64, #[case] old: u64, #[case] expected: &[f64]) {
+        let previous = entry(0, old, 100);
+        let current = entry(1, new, 100);
+        let expected = expected
+            .iter()
+            .map(|diff_pct| StepChange {
+                event_kind: EventKind::Ir,
+                diff_pct: *diff_pct,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(current.step_changes(&previous, 5.0), expected);
+    }
+
+    #[test]
+    fn test_save_and_load_roundtrip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        let entries = vec![entry(0, 1000, 100), entry(1, 1010, 100)];
+
+        let mut file = File::create(&path).unwrap();
+        for entry in &entries {
+            writeln!(file, "{}", serde_json::to_string(entry).unwrap()).unwrap();
+        }
+        writeln!(file).unwrap();
+
+        assert_eq!(load(&path).unwrap(), entries);
+    }
+
+    #[test]
+    fn test_load_when_invalid_line_then_error() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(FILE_NAME);
+        std::fs::write(&path, "{}\n").unwrap();
+
+        let error = load(&path).unwrap_err();
+        assert!(error.to_string().starts_with("Failed to parse line 1"));
+    }
+
+    #[rstest]
+    #[case::step_change(
+        vec![entry(0, 1000, 100), entry(86_400, 1200, 100)],
+        10,
+        "  1970-01-01 00:00:00 0123456 -          Instructions: 1000\n  \
+         1970-01-02 00:00:00 0123456 -          Instructions: 1200 (+20.0000%) step change: Instructions +20.0000%\n  \
+         Change over 2 runs: Instructions +20.0000%\n"
+    )]
+    #[case::slow_creep(
+        vec![entry(0, 1000, 100), entry(1, 1030, 100), entry(2, 1060, 100)],
+        10,
+        "  1970-01-01 00:00:00 0123456 -          Instructions: 1000\n  \
+         1970-01-01 00:00:01 0123456 -          Instructions: 1030 (+3.00000%)\n  \
+         1970-01-01 00:00:02 0123456 -          Instructions: 1060 (+2.91262%)\n  \
+         Change over 3 runs: Instructions +6.00000% slow creep\n"
+    )]
+    #[case::last(
+        vec![entry(0, 1000, 100), entry(1, 1000, 100), entry(2, 1000, 100)],
+        1,
+        "  1970-01-01 00:00:02 0123456 -          Instructions: 1000\n  \
+         Change over 1 runs: Instructions +0.00000%\n"
+    )]
+    fn test_print_entries(
+        #[case] entries: Vec<HistoryEntry>,
+        #[case] last: usize,
+        #[case] expected: &str,
+    ) {
+        colored::control::set_override(false);
+        let mut output = vec![];
+        print_entries(&mut output, &entries, last, 5.0).unwrap();
+        assert_eq!(String::from_utf8(output).unwrap(), expected);
+    }
+}
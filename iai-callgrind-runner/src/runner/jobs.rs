@@ -0,0 +1,240 @@
+//! Run benchmarks in parallel (`--jobs`)
+//!
+//! Valgrind runs are deterministic, so running benchmarks in parallel doesn't change the metrics.
+//! The output of the benchmarks is captured and printed in the order of the declaration of the
+//! benchmarks.
+
+use std::collections::BTreeMap;
+use std::num::NonZeroUsize;
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::{mpsc, Mutex};
+use std::thread;
+
+use anyhow::Result;
+
+use super::output;
+
+/// Run the `run` function for all `items` with up to `jobs` threads and handle the results in order
+///
+/// The `handle` function is called with the result of `run` in the order of the `items`, after the
+/// captured output of `run` was printed. Items for which `is_isolated` returns true are run on the
+/// current thread after all other running items have finished and no other item is started until
+/// the isolated item is handled.
+///
+/// If `handle` returns an error, no new items are started and the error is returned after the
+/// running items have finished. With a single job, all items are run on the current thread without
+/// capturing the output.
+pub fn run_in_order<T, R, F, I, H>(
+    items: &[T],
+    jobs: NonZeroUsize,
+    run: F,
+    is_isolated: I,
+    mut handle: H,
+) -> Result<()>
+where
+    T: Sync,
+    R: Send,
+    F: Fn(&T) -> R + Sync,
+    I: Fn(&T) -> bool,
+    H: FnMut(&T, R) -> Result<()>,
+{
+    let jobs = jobs.get().min(items.len());
+    if jobs <= 1 {
+        return items.iter().try_for_each(|item| handle(item, run(item)));
+    }
+
+    let (job_sender, job_receiver) = mpsc::channel::<usize>();
+    let job_receiver = Mutex::new(job_receiver);
+    let (result_sender, result_receiver) = mpsc::channel();
+
+    thread::scope(|scope| {
+        for _ in 0..jobs {
+            let job_receiver = &job_receiver;
+            let result_sender = result_sender.clone();
+            let run = &run;
+            scope.spawn(move || loop {
+                let Ok(index) = job_receiver.lock().unwrap().recv() else {
+                    break;
+                };
+                let (result, captured) = output::capture(|| {
+                    panic::catch_unwind(AssertUnwindSafe(|| run(&items[index])))
+                });
+                if result_sender.send((index, result, captured)).is_err() {
+                    break;
+                }
+            });
+        }
+        drop(result_sender);
+
+        // Dropping the `job_sender` on return stops the worker threads
+        let job_sender = job_sender;
+        let mut pending = BTreeMap::new();
+        let (mut next_start, mut next_handle) = (0, 0);
+        while next_handle < items.len() {
+            while next_start < items.len()
+                && next_start - next_handle < jobs
+                && !is_isolated(&items[next_start])
+            {
+                job_sender
+                    .send(next_start)
+                    .expect("The worker threads should be alive");
+                next_start += 1;
+            }
+
+            if next_start == next_handle {
+                let item = &items[next_handle];
+                handle(item, run(item))?;
+                next_start += 1;
+                next_handle += 1;
+                continue;
+            }
+
+            let (index, result, captured) = result_receiver
+                .recv()
+                .expect("The worker threads should be alive");
+            pending.insert(index, (result, captured));
+
+            while let Some((result, captured)) = pending.remove(&next_handle) {
+                captured.print()?;
+                let result = result.unwrap_or_else(|payload| panic::resume_unwind(payload));
+                handle(&items[next_handle], result)?;
+                next_handle += 1;
+            }
+        }
+
+        Ok(())
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::sync::atomic::{AtomicUsize, Ordering};
+    use std::sync::Once;
+    use std::time::Duration;
+
+    use anyhow::anyhow;
+    use log::{info, Log, Metadata, Record};
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    /// A logger which writes the log messages of this module to the `stderr` of the `output`
+    /// module like the logger of the `iai-callgrind-runner` binary
+    struct TestLogger;
+
+    impl Log for TestLogger {
+        fn enabled(&self, metadata: &Metadata) -> bool {
+            metadata.target() == module_path!()
+        }
+
+        fn log(&self, record: &Record) {
+            if self.enabled(record.metadata()) {
+                writeln!(output::stderr(), "{}", record.args()).unwrap();
+            }
+        }
+
+        fn flush(&self) {}
+    }
+
+    fn init_logger() {
+        static INIT: Once = Once::new();
+        INIT.call_once(|| {
+            log::set_logger(&TestLogger).unwrap();
+            log::set_max_level(log::LevelFilter::Info);
+        });
+    }
+
+    #[rstest]
+    #[case::single_job(1, &[])]
+    #[case::more_jobs_than_items(16, &[])]
+    #[case::parallel(3, &[])]
+    #[case::isolated(3, &[0, 4, 5, 9])]
+    fn test_run_in_order(#[case] jobs: usize, #[case] isolated: &[usize]) {
+        let items = (0..10).collect::<Vec<usize>>();
+        let running = AtomicUsize::new(0);
+
+        let mut actual = vec![];
+        run_in_order(
+            &items,
+            NonZeroUsize::new(jobs).unwrap(),
+            |item| {
+                let num_running = running.fetch_add(1, Ordering::SeqCst) + 1;
+                // Let the later items finish first
+                thread::sleep(Duration::from_millis((10 - *item as u64) * 2));
+                running.fetch_sub(1, Ordering::SeqCst);
+                (*item, num_running)
+            },
+            |item| isolated.contains(item),
+            |item, (result, num_running)| {
+                assert_eq!(*item, result);
+                assert!(num_running <= jobs);
+                if isolated.contains(item) {
+                    assert_eq!(num_running, 1);
+                }
+                actual.push(result);
+                Ok(())
+            },
+        )
+        .unwrap();
+
+        assert_eq!(actual, items);
+    }
+
+    #[test]
+    fn test_run_in_order_when_logging_from_two_jobs() {
+        init_logger();
+        let items = [0, 1];
+        let log = |item: &usize| {
+            info!("start {item}");
+            // Let the second item log in between the log messages of the first item
+            thread::sleep(Duration::from_millis(((1 - *item as u64) * 50) + 10));
+            info!("end {item}");
+        };
+
+        let (result, actual) = output::capture(|| {
+            run_in_order(
+                &items,
+                NonZeroUsize::new(2).unwrap(),
+                log,
+                |_| false,
+                |_, ()| Ok(()),
+            )
+        });
+        result.unwrap();
+
+        let ((), expected) = output::capture(|| items.iter().for_each(log));
+        assert_ne!(expected, output::Captured::default());
+        assert_eq!(actual, expected);
+    }
+
+    #[test]
+    fn test_run_in_order_stops_on_error() {
+        let items = (0..10).collect::<Vec<usize>>();
+        let started = AtomicUsize::new(0);
+
+        let mut handled = vec![];
+        let result = run_in_order(
+            &items,
+            NonZeroUsize::new(2).unwrap(),
+            |item| {
+                started.fetch_add(1, Ordering::SeqCst);
+                *item
+            },
+            |_| false,
+            |item, _| {
+                handled.push(*item);
+                if *item == 3 {
+                    Err(anyhow!("Error"))
+                } else {
+                    Ok(())
+                }
+            },
+        );
+
+        assert!(result.is_err());
+        assert_eq!(handled, [0, 1, 2, 3]);
+        assert!(started.load(Ordering::SeqCst) <= 5);
+    }
+}
@@ -1,34 +1,46 @@
 //! The `lib_bench` module
 //!
 //! This module runs all the library benchmarks
-use std::collections::HashMap;
+use std::collections::{BTreeSet, HashMap};
 use std::ffi::OsString;
-use std::io::stderr;
+use std::path::PathBuf;
 
 use anyhow::Result;
+use log::debug;
 
+use super::args::{BenchmarkIdentifiers, NoCapture};
 use super::callgrind::args::Args;
 use super::callgrind::flamegraph::{
     BaselineFlamegraphGenerator, Config as FlamegraphConfig, Flamegraph, FlamegraphGenerator,
     LoadBaselineFlamegraphGenerator, SaveBaselineFlamegraphGenerator,
 };
-use super::callgrind::parser::{CallgrindParser, Sentinel};
-use super::callgrind::summary_parser::SummaryParser;
-use super::callgrind::{RegressionConfig, Summaries};
+use super::callgrind::parser::Sentinel;
+use super::callgrind::summary_parser::parser_factory;
+use super::callgrind::{annotate, functions, RegressionConfig, Summaries};
 use super::common::{Assistant, AssistantKind, Config, ModulePath};
+use super::dry_run::BenchmarkInvocations;
+use super::effective_config::{EffectiveConfig, Layer};
 use super::format::{
     print_no_capture_footer, Formatter, LibraryBenchmarkHeader, OutputFormat, VerticalFormatter,
 };
+use super::list::BenchmarkListing;
 use super::meta::Metadata;
+use super::output::stderr;
 use super::summary::{
-    BaselineKind, BaselineName, BenchmarkKind, BenchmarkSummary, CallgrindRegression,
-    CallgrindSummary, MetricsSummary, SummaryOutput, ToolRun,
+    BaselineKind, BaselineName, BaselineStatus, BenchmarkKind, BenchmarkSummary, CachegrindSummary,
+    CallgrindRegression, CallgrindSummary, MetricsSummary, NoiseSummary, RunSummary, SummaryOutput,
+    ToolRun,
 };
+use super::sweep::SweepSummary;
+use super::tool::args::ToolArgs;
 use super::tool::{
-    RunOptions, ToolCommand, ToolConfig, ToolConfigs, ToolOutputPath, ToolOutputPathKind,
-    ValgrindTool,
+    RunOptions, ToolCommand, ToolConfig, ToolConfigs, ToolOutput, ToolOutputPath,
+    ToolOutputPathKind, ValgrindTool,
+};
+use super::{
+    clean, dry_run, effective_config, history, jobs, list, noise, report, sweep, Error,
+    DEFAULT_TOGGLE,
 };
-use super::{Error, DEFAULT_TOGGLE};
 use crate::api::{self, EntryPoint, LibraryBenchmarkGroups};
 
 mod defaults {
@@ -54,7 +66,11 @@ struct Group {
 
 /// `Groups` is the top-level organizational unit of the `main!` macro for library benchmarks
 #[derive(Debug)]
-struct Groups(Vec<Group>);
+struct Groups {
+    groups: Vec<Group>,
+    /// The output directories of the benchmarks which are filtered out
+    filtered_out_dirs: BTreeSet<PathBuf>,
+}
 
 /// A `LibBench` represents a single benchmark under the `#[library_benchmark]` attribute macro
 ///
@@ -67,13 +83,17 @@ pub struct LibBench {
     pub function_name: String,
     pub args: Option<String>,
     pub run_options: RunOptions,
+    pub default_tool: ValgrindTool,
     pub callgrind_args: Args,
+    pub cachegrind_args: ToolArgs,
     pub flamegraph_config: Option<FlamegraphConfig>,
     pub regression_config: Option<RegressionConfig>,
     pub tools: ToolConfigs,
     pub module_path: ModulePath,
     pub entry_point: EntryPoint,
     pub output_format: OutputFormat,
+    pub sweep: bool,
+    pub effective_config: Option<EffectiveConfig>,
 }
 
 /// Implements [`Benchmark`] to load a [`LibBench`] baseline run and compare against another
@@ -107,7 +127,7 @@ struct SaveBaselineBenchmark {
 ///
 /// Despite having the same name, this trait differs from `bin_bench::Benchmark` and is
 /// designed to run a `LibBench` only.
-trait Benchmark: std::fmt::Debug {
+trait Benchmark: std::fmt::Debug + Sync {
     fn output_path(&self, lib_bench: &LibBench, config: &Config, group: &Group) -> ToolOutputPath;
     fn baselines(&self) -> (Option<String>, Option<String>);
     fn run(&self, lib_bench: &LibBench, config: &Config, group: &Group)
@@ -118,7 +138,7 @@ impl Benchmark for BaselineBenchmark {
     fn output_path(&self, lib_bench: &LibBench, config: &Config, group: &Group) -> ToolOutputPath {
         ToolOutputPath::new(
             ToolOutputPathKind::Out,
-            ValgrindTool::Callgrind,
+            lib_bench.default_tool,
             &self.baseline_kind,
             &config.meta.target_dir,
             &group.module_path,
@@ -142,29 +162,18 @@ impl Benchmark for BaselineBenchmark {
         let header = LibraryBenchmarkHeader::new(lib_bench);
         header.print();
 
-        let callgrind_command = ToolCommand::new(
-            ValgrindTool::Callgrind,
-            &config.meta,
-            config.meta.args.nocapture,
-        );
-
-        let mut callgrind_args = lib_bench.callgrind_args.clone();
-
-        match &lib_bench.entry_point {
-            EntryPoint::None => {}
-            EntryPoint::Default => {
-                callgrind_args.insert_toggle_collect(DEFAULT_TOGGLE);
-            }
-            EntryPoint::Custom(custom) => {
-                callgrind_args.insert_toggle_collect(custom);
-            }
-        };
-
-        let tool_config = ToolConfig::new(ValgrindTool::Callgrind, true, callgrind_args, None);
-
         let bench_args = lib_bench.bench_args(group);
 
         let out_path = self.output_path(lib_bench, config, group);
+        if let BaselineKind::Name(name) = &self.baseline_kind {
+            for tool in lib_bench.tools.missing_baselines(&out_path) {
+                config
+                    .meta
+                    .args
+                    .missing_baseline
+                    .check(name, tool, &lib_bench.module_path)?;
+            }
+        }
         out_path.init()?;
         out_path.shift()?;
 
@@ -184,15 +193,11 @@ impl Benchmark for BaselineBenchmark {
             header.description(),
         )?;
 
-        let output = callgrind_command.run(
-            tool_config,
-            &config.bench_bin,
-            &bench_args,
-            lib_bench.run_options.clone(),
-            &out_path,
-            &lib_bench.module_path,
-            None,
-        )?;
+        let parser = parser_factory(lib_bench.default_tool);
+        let (output, noise) =
+            noise::run_repeated(config.meta.args.repeat, &out_path, parser.as_ref(), || {
+                lib_bench.run_default_tool(config, &bench_args, &out_path)
+            })?;
 
         print_no_capture_footer(
             config.meta.args.nocapture,
@@ -200,12 +205,15 @@ impl Benchmark for BaselineBenchmark {
             lib_bench.run_options.stderr.as_ref(),
         );
 
-        let parser = SummaryParser;
         let parsed_new = parser.parse(&out_path)?;
         let parsed_old = old_path
             .exists()
             .then(|| parser.parse(&old_path))
             .transpose()?;
+        benchmark_summary.baseline_status = BaselineStatus::new(
+            &self.baseline_kind,
+            lib_bench.tools.missing_baselines(&out_path).is_empty(),
+        );
 
         let summaries = Summaries::new(parsed_new, parsed_old);
 
@@ -214,28 +222,24 @@ impl Benchmark for BaselineBenchmark {
             self.baselines(),
             &ToolRun::from(&summaries),
         )?;
+        noise::print(noise.as_ref(), &lib_bench.output_format);
 
         output.dump_log(log::Level::Info);
         log_path.dump_log(log::Level::Info, &mut stderr())?;
 
-        let regressions = lib_bench.check_and_print_regressions(&summaries.total);
-
-        let callgrind_summary = benchmark_summary
-            .callgrind_summary
-            .insert(CallgrindSummary::new(
-                log_path.real_paths()?,
-                out_path.real_paths()?,
-            ));
-
-        callgrind_summary.add_summaries(
-            &config.bench_bin,
+        let callgrind_summary = lib_bench.add_default_tool_summaries(
+            &mut benchmark_summary,
+            config,
+            &out_path,
             &bench_args,
             &self.baselines(),
             summaries,
-            regressions,
-        );
+            noise,
+        )?;
 
-        if let Some(flamegraph_config) = lib_bench.flamegraph_config.clone() {
+        if let (Some(callgrind_summary), Some(flamegraph_config)) =
+            (callgrind_summary, lib_bench.flamegraph_config.clone())
+        {
             callgrind_summary.flamegraphs = BaselineFlamegraphGenerator {
                 baseline_kind: self.baseline_kind.clone(),
             }
@@ -261,6 +265,7 @@ impl Benchmark for BaselineBenchmark {
             None,
             None,
             None,
+            lib_bench.regression_config.as_ref(),
             &lib_bench.output_format,
         )?;
 
@@ -268,6 +273,13 @@ impl Benchmark for BaselineBenchmark {
     }
 }
 
+impl Group {
+    /// Return true if the benchmarks of this group can't be run in parallel with other benchmarks
+    fn is_isolated(&self) -> bool {
+        self.setup.is_some() || self.teardown.is_some()
+    }
+}
+
 impl Groups {
     /// Create this `Groups` from a [`crate::api::LibraryBenchmark`] submitted by the benchmarking
     /// harness
@@ -278,7 +290,9 @@ impl Groups {
     ) -> Result<Self> {
         let global_config = benchmark_groups.config;
         let mut groups = vec![];
+        let mut filtered_out_dirs = BTreeSet::new();
         let meta_callgrind_args = meta.args.callgrind_args.clone().unwrap_or_default();
+        let meta_cachegrind_args = meta.args.cachegrind_args.clone().unwrap_or_default();
 
         for library_benchmark_group in benchmark_groups.groups {
             let group_module_path = module_path.join(&library_benchmark_group.id);
@@ -324,10 +338,59 @@ impl Groups {
                 for (index, library_benchmark_bench) in
                     library_benchmark_benches.benches.into_iter().enumerate()
                 {
+                    let module_path =
+                        group_module_path.join(&library_benchmark_bench.function_name);
+                    let full_name = BenchmarkIdentifiers::full_name(
+                        &module_path,
+                        library_benchmark_bench.id.as_deref(),
+                    );
+                    if meta.args.is_filtered_out(&BenchmarkIdentifiers {
+                        bench_name: &meta.bench_name,
+                        full_name: &full_name,
+                        group: &group.id,
+                        function: &library_benchmark_bench.function_name,
+                        id: library_benchmark_bench.id.as_deref(),
+                    }) {
+                        debug!("Benchmark '{full_name}' is filtered out");
+                        // The output directory is the same for all tools
+                        filtered_out_dirs.insert(
+                            ToolOutputPath::new(
+                                ToolOutputPathKind::Out,
+                                ValgrindTool::Callgrind,
+                                &BaselineKind::Old,
+                                &meta.target_dir,
+                                &group_module_path,
+                                &library_benchmark_bench.id.as_ref().map_or_else(
+                                    || library_benchmark_bench.function_name.clone(),
+                                    |id| format!("{}.{id}", library_benchmark_bench.function_name),
+                                ),
+                            )
+                            .dir,
+                        );
+                        continue;
+                    }
+
                     let config = group_config.clone().update_from_all([
                         library_benchmark_benches.config.as_ref(),
                         library_benchmark_bench.config.as_ref(),
                     ]);
+                    let effective_config = meta
+                        .args
+                        .show_config
+                        .then(|| {
+                            EffectiveConfig::new(
+                                full_name.clone(),
+                                &config,
+                                &[
+                                    (Layer::Main, Some(&global_config)),
+                                    (Layer::Group, library_benchmark_group.config.as_ref()),
+                                    (Layer::Function, library_benchmark_benches.config.as_ref()),
+                                    (Layer::Bench, library_benchmark_bench.config.as_ref()),
+                                ],
+                                meta,
+                            )
+                        })
+                        .transpose()?;
                     let envs = config.resolve_envs();
 
                     let callgrind_args = Args::try_from_raw_args(&[
@@ -336,9 +399,15 @@ impl Groups {
                         &meta_callgrind_args,
                     ])?;
 
+                    let default_tool = ValgrindTool::try_default_tool(config.default_tool, meta)?;
+                    check_default_tool(default_tool, &module_path)?;
+                    let mut cachegrind_args = config.valgrind_args.clone();
+                    cachegrind_args.extend_ignore_flag(config.cachegrind_args.0.iter());
+                    cachegrind_args.extend_ignore_flag(meta_cachegrind_args.0.iter());
+                    let cachegrind_args =
+                        ToolArgs::try_from_raw_args(ValgrindTool::Cachegrind, cachegrind_args)?;
+
                     let flamegraph_config = config.flamegraph_config.map(Into::into);
-                    let module_path =
-                        group_module_path.join(&library_benchmark_bench.function_name);
 
                     let mut output_format = config
                         .output_format
@@ -357,74 +426,226 @@ impl Groups {
                             envs,
                             ..Default::default()
                         },
+                        default_tool,
                         callgrind_args,
+                        cachegrind_args,
                         flamegraph_config,
                         regression_config: api::update_option(
                             &config.regression_config,
                             &meta.regression_config,
                         )
                         .map(Into::into),
-                        tools: ToolConfigs(
-                            config
-                                .tools
-                                .0
-                                .into_iter()
-                                .map(|mut t| {
-                                    if !config.valgrind_args.is_empty() {
-                                        let mut new_args = config.valgrind_args.clone();
-                                        new_args.extend_ignore_flag(t.raw_args.0.iter());
-                                        t.raw_args = new_args;
-                                    }
-                                    t.try_into()
-                                })
-                                .collect::<Result<Vec<_>, _>>()?,
-                        ),
+                        tools: ToolConfigs::new(config.tools, &config.valgrind_args, default_tool)?,
                         module_path,
                         output_format,
+                        sweep: config.sweep.unwrap_or(false),
+                        effective_config,
                     };
                     group.benches.push(lib_bench);
                 }
             }
 
-            groups.push(group);
+            if group.benches.is_empty() {
+                debug!("All benchmarks of group '{}' are filtered out", group.id);
+            } else {
+                groups.push(group);
+            }
+        }
+
+        Ok(Self {
+            groups,
+            filtered_out_dirs,
+        })
+    }
+
+    /// Return the [`EffectiveConfig`]s of all benchmarks
+    fn effective_configs(&self) -> Vec<EffectiveConfig> {
+        self.groups
+            .iter()
+            .flat_map(|group| group.benches.iter())
+            .filter_map(|bench| bench.effective_config.clone())
+            .collect()
+    }
+
+    /// Return the [`BenchmarkInvocations`] of all benchmarks
+    ///
+    /// The invocations use the output paths of the `benchmark`.
+    fn invocations(
+        &self,
+        benchmark: &dyn Benchmark,
+        config: &Config,
+    ) -> Result<Vec<BenchmarkInvocations>> {
+        let mut benchmarks = vec![];
+        for group in &self.groups {
+            for lib_bench in &group.benches {
+                let out_path = benchmark.output_path(lib_bench, config, group);
+                let bench_args = lib_bench.bench_args(group);
+                let mut invocations = vec![ToolCommand::new(
+                    lib_bench.default_tool,
+                    &config.meta,
+                    config.meta.args.nocapture,
+                )
+                .to_invocation(
+                    lib_bench.default_tool_config(),
+                    &config.bench_bin,
+                    &bench_args,
+                    &lib_bench.run_options,
+                    &out_path,
+                )?];
+                for tool_config in lib_bench.tools.0.iter().filter(|t| t.is_enabled) {
+                    invocations.push(
+                        ToolCommand::new(tool_config.tool, &config.meta, NoCapture::False)
+                            .to_invocation(
+                                tool_config.clone(),
+                                &config.bench_bin,
+                                &bench_args,
+                                &lib_bench.run_options,
+                                &out_path.to_tool_output(tool_config.tool),
+                            )?,
+                    );
+                }
+
+                benchmarks.push(BenchmarkInvocations {
+                    name: BenchmarkIdentifiers::full_name(
+                        &lib_bench.module_path,
+                        lib_bench.id.as_deref(),
+                    ),
+                    invocations,
+                });
+            }
         }
 
-        Ok(Self(groups))
+        Ok(benchmarks)
+    }
+
+    /// Return the [`BenchmarkListing`] of all benchmarks
+    fn listings(&self) -> Vec<BenchmarkListing> {
+        self.groups
+            .iter()
+            .flat_map(|group| {
+                group.benches.iter().map(|lib_bench| {
+                    BenchmarkListing::new(
+                        BenchmarkKind::LibraryBenchmark,
+                        &lib_bench.module_path,
+                        lib_bench.id.as_deref(),
+                        lib_bench.args.as_deref(),
+                        lib_bench.default_tool,
+                        &lib_bench.tools,
+                    )
+                })
+            })
+            .collect()
+    }
+
+    /// Return the directories of the output files of all benchmarks including the benchmarks which
+    /// are filtered out
+    fn all_benchmark_dirs(&self, config: &Config) -> BTreeSet<PathBuf> {
+        let mut dirs = self.benchmark_dirs(config);
+        dirs.extend(self.filtered_out_dirs.iter().cloned());
+        dirs
+    }
+
+    /// Return the directories of the output files of all benchmarks
+    fn benchmark_dirs(&self, config: &Config) -> BTreeSet<PathBuf> {
+        self.groups
+            .iter()
+            .flat_map(|group| {
+                group.benches.iter().map(|lib_bench| {
+                    ToolOutputPath::new(
+                        ToolOutputPathKind::Out,
+                        lib_bench.default_tool,
+                        &BaselineKind::Old,
+                        &config.meta.target_dir,
+                        &group.module_path,
+                        &lib_bench.name(),
+                    )
+                    .dir
+                })
+            })
+            .collect()
     }
 
     /// Run all [`LibBench`] benchmarks
-    fn run(&self, benchmark: &dyn Benchmark, config: &Config) -> Result<()> {
+    ///
+    /// The [`BenchmarkSummary`] of each benchmark is pushed to the `summaries` as soon as the
+    /// benchmark has finished, so the `summaries` are complete up to the benchmark which caused an
+    /// error.
+    ///
+    /// With `--jobs` the benchmarks of consecutive groups without setup and teardown functions are
+    /// run in parallel. The benchmarks of a group with a setup or teardown function are run one
+    /// after the other.
+    fn run(
+        &self,
+        benchmark: &dyn Benchmark,
+        config: &Config,
+        summaries: &mut Vec<BenchmarkSummary>,
+    ) -> Result<()> {
         let mut is_regressed = false;
+        // The indices of the `summaries` with the same group and id
+        let mut compare: HashMap<(&str, String), Vec<usize>> = HashMap::new();
 
-        for group in &self.0 {
+        let mut groups = self.groups.iter().peekable();
+        while let Some(group) = groups.next() {
             if let Some(setup) = &group.setup {
                 setup.run(config, &group.module_path)?;
             }
 
-            let mut summaries: HashMap<String, Vec<BenchmarkSummary>> =
-                HashMap::with_capacity(group.benches.len());
-            for bench in &group.benches {
-                let fail_fast = bench
-                    .regression_config
-                    .as_ref()
-                    .map_or(false, |r| r.fail_fast);
-                let summary = benchmark.run(bench, config, group)?;
-                summary.print_and_save(&config.meta.args.output_format)?;
-                summary.check_regression(&mut is_regressed, fail_fast)?;
-
-                if group.compare_by_id && bench.output_format.is_default() {
-                    if let Some(id) = &summary.id {
-                        if let Some(sums) = summaries.get_mut(id) {
-                            for sum in sums.iter() {
-                                sum.compare_and_print(id, &summary, &bench.output_format)?;
+            let mut benches = group
+                .benches
+                .iter()
+                .map(|bench| (group, bench))
+                .collect::<Vec<_>>();
+            if !group.is_isolated() {
+                while let Some(next) = groups.next_if(|next| !next.is_isolated()) {
+                    benches.extend(next.benches.iter().map(|bench| (next, bench)));
+                }
+            }
+
+            let start = summaries.len();
+            jobs::run_in_order(
+                &benches,
+                config.meta.jobs(),
+                |(group, bench)| benchmark.run(bench, config, group),
+                |(group, _)| group.is_isolated(),
+                |(group, bench), summary| {
+                    let mut summary = summary?;
+                    let fail_fast = bench
+                        .regression_config
+                        .as_ref()
+                        .map_or(false, |r| r.fail_fast);
+                    summary.sweep = sweep_summary(
+                        &benches,
+                        &summaries[start..],
+                        &summary,
+                        summaries.len() - start,
+                    );
+                    if let Some(sweep) = &summary.sweep {
+                        sweep::print(sweep, &bench.output_format);
+                    }
+                    summary.print_and_save(&config.meta.args.output_format)?;
+                    if let Err(error) = summary.check_regression(&mut is_regressed, fail_fast) {
+                        summaries.push(summary);
+                        return Err(error);
+                    }
+
+                    if group.compare_by_id && bench.output_format.is_default() {
+                        if let Some(id) = &summary.id {
+                            let indices = compare.entry((&group.id, id.clone())).or_default();
+                            for index in indices.iter() {
+                                summaries[*index].compare_and_print(
+                                    id,
+                                    &summary,
+                                    &bench.output_format,
+                                )?;
                             }
-                            sums.push(summary);
-                        } else {
-                            summaries.insert(id.clone(), vec![summary]);
+                            indices.push(summaries.len());
                         }
                     }
-                }
-            }
+
+                    summaries.push(summary);
+                    Ok(())
+                },
+            )?;
 
             if let Some(teardown) = &group.teardown {
                 teardown.run(config, &group.module_path)?;
@@ -494,14 +715,115 @@ impl LibBench {
         ))
     }
 
+    /// Return the [`ToolConfig`] of the default tool
+    fn default_tool_config(&self) -> ToolConfig {
+        let mut callgrind_args = self.callgrind_args.clone();
+        match &self.entry_point {
+            EntryPoint::None => {}
+            EntryPoint::Default => {
+                callgrind_args.insert_toggle_collect(DEFAULT_TOGGLE);
+            }
+            EntryPoint::Custom(custom) => {
+                callgrind_args.insert_toggle_collect(custom);
+            }
+        };
+
+        ToolConfig::new(ValgrindTool::Callgrind, true, callgrind_args, None)
+    }
+
+    /// Run the default tool once with the output written to the `out_path`
+    fn run_default_tool(
+        &self,
+        config: &Config,
+        bench_args: &[OsString],
+        out_path: &ToolOutputPath,
+    ) -> Result<ToolOutput> {
+        ToolCommand::new(self.default_tool, &config.meta, config.meta.args.nocapture).run(
+            self.default_tool_config(),
+            &config.bench_bin,
+            bench_args,
+            self.run_options.clone(),
+            out_path,
+            &self.module_path,
+            None,
+        )
+    }
+
+    /// Check the `summaries` of the default tool for regressions and add them to the
+    /// [`BenchmarkSummary`]
+    ///
+    /// Returns the [`CallgrindSummary`] if callgrind is the default tool, so the flamegraphs can
+    /// be added to it.
+    fn add_default_tool_summaries<'a>(
+        &self,
+        benchmark_summary: &'a mut BenchmarkSummary,
+        config: &Config,
+        out_path: &ToolOutputPath,
+        bench_args: &[OsString],
+        baselines: &(Option<String>, Option<String>),
+        summaries: Summaries,
+        noise: Option<NoiseSummary>,
+    ) -> Result<Option<&'a mut CallgrindSummary>> {
+        let functions = if self.default_tool == ValgrindTool::Callgrind {
+            if let Some(top) = config.meta.args.annotate {
+                annotate::annotate_and_write(
+                    out_path,
+                    &config.meta.project_root,
+                    top.get(),
+                    self.output_format.sort_functions_by,
+                )?;
+            }
+            functions::summarize_and_print(
+                out_path,
+                &config.meta.project_root,
+                &self.output_format,
+            )?
+        } else {
+            vec![]
+        };
+        let regressions = self.check_and_print_regressions(&summaries.total, noise.as_ref());
+        let log_paths = out_path.to_log_output().real_paths()?;
+        let out_paths = out_path.real_paths()?;
+
+        if self.default_tool == ValgrindTool::Cachegrind {
+            let cachegrind_summary = benchmark_summary
+                .cachegrind_summary
+                .insert(CachegrindSummary::new(log_paths, out_paths));
+            cachegrind_summary.add_summaries(
+                &config.bench_bin,
+                bench_args,
+                baselines,
+                summaries,
+                regressions,
+            );
+            cachegrind_summary.cachegrind_run.total.noise = noise;
+            Ok(None)
+        } else {
+            let callgrind_summary = benchmark_summary
+                .callgrind_summary
+                .insert(CallgrindSummary::new(log_paths, out_paths));
+            callgrind_summary.add_summaries(
+                &config.bench_bin,
+                bench_args,
+                baselines,
+                summaries,
+                regressions,
+            );
+            callgrind_summary.callgrind_run.total.noise = noise;
+            callgrind_summary.functions = functions;
+            Ok(Some(callgrind_summary))
+        }
+    }
+
     /// Check for regressions as defined in [`RegressionConfig`] and print an error if a regression
     /// occurred
     fn check_and_print_regressions(
         &self,
         metrics_summary: &MetricsSummary,
+        noise: Option<&NoiseSummary>,
     ) -> Vec<CallgrindRegression> {
         if let Some(regression_config) = &self.regression_config {
-            regression_config.check_and_print(metrics_summary)
+            regression_config.check_and_print(metrics_summary, noise)
         } else {
             vec![]
         }
@@ -512,7 +834,7 @@ impl Benchmark for LoadBaselineBenchmark {
     fn output_path(&self, lib_bench: &LibBench, config: &Config, group: &Group) -> ToolOutputPath {
         ToolOutputPath::new(
             ToolOutputPathKind::Base(self.loaded_baseline.to_string()),
-            ValgrindTool::Callgrind,
+            lib_bench.default_tool,
             &BaselineKind::Name(self.baseline.clone()),
             &config.meta.target_dir,
             &group.module_path,
@@ -539,7 +861,6 @@ impl Benchmark for LoadBaselineBenchmark {
         let bench_args = lib_bench.bench_args(group);
         let out_path = self.output_path(lib_bench, config, group);
         let old_path = out_path.to_base_path();
-        let log_path = out_path.to_log_output();
 
         let mut benchmark_summary = lib_bench.create_benchmark_summary(
             config,
@@ -548,9 +869,10 @@ impl Benchmark for LoadBaselineBenchmark {
             header.description(),
         )?;
 
-        let parser = SummaryParser;
+        let parser = parser_factory(lib_bench.default_tool);
         let parsed_new = parser.parse(&out_path)?;
         let parsed_old = Some(parser.parse(&old_path)?);
+        benchmark_summary.baseline_status = BaselineStatus::Compared;
         let summaries = Summaries::new(parsed_new, parsed_old);
 
         VerticalFormatter::new(lib_bench.output_format).print(
@@ -559,24 +881,19 @@ impl Benchmark for LoadBaselineBenchmark {
             &ToolRun::from(&summaries),
         )?;
 
-        let regressions = lib_bench.check_and_print_regressions(&summaries.total);
-
-        let callgrind_summary = benchmark_summary
-            .callgrind_summary
-            .insert(CallgrindSummary::new(
-                log_path.real_paths()?,
-                out_path.real_paths()?,
-            ));
-
-        callgrind_summary.add_summaries(
-            &config.bench_bin,
+        let callgrind_summary = lib_bench.add_default_tool_summaries(
+            &mut benchmark_summary,
+            config,
+            &out_path,
             &bench_args,
             &self.baselines(),
             summaries,
-            regressions,
-        );
+            None,
+        )?;
 
-        if let Some(flamegraph_config) = lib_bench.flamegraph_config.clone() {
+        if let (Some(callgrind_summary), Some(flamegraph_config)) =
+            (callgrind_summary, lib_bench.flamegraph_config.clone())
+        {
             callgrind_summary.flamegraphs = LoadBaselineFlamegraphGenerator {
                 loaded_baseline: self.loaded_baseline.clone(),
                 baseline: self.baseline.clone(),
@@ -591,10 +908,12 @@ impl Benchmark for LoadBaselineBenchmark {
             )?;
         }
 
-        benchmark_summary.tool_summaries =
-            lib_bench
-                .tools
-                .run_loaded_vs_base(config, &out_path, &lib_bench.output_format)?;
+        benchmark_summary.tool_summaries = lib_bench.tools.run_loaded_vs_base(
+            config,
+            &out_path,
+            lib_bench.regression_config.as_ref(),
+            &lib_bench.output_format,
+        )?;
 
         Ok(benchmark_summary)
     }
@@ -659,11 +978,73 @@ impl Runner {
 
     /// Run all benchmarks in all groups
     fn run(&self) -> Result<()> {
+        if self.config.meta.args.show_config {
+            return effective_config::print(
+                &self.groups.effective_configs(),
+                self.config.meta.args.output_format,
+            );
+        }
+        if self.config.meta.args.dry_run {
+            return dry_run::print(
+                &self
+                    .groups
+                    .invocations(self.benchmark.as_ref(), &self.config)?,
+                self.config.meta.args.output_format,
+            );
+        }
+        if self.config.meta.args.list {
+            return list::print(
+                &self.groups.listings(),
+                self.config.meta.args.list_format,
+                self.config.meta.args.output_format,
+            );
+        }
+        if let Some(mode) = self.config.meta.args.clean {
+            return clean::run(
+                &self.config,
+                mode,
+                &self.groups.all_benchmark_dirs(&self.config),
+                true,
+            );
+        }
+        if let Some(last) = self.config.meta.args.show_history {
+            return history::show(
+                &self.groups.benchmark_dirs(&self.config),
+                last.get(),
+                self.config.meta.args.history_threshold,
+            );
+        }
+
+        if self.groups.groups.is_empty() {
+            debug!(
+                "All benchmarks of '{}' are filtered out",
+                self.config.bench_file.display()
+            );
+            return Ok(());
+        }
+
         if let Some(setup) = &self.setup {
             setup.run(&self.config, &self.config.module_path)?;
         }
 
-        self.groups.run(self.benchmark.as_ref(), &self.config)?;
+        let mut summaries = vec![];
+        let result = self
+            .groups
+            .run(self.benchmark.as_ref(), &self.config, &mut summaries);
+        report::save_reports(&self.config, &summaries)?;
+        if self.config.meta.args.save_history == Some(true)
+            && self.config.meta.args.load_baseline.is_none()
+        {
+            history::save(
+                &self.config,
+                self.benchmark.baselines().0.as_deref(),
+                &summaries,
+            )?;
+        }
+        if let Some(format) = self.config.meta.args.save_summary {
+            RunSummary::save(&self.config.meta, format, summaries)?;
+        }
+        result?;
 
         if let Some(teardown) = &self.teardown {
             teardown.run(&self.config, &self.config.module_path)?;
@@ -677,7 +1058,7 @@ impl Benchmark for SaveBaselineBenchmark {
     fn output_path(&self, lib_bench: &LibBench, config: &Config, group: &Group) -> ToolOutputPath {
         ToolOutputPath::new(
             ToolOutputPathKind::Base(self.baseline.to_string()),
-            ValgrindTool::Callgrind,
+            lib_bench.default_tool,
             &BaselineKind::Name(self.baseline.clone()),
             &config.meta.target_dir,
             &group.module_path,
@@ -701,31 +1082,12 @@ impl Benchmark for SaveBaselineBenchmark {
         let header = LibraryBenchmarkHeader::new(lib_bench);
         header.print();
 
-        let callgrind_command = ToolCommand::new(
-            ValgrindTool::Callgrind,
-            &config.meta,
-            config.meta.args.nocapture,
-        );
-
-        let mut callgrind_args = lib_bench.callgrind_args.clone();
-        match &lib_bench.entry_point {
-            EntryPoint::None => {}
-            EntryPoint::Default => {
-                callgrind_args.insert_toggle_collect(DEFAULT_TOGGLE);
-            }
-            EntryPoint::Custom(custom) => {
-                callgrind_args.insert_toggle_collect(custom);
-            }
-        };
-
-        let tool_config = ToolConfig::new(ValgrindTool::Callgrind, true, callgrind_args, None);
-
         let bench_args = lib_bench.bench_args(group);
 
         let out_path = self.output_path(lib_bench, config, group);
         out_path.init()?;
 
-        let parser = SummaryParser;
+        let parser = parser_factory(lib_bench.default_tool);
         let parsed_old = out_path
             .exists()
             .then(|| {
@@ -745,15 +1107,10 @@ impl Benchmark for SaveBaselineBenchmark {
             header.description(),
         )?;
 
-        let output = callgrind_command.run(
-            tool_config,
-            &config.bench_bin,
-            &bench_args,
-            lib_bench.run_options.clone(),
-            &out_path,
-            &lib_bench.module_path,
-            None,
-        )?;
+        let (output, noise) =
+            noise::run_repeated(config.meta.args.repeat, &out_path, parser.as_ref(), || {
+                lib_bench.run_default_tool(config, &bench_args, &out_path)
+            })?;
 
         print_no_capture_footer(
             config.meta.args.nocapture,
@@ -762,6 +1119,11 @@ impl Benchmark for SaveBaselineBenchmark {
         );
 
         let parsed_new = parser.parse(&out_path)?;
+        benchmark_summary.baseline_status = if parsed_old.is_some() {
+            BaselineStatus::Compared
+        } else {
+            BaselineStatus::NotRequested
+        };
         let summaries = Summaries::new(parsed_new, parsed_old);
 
         VerticalFormatter::new(lib_bench.output_format).print(
@@ -769,28 +1131,24 @@ impl Benchmark for SaveBaselineBenchmark {
             self.baselines(),
             &ToolRun::from(&summaries),
         )?;
+        noise::print(noise.as_ref(), &lib_bench.output_format);
 
         output.dump_log(log::Level::Info);
         log_path.dump_log(log::Level::Info, &mut stderr())?;
 
-        let regressions = lib_bench.check_and_print_regressions(&summaries.total);
-
-        let callgrind_summary = benchmark_summary
-            .callgrind_summary
-            .insert(CallgrindSummary::new(
-                log_path.real_paths()?,
-                out_path.real_paths()?,
-            ));
-
-        callgrind_summary.add_summaries(
-            &config.bench_bin,
+        let callgrind_summary = lib_bench.add_default_tool_summaries(
+            &mut benchmark_summary,
+            config,
+            &out_path,
             &bench_args,
             &self.baselines(),
             summaries,
-            regressions,
-        );
+            noise,
+        )?;
 
-        if let Some(flamegraph_config) = lib_bench.flamegraph_config.clone() {
+        if let (Some(callgrind_summary), Some(flamegraph_config)) =
+            (callgrind_summary, lib_bench.flamegraph_config.clone())
+        {
             callgrind_summary.flamegraphs = SaveBaselineFlamegraphGenerator {
                 baseline: self.baseline.clone(),
             }
@@ -817,6 +1175,7 @@ impl Benchmark for SaveBaselineBenchmark {
             None,
             None,
             None,
+            lib_bench.regression_config.as_ref(),
             &lib_bench.output_format,
         )?;
 
@@ -824,7 +1183,184 @@ impl Benchmark for SaveBaselineBenchmark {
     }
 }
 
+/// Return an error if the `default_tool` can't be the default tool of a library benchmark
+///
+/// Cachegrind has no `--toggle-collect` option, so it can't start the measurement at the entry
+/// point and would measure the whole benchmark harness instead of just the benchmark function.
+fn check_default_tool(default_tool: ValgrindTool, module_path: &ModulePath) -> Result<()> {
+    if default_tool == ValgrindTool::Cachegrind {
+        return Err(Error::BenchmarkError(
+            default_tool,
+            module_path.clone(),
+            "Cachegrind can't be the default tool of library benchmarks because it doesn't \
+             support the entry point. Use cachegrind as default tool of binary benchmarks only"
+                .to_owned(),
+        )
+        .into());
+    }
+    Ok(())
+}
+
+/// Return the [`SweepSummary`] if the bench at `index` is the last bench of a benchmark function
+/// with `sweep` enabled
+///
+/// The `summaries` are the summaries of the `benches` before the `index` in the same order and the
+/// `summary` is the summary of the bench at `index`. The benches of the same benchmark function are
+/// always consecutive.
+fn sweep_summary(
+    benches: &[(&Group, &LibBench)],
+    summaries: &[BenchmarkSummary],
+    summary: &BenchmarkSummary,
+    index: usize,
+) -> Option<SweepSummary> {
+    let is_same_function =
+        |(group, bench): &(&Group, &LibBench), (other_group, other_bench): &(&Group, &LibBench)| {
+            group.id == other_group.id && bench.bench_index == other_bench.bench_index
+        };
+
+    let current = &benches[index];
+    let bench = current.1;
+    if !bench.sweep
+        || benches
+            .get(index + 1)
+            .map_or(false, |next| is_same_function(next, current))
+    {
+        return None;
+    }
+
+    let first = benches[..index]
+        .iter()
+        .rposition(|other| !is_same_function(other, current))
+        .map_or(0, |position| position + 1);
+    let sweep = benches[first..index]
+        .iter()
+        .zip(&summaries[first..index])
+        .map(|((_, other), summary)| (other.args.as_deref(), summary))
+        .chain(std::iter::once((bench.args.as_deref(), summary)))
+        .collect::<Vec<_>>();
+
+    SweepSummary::new(
+        &bench.function_name,
+        &sweep,
+        bench.regression_config.is_some(),
+    )
+}
+
 /// The top-level method which should be used to initiate running all benchmarks
 pub fn run(benchmark_groups: LibraryBenchmarkGroups, config: Config) -> Result<()> {
     Runner::new(benchmark_groups, config)?.run()
 }
+
+#[cfg(test)]
+mod tests {
+    use std::path::Path;
+
+    use clap::Parser;
+
+    use super::*;
+    use crate::api::{LibraryBenchmark, LibraryBenchmarkBench, LibraryBenchmarkGroup};
+    use crate::runner::args::CommandLineArgs;
+    use crate::runner::clean::CleanMode;
+    use crate::runner::meta::Cmd;
+
+    fn library_benchmark_groups() -> LibraryBenchmarkGroups {
+        LibraryBenchmarkGroups {
+            groups: vec![LibraryBenchmarkGroup {
+                id: "my_group".to_owned(),
+                config: None,
+                compare_by_id: None,
+                library_benchmarks: vec![LibraryBenchmark {
+                    config: None,
+                    benches: ["bench_a", "bench_b"]
+                        .iter()
+                        .map(|function_name| LibraryBenchmarkBench {
+                            function_name: (*function_name).to_owned(),
+                            ..Default::default()
+                        })
+                        .collect(),
+                }],
+                has_setup: false,
+                has_teardown: false,
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn metadata(target_dir: &Path, args: &[&str]) -> Metadata {
+        Metadata {
+            arch: "x86_64".to_owned(),
+            package_name: "my_package".to_owned(),
+            project_root: target_dir.to_owned(),
+            home_dir: target_dir.to_owned(),
+            target_dir: target_dir.to_owned(),
+            valgrind: Cmd {
+                bin: PathBuf::from("valgrind"),
+                args: vec![],
+            },
+            valgrind_wrapper: None,
+            regression_config: None,
+            args: CommandLineArgs::try_parse_from(args).unwrap(),
+            command_line_args: vec![],
+            bench_name: "my_bench".to_owned(),
+        }
+    }
+
+    #[test]
+    fn test_clean_when_filtered_then_keep_filtered_out_benchmarks() {
+        let target_dir = tempfile::tempdir().unwrap();
+        let target_dir = target_dir.path();
+        let meta = metadata(target_dir, &["bench_a", "--clean"]);
+        let module_path = ModulePath::new("my_bench");
+        let groups =
+            Groups::from_library_benchmark(&module_path, library_benchmark_groups(), &meta)
+                .unwrap();
+        let config = Config {
+            package_dir: target_dir.to_owned(),
+            bench_file: PathBuf::from("benches/my_bench.rs"),
+            module_path,
+            bench_bin: PathBuf::from("my_bench"),
+            meta,
+        };
+
+        let group_dir = target_dir.join("my_bench/my_group");
+        for dir in ["bench_a", "bench_b", "stale"] {
+            std::fs::create_dir_all(group_dir.join(dir)).unwrap();
+        }
+
+        clean::run(
+            &config,
+            CleanMode::Delete,
+            &groups.all_benchmark_dirs(&config),
+            true,
+        )
+        .unwrap();
+
+        assert!(group_dir.join("bench_a").exists());
+        assert!(group_dir.join("bench_b").exists());
+        assert!(!group_dir.join("stale").exists());
+    }
+
+    #[test]
+    fn test_groups_when_cachegrind_is_default_tool_then_error() {
+        let target_dir = tempfile::tempdir().unwrap();
+        let meta = metadata(target_dir.path(), &["--default-tool=cachegrind"]);
+
+        let error = Groups::from_library_benchmark(
+            &ModulePath::new("my_bench"),
+            library_benchmark_groups(),
+            &meta,
+        )
+        .unwrap_err();
+
+        assert_eq!(
+            error.downcast_ref::<Error>(),
+            Some(&Error::BenchmarkError(
+                ValgrindTool::Cachegrind,
+                ModulePath::new("my_bench::my_group::bench_a"),
+                "Cachegrind can't be the default tool of library benchmarks because it doesn't \
+                 support the entry point. Use cachegrind as default tool of binary benchmarks only"
+                    .to_owned()
+            ))
+        );
+    }
+}
@@ -0,0 +1,217 @@
+//! List the benchmarks of a benchmark file without running them (`--list`)
+//!
+//! The listing respects the `BENCHNAME` filter and the `--skip` patterns. The default format shows
+//! the full name of each benchmark together with its arguments and tools. The libtest compatible
+//! terse format (`--list --format terse`) prints a single `<name>: benchmark` line per benchmark,
+//! so IDEs and other test runners can discover the benchmarks. With `--output-format=json` each
+//! benchmark is printed as a json object per line.
+
+use std::io::Write;
+
+use anyhow::{Context, Result};
+use serde::Serialize;
+
+use super::args::BenchmarkIdentifiers;
+use super::common::ModulePath;
+use super::format::OutputFormatKind;
+use super::output::stdout;
+use super::summary::BenchmarkKind;
+use super::tool::{ToolConfigs, ValgrindTool};
+
+/// The format of the `--list` output in the style of libtest
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ListFormat {
+    /// Show the name of each benchmark with its arguments and tools
+    #[default]
+    Pretty,
+    /// Show only the name of each benchmark as `<name>: benchmark`
+    Terse,
+}
+
+/// A single benchmark of the `--list` output
+#[derive(Debug, PartialEq, Serialize)]
+pub struct BenchmarkListing {
+    /// The kind of benchmark
+    pub kind: BenchmarkKind,
+    /// The full name of the benchmark `<module>::<group>::<function>[.<id>]`
+    pub name: String,
+    /// The module path `<module>::<group>::<function>` of the benchmark
+    pub module_path: String,
+    /// The id of the benchmark if present
+    pub id: Option<String>,
+    /// The arguments of the benchmark function or command as string
+    pub args: Option<String>,
+    /// The default tool
+    pub default_tool: ValgrindTool,
+    /// The enabled tools besides the default tool
+    pub tools: Vec<ValgrindTool>,
+}
+
+impl BenchmarkListing {
+    /// Create a new `BenchmarkListing`
+    pub fn new(
+        kind: BenchmarkKind,
+        module_path: &ModulePath,
+        id: Option<&str>,
+        args: Option<&str>,
+        default_tool: ValgrindTool,
+        tools: &ToolConfigs,
+    ) -> Self {
+        Self {
+            kind,
+            name: BenchmarkIdentifiers::full_name(module_path, id),
+            module_path: module_path.to_string(),
+            id: id.map(ToOwned::to_owned),
+            args: args.map(ToOwned::to_owned),
+            default_tool,
+            tools: tools
+                .0
+                .iter()
+                .filter(|tool| tool.is_enabled)
+                .map(|tool| tool.tool)
+                .collect(),
+        }
+    }
+}
+
+/// Write the `listings` to the `writer` in the `format` or in json if `output_format` is one of
+/// the json formats
+pub fn write<W: Write>(
+    mut writer: W,
+    listings: &[BenchmarkListing],
+    format: ListFormat,
+    output_format: OutputFormatKind,
+) -> Result<()> {
+    match output_format {
+        OutputFormatKind::Json => {
+            for listing in listings {
+                serde_json::to_writer(&mut writer, listing)?;
+                writeln!(writer)?;
+            }
+            return Ok(());
+        }
+        OutputFormatKind::PrettyJson => {
+            for listing in listings {
+                serde_json::to_writer_pretty(&mut writer, listing)?;
+                writeln!(writer)?;
+            }
+            return Ok(());
+        }
+        OutputFormatKind::Default => {}
+    }
+
+    for listing in listings {
+        writeln!(writer, "{}: benchmark", listing.name)?;
+        if format == ListFormat::Terse {
+            continue;
+        }
+
+        if let Some(args) = &listing.args {
+            writeln!(writer, "  Args:  {args}")?;
+        }
+        let tools = std::iter::once(format!("{} (default)", listing.default_tool))
+            .chain(listing.tools.iter().map(ToString::to_string))
+            .collect::<Vec<_>>()
+            .join(", ");
+        writeln!(writer, "  Tools: {tools}")?;
+    }
+
+    if format == ListFormat::Pretty {
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "{} benchmark{}",
+            listings.len(),
+            if listings.len() == 1 { "" } else { "s" }
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Print the `listings` to stdout
+pub fn print(
+    listings: &[BenchmarkListing],
+    format: ListFormat,
+    output_format: OutputFormatKind,
+) -> Result<()> {
+    let mut writer = stdout();
+    write(&mut writer, listings, format, output_format)
+        .and_then(|()| writer.flush().map_err(Into::into))
+        .context("Failed to print the list of benchmarks to stdout")
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    fn listings() -> Vec<BenchmarkListing> {
+        vec![
+            BenchmarkListing {
+                kind: BenchmarkKind::LibraryBenchmark,
+                name: "my_bench::my_group::bench_fn.small".to_owned(),
+                module_path: "my_bench::my_group::bench_fn".to_owned(),
+                id: Some("small".to_owned()),
+                args: Some("(10)".to_owned()),
+                default_tool: ValgrindTool::Callgrind,
+                tools: vec![ValgrindTool::DHAT, ValgrindTool::Memcheck],
+            },
+            BenchmarkListing {
+                kind: BenchmarkKind::LibraryBenchmark,
+                name: "my_bench::my_group::other_fn".to_owned(),
+                module_path: "my_bench::my_group::other_fn".to_owned(),
+                id: None,
+                args: None,
+                default_tool: ValgrindTool::Cachegrind,
+                tools: vec![],
+            },
+        ]
+    }
+
+    #[rstest]
+    #[case::pretty(
+        ListFormat::Pretty,
+        "my_bench::my_group::bench_fn.small: benchmark
+  Args:  (10)
+  Tools: callgrind (default), dhat, memcheck
+my_bench::my_group::other_fn: benchmark
+  Tools: cachegrind (default)
+
+2 benchmarks
+"
+    )]
+    #[case::terse(
+        ListFormat::Terse,
+        "my_bench::my_group::bench_fn.small: benchmark
+my_bench::my_group::other_fn: benchmark
+"
+    )]
+    fn test_write(#[case] format: ListFormat, #[case] expected: &str) {
+        let mut actual = vec![];
+        write(&mut actual, &listings(), format, OutputFormatKind::Default).unwrap();
+        assert_eq!(String::from_utf8(actual).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_write_json() {
+        let mut actual = vec![];
+        write(
+            &mut actual,
+            &listings()[1..],
+            ListFormat::Terse,
+            OutputFormatKind::Json,
+        )
+        .unwrap();
+
+        let expected = concat!(
+            r#"{"kind":"LibraryBenchmark","name":"my_bench::my_group::other_fn","#,
+            r#""module_path":"my_bench::my_group::other_fn","id":null,"args":null,"#,
+            r#""default_tool":"Cachegrind","tools":[]}"#,
+            "\n"
+        );
+        assert_eq!(String::from_utf8(actual).unwrap(), expected);
+    }
+}
@@ -0,0 +1,143 @@
+use std::fs::File;
+use std::io::{BufRead, BufReader};
+use std::path::{Path, PathBuf};
+
+use anyhow::{anyhow, Context, Result};
+use log::debug;
+
+use crate::api::MassifMetricKind;
+use crate::error::Error;
+use crate::runner::metrics::Metrics;
+use crate::runner::summary::ToolMetrics;
+use crate::runner::tool::generic_parser::GenericLogfileParser;
+use crate::runner::tool::logfile_parser::{Logfile, LogfileParser};
+
+/// The parser for the massif log files and the massif output files
+///
+/// The log file of massif itself doesn't contain any metrics, so the header and details are parsed
+/// like in the [`GenericLogfileParser`]. The metrics are extracted from the `massif.out` file
+/// which belongs to the log file.
+pub struct MassifLogfileParser {
+    pub root_dir: PathBuf,
+}
+
+impl MassifLogfileParser {
+    /// Return the path to the massif output file which belongs to the log file at `path`
+    ///
+    /// The sanitized log file names have the form `massif.<name>[.<pid>].log[.<base>]` and the
+    /// output file names differ only in `out` instead of `log`.
+    fn out_path(path: &Path) -> Result<PathBuf> {
+        let file_name = path
+            .file_name()
+            .and_then(|f| f.to_str())
+            .ok_or_else(|| anyhow!("Invalid massif log file name: '{}'", path.display()))?;
+
+        let index = file_name
+            .rfind(".log")
+            .ok_or_else(|| anyhow!("Invalid massif log file name: '{}'", path.display()))?;
+
+        Ok(path.with_file_name(format!(
+            "{}.out{}",
+            &file_name[..index],
+            &file_name[index + 4..]
+        )))
+    }
+
+    /// Parse the massif output file at `path`
+    ///
+    /// The peaks of the heap, extra heap and stacks are the maximum values over all snapshots.
+    pub fn parse_out_file(path: &Path) -> Result<Metrics<MassifMetricKind>> {
+        let file = File::open(path)
+            .with_context(|| format!("Error opening massif output file '{}'", path.display()))?;
+
+        let mut peak_heap = 0;
+        let mut peak_extra_heap = 0;
+        let mut peak_stacks = 0;
+        let mut snapshots = 0;
+
+        for line in BufReader::new(file).lines() {
+            let line = line?;
+
+            // The heap trees of the detailed snapshots are indented or start with `n<number>:`, so
+            // we only need to look at `key=value` lines with one of the known keys
+            let Some((key, value)) = line.split_once('=') else {
+                continue;
+            };
+
+            let parse_value = || {
+                value.trim().parse::<u64>().with_context(|| {
+                    format!(
+                        "Invalid value of '{key}' in massif output file '{}'",
+                        path.display()
+                    )
+                })
+            };
+
+            match key {
+                "snapshot" => snapshots += 1,
+                "mem_heap_B" => peak_heap = peak_heap.max(parse_value()?),
+                "mem_heap_extra_B" => peak_extra_heap = peak_extra_heap.max(parse_value()?),
+                "mem_stacks_B" => peak_stacks = peak_stacks.max(parse_value()?),
+                _ => {}
+            }
+        }
+
+        if snapshots == 0 {
+            return Err(Error::ParseError((
+                path.to_owned(),
+                "No snapshots found in massif output file".to_owned(),
+            ))
+            .into());
+        }
+
+        Ok(Metrics::with_metric_kinds([
+            (MassifMetricKind::PeakHeapBytes, peak_heap),
+            (MassifMetricKind::PeakExtraHeapBytes, peak_extra_heap),
+            (MassifMetricKind::PeakStacksBytes, peak_stacks),
+            (MassifMetricKind::Snapshots, snapshots),
+        ]))
+    }
+}
+
+impl LogfileParser for MassifLogfileParser {
+    fn parse_single(&self, path: PathBuf) -> Result<Logfile> {
+        let out_path = Self::out_path(&path)?;
+        debug!("Parsing massif output file '{}'", out_path.display());
+
+        let metrics = Self::parse_out_file(&out_path)?;
+
+        let logfile = GenericLogfileParser {
+            root_dir: self.root_dir.clone(),
+        }
+        .parse_single(path)?;
+
+        Ok(Logfile {
+            metrics: ToolMetrics::MassifMetrics(metrics),
+            ..logfile
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use rstest::rstest;
+
+    use super::*;
+
+    #[rstest]
+    #[case::simple("massif.some.log", "massif.some.out")]
+    #[case::with_pid("massif.some.1234.log", "massif.some.1234.out")]
+    #[case::old("massif.some.log.old", "massif.some.out.old")]
+    #[case::base(
+        "massif.some.1234.log.base@default",
+        "massif.some.1234.out.base@default"
+    )]
+    #[case::name_with_log("massif.some.log.log", "massif.some.log.out")]
+    fn test_massif_out_path(#[case] log_path: &str, #[case] expected: &str) {
+        let dir = PathBuf::from("/some/dir");
+        assert_eq!(
+            MassifLogfileParser::out_path(&dir.join(log_path)).unwrap(),
+            dir.join(expected)
+        );
+    }
+}
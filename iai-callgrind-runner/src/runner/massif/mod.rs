@@ -0,0 +1 @@
+pub mod logfile_parser;
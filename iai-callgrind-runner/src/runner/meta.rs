@@ -1,4 +1,5 @@
 use std::ffi::OsString;
+use std::num::NonZeroUsize;
 use std::path::{Path, PathBuf};
 use std::process::Command;
 
@@ -6,7 +7,7 @@ use anyhow::Result;
 use clap::Parser;
 use log::debug;
 
-use super::args::CommandLineArgs;
+use super::args::{CommandLineArgs, NoCapture};
 use super::envs;
 use crate::api::RegressionConfig;
 use crate::util::resolve_binary_path;
@@ -22,8 +23,13 @@ pub struct Cmd {
 #[derive(Debug, Clone)]
 pub struct Metadata {
     pub arch: String,
+    /// The name of the package of the benchmark target
+    pub package_name: String,
     pub project_root: PathBuf,
     /// The absolute path of the `HOME` (per default `$WORKSPACE_ROOT/target/iai`). Plus, if
+    /// configured, the target of the host like `x86_64-linux-unknown-gnu`.
+    pub home_dir: PathBuf,
+    /// The absolute path of the `HOME` (per default `$WORKSPACE_ROOT/target/iai`). Plus, if
     /// configured, the target of the host like `x86_64-linux-unknown-gnu`. The final component is
     /// the `CARGO_PKG_NAME`.
     ///
@@ -35,6 +41,8 @@ pub struct Metadata {
     pub valgrind_wrapper: Option<Cmd>,
     pub regression_config: Option<RegressionConfig>,
     pub args: CommandLineArgs,
+    /// The raw command-line arguments as we receive them from `cargo bench`
+    pub command_line_args: Vec<String>,
     pub bench_name: String,
 }
 
@@ -70,7 +78,7 @@ impl Metadata {
         let project_root = meta.workspace_root.into_std_path_buf();
         debug!("Detected project root: '{}'", project_root.display());
 
-        let target_dir = {
+        let home_dir = {
             let mut home = args.home.as_ref().map_or_else(
                 || {
                     std::env::var_os(envs::CARGO_TARGET_DIR)
@@ -83,10 +91,10 @@ impl Metadata {
             if args.separate_targets {
                 home = home.join(env!("IC_BUILD_TRIPLE").to_ascii_lowercase());
             }
-            home.join(
-                std::env::var_os(envs::CARGO_PKG_NAME).map_or_else(PathBuf::new, PathBuf::from),
-            )
+            home
         };
+        let target_dir = home_dir
+            .join(std::env::var_os(envs::CARGO_PKG_NAME).map_or_else(PathBuf::new, PathBuf::from));
 
         debug!("Detected target directory: '{}'", target_dir.display());
 
@@ -139,6 +147,8 @@ impl Metadata {
 
         Ok(Self {
             arch,
+            package_name: package_name.to_owned(),
+            home_dir,
             target_dir,
             valgrind: Cmd {
                 bin: valgrind_path,
@@ -148,9 +158,22 @@ impl Metadata {
             project_root,
             regression_config: Into::<Option<RegressionConfig>>::into(&args),
             args,
+            command_line_args: raw_command_line_args.to_vec(),
             bench_name,
         })
     }
+
+    /// The number of benchmarks which can be run in parallel (`--jobs`)
+    ///
+    /// If the output of the benchmarks is not captured (`--nocapture`), the benchmarks are run one
+    /// after the other, so the output of the benchmarks doesn't get mixed up.
+    pub fn jobs(&self) -> NonZeroUsize {
+        if self.args.nocapture == NoCapture::False {
+            self.args.jobs
+        } else {
+            NonZeroUsize::new(1).unwrap()
+        }
+    }
 }
 
 impl From<&Metadata> for Command {
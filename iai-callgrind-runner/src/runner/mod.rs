@@ -1,13 +1,27 @@
+pub mod archive;
 pub mod args;
 pub mod bin_bench;
+pub mod cachegrind;
 pub mod callgrind;
+pub mod clean;
 pub mod common;
+pub mod compare;
 pub mod dhat;
+pub mod dry_run;
+pub mod effective_config;
 pub mod format;
+pub mod history;
+pub mod jobs;
 pub mod lib_bench;
+pub mod list;
+pub mod massif;
 pub mod meta;
 pub mod metrics;
+pub mod noise;
+pub mod output;
+pub mod report;
 pub mod summary;
+pub mod sweep;
 pub mod tool;
 
 use std::env::ArgsOs;
@@ -174,6 +188,13 @@ where
 }
 
 pub fn run() -> Result<()> {
+    if std::env::args_os()
+        .nth(1)
+        .map_or(false, |arg| arg == compare::SUBCOMMAND)
+    {
+        return compare::run(std::env::args_os().skip(2));
+    }
+
     let RunnerArgs {
         bench_kind,
         package_dir,
@@ -192,16 +213,6 @@ pub fn run() -> Result<()> {
                 &package_name,
                 &bench_file,
             )?;
-            if meta
-                .args
-                .filter
-                .as_ref()
-                .map_or(false, |filter| !filter.apply(&meta.bench_name))
-            {
-                debug!("Benchmark '{}' is filtered out", bench_file.display());
-                return Ok(());
-            }
-
             let config = Config {
                 package_dir,
                 bench_file,
@@ -209,6 +220,12 @@ pub fn run() -> Result<()> {
                 bench_bin,
                 meta,
             };
+            if let Some(baseline) = &config.meta.args.export_baseline {
+                return archive::export(&config, baseline);
+            }
+            if let Some(path) = &config.meta.args.import_baseline {
+                return archive::import(&config, path);
+            }
 
             lib_bench::run(benchmark_groups, config)
         }
@@ -219,16 +236,6 @@ pub fn run() -> Result<()> {
                 &package_name,
                 &bench_file,
             )?;
-            if meta
-                .args
-                .filter
-                .as_ref()
-                .map_or(false, |filter| !filter.apply(&meta.bench_name))
-            {
-                debug!("Benchmark '{}' is filtered out", bench_file.display());
-                return Ok(());
-            }
-
             let config = Config {
                 package_dir,
                 bench_file,
@@ -236,6 +243,12 @@ pub fn run() -> Result<()> {
                 bench_bin,
                 meta,
             };
+            if let Some(baseline) = &config.meta.args.export_baseline {
+                return archive::export(&config, baseline);
+            }
+            if let Some(path) = &config.meta.args.import_baseline {
+                return archive::import(&config, path);
+            }
 
             bin_bench::run(benchmark_groups, config)
         }
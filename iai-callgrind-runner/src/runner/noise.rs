@@ -0,0 +1,235 @@
+//! Detect the noise of non-deterministic benchmarks by running them repeatedly (`--repeat`)
+//!
+//! Some benchmarks, for example hash maps with random seeds or thread pools, produce slightly
+//! different metrics in each run. Such benchmarks can be run multiple times with the default tool.
+//! The output files of the run with the median of the first metric (usually `Ir`) are kept as if
+//! the benchmark was run only once and the minimum, median and maximum of all metrics over all runs
+//! are recorded in a [`NoiseSummary`].
+
+use std::num::NonZeroUsize;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use log::debug;
+
+use super::callgrind::model::Metrics;
+use super::callgrind::parser::{CallgrindParser, ParserOutput};
+use super::format::{OutputFormat, VerticalFormatter};
+use super::summary::NoiseSummary;
+use super::tool::{ToolOutput, ToolOutputPath};
+
+/// Return the sum of the metrics of all segments of a parsed run
+fn total(parsed: ParserOutput) -> Metrics {
+    let mut iter = parsed.into_iter().map(|(_, _, metrics)| metrics);
+    let mut total = iter.next().unwrap_or_else(Metrics::empty);
+    for metrics in iter {
+        total.add(&metrics);
+    }
+    total
+}
+
+/// Return the index of the run with the median of the first metric
+fn median_run(totals: &[Metrics]) -> usize {
+    let mut indices = (0..totals.len()).collect::<Vec<_>>();
+    indices.sort_by_key(|index| totals[*index].metric_by_index(0));
+    indices[(indices.len() - 1) / 2]
+}
+
+/// Move all output and log files of the `out_path` into the directory `dest`
+fn move_files(out_path: &ToolOutputPath, dest: &Path) -> Result<()> {
+    std::fs::create_dir_all(dest)
+        .with_context(|| format!("Failed to create directory '{}'", dest.display()))?;
+
+    for path in out_path
+        .real_paths()?
+        .into_iter()
+        .chain(out_path.to_log_output().real_paths()?)
+    {
+        let file_name = path.file_name().expect("A file name should be present");
+        let new_path = dest.join(file_name);
+        std::fs::rename(&path, &new_path).with_context(|| {
+            format!(
+                "Failed to move benchmark file from '{}' to '{}'",
+                path.display(),
+                new_path.display()
+            )
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Move all files in the directory `source` back into the directory of the `out_path`
+fn restore_files(source: &Path, out_path: &ToolOutputPath) -> Result<()> {
+    for entry in std::fs::read_dir(source)
+        .with_context(|| format!("Failed to read directory '{}'", source.display()))?
+    {
+        let path = entry?.path();
+        let file_name = path.file_name().expect("A file name should be present");
+        let new_path = out_path.dir.join(file_name);
+        std::fs::rename(&path, &new_path).with_context(|| {
+            format!(
+                "Failed to move benchmark file from '{}' to '{}'",
+                path.display(),
+                new_path.display()
+            )
+        })?;
+    }
+
+    Ok(())
+}
+
+/// Run the default tool `repeat` times with the `run` function
+///
+/// Without `repeat` or if `repeat` is 1, this is the same as calling `run` once. Otherwise, the
+/// output and log files of each run are parsed with the `parser` and moved aside. After all runs,
+/// the files of the run with the median of the first metric are moved back to the `out_path` and
+/// the [`ToolOutput`] of this run is returned together with the [`NoiseSummary`] of all runs.
+pub fn run_repeated<F>(
+    repeat: Option<NonZeroUsize>,
+    out_path: &ToolOutputPath,
+    parser: &dyn CallgrindParser<Output = Metrics>,
+    mut run: F,
+) -> Result<(ToolOutput, Option<NoiseSummary>)>
+where
+    F: FnMut() -> Result<ToolOutput>,
+{
+    let repeat = repeat.map_or(1, NonZeroUsize::get);
+    if repeat == 1 {
+        return run().map(|output| (output, None));
+    }
+
+    // The directory has to be on the same file system as the output files to be able to move them
+    let temp_dir = tempfile::Builder::new()
+        .prefix(".repeat")
+        .tempdir_in(&out_path.dir)
+        .context("Failed to create a temporary directory for the repeated runs")?;
+
+    let mut outputs = vec![];
+    let mut totals = vec![];
+    for index in 0..repeat {
+        debug!("Running repetition {} of {repeat}", index + 1);
+        outputs.push(run()?);
+        totals.push(total(parser.parse(out_path)?));
+        move_files(out_path, &temp_dir.path().join(index.to_string()))?;
+    }
+
+    let median = median_run(&totals);
+    debug!("Keeping the output of repetition {}", median + 1);
+    restore_files(&temp_dir.path().join(median.to_string()), out_path)?;
+
+    Ok((
+        outputs.swap_remove(median),
+        Some(NoiseSummary::new(&totals)),
+    ))
+}
+
+/// Print the [`NoiseSummary`] if present and the `output_format` is the default format
+pub fn print(noise: Option<&NoiseSummary>, output_format: &OutputFormat) {
+    if let Some(noise) = noise {
+        if output_format.is_default() {
+            let mut formatter = VerticalFormatter::new(*output_format);
+            formatter.format_noise(noise);
+            formatter.print_buffer();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::api::EventKind;
+    use crate::runner::callgrind::parser::CallgrindProperties;
+    use crate::runner::callgrind::RegressionConfig;
+    use crate::runner::summary::{CallgrindLimit, MetricsSummary, NoiseMetric};
+    use crate::util::EitherOrBoth;
+
+    fn metrics(ir: u64, dr: u64) -> Metrics {
+        Metrics::with_metric_kinds([(EventKind::Ir, ir), (EventKind::Dr, dr)])
+    }
+
+    #[rstest]
+    #[case::single(&[10], 0)]
+    #[case::odd(&[30, 10, 20], 2)]
+    #[case::even(&[40, 10, 30, 20], 3)]
+    #[case::equal(&[10, 10, 10], 1)]
+    fn test_median_run(#[case] values: &[u64], #[case] expected: usize) {
+        let totals = values
+            .iter()
+            .map(|value| metrics(*value, 0))
+            .collect::<Vec<_>>();
+        assert_eq!(median_run(&totals), expected);
+    }
+
+    #[test]
+    fn test_noise_summary_new() {
+        let totals = [metrics(1010, 5), metrics(1000, 7), metrics(1004, 5)];
+        let noise = NoiseSummary::new(&totals);
+
+        assert_eq!(noise.runs, 3);
+        assert_eq!(
+            noise.metrics.get(&EventKind::Ir),
+            Some(&NoiseMetric {
+                min: 1000,
+                median: 1004,
+                max: 1010,
+                spread: 10
+            })
+        );
+        assert_eq!(
+            noise.metrics.get(&EventKind::Dr),
+            Some(&NoiseMetric {
+                min: 5,
+                median: 5,
+                max: 7,
+                spread: 2
+            })
+        );
+    }
+
+    // The median of `Dr` (110) is not the `Dr` of the run with the median `Ir` (130). The
+    // regression check has to use the metrics of the kept run, which are also displayed and saved.
+    #[test]
+    fn test_regression_check_when_orderings_differ_between_metric_kinds() {
+        let totals = [metrics(1000, 110), metrics(1010, 100), metrics(1004, 130)];
+        let noise = NoiseSummary::new(&totals);
+        let median = median_run(&totals);
+        assert_eq!(median, 2);
+
+        let summary = MetricsSummary::new(EitherOrBoth::Both(
+            totals[median].clone(),
+            metrics(1000, 95),
+        ));
+        let config = RegressionConfig {
+            limits: vec![(EventKind::Ir, 0f64), (EventKind::Dr, 0f64)],
+            ..Default::default()
+        };
+
+        let actual = config
+            .check_and_print(&summary, Some(&noise))
+            .into_iter()
+            .map(|r| (r.event_kind, r.new, r.old, r.limit))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            actual,
+            vec![(
+                EventKind::Dr,
+                130,
+                Some(95),
+                CallgrindLimit::Percentage(0f64)
+            )]
+        );
+    }
+
+    #[test]
+    fn test_total() {
+        let parsed = vec![
+            ("a".into(), CallgrindProperties::default(), metrics(10, 1)),
+            ("b".into(), CallgrindProperties::default(), metrics(20, 2)),
+        ];
+        assert_eq!(total(parsed), metrics(30, 3));
+    }
+}
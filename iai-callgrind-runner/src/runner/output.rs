@@ -0,0 +1,161 @@
+//! The terminal output of the runner which can be captured per thread
+//!
+//! Benchmarks which are run in parallel (`--jobs`) capture their terminal output, so the output can
+//! be printed in the order of the declaration of the benchmarks instead of the order in which the
+//! benchmarks finish. All output of the runner to `stdout` and `stderr` should go through the
+//! [`stdout`] and [`stderr`] writers of this module, including the log messages and the output of
+//! child processes which would otherwise inherit `stdout` or `stderr` (see [`is_captured`]).
+
+use std::cell::RefCell;
+use std::io::{self, Write};
+
+thread_local! {
+    static CAPTURED: RefCell<Option<Captured>> = const { RefCell::new(None) };
+}
+
+/// The output stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Stream {
+    /// The standard output
+    Stdout,
+    /// The standard error
+    Stderr,
+}
+
+/// The `Captured` output of a thread in the order in which it was written
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Captured(Vec<(Stream, Vec<u8>)>);
+
+/// A `Writer` to `stdout` or `stderr` which respects the capturing of the current thread
+#[derive(Debug)]
+pub struct Writer(Stream);
+
+impl Captured {
+    /// Print the captured output to the [`stdout`] and [`stderr`] writers
+    ///
+    /// If the output of the current thread is not captured, this is the real `stdout` and
+    /// `stderr`.
+    pub fn print(&self) -> io::Result<()> {
+        for (stream, bytes) in &self.0 {
+            let mut writer = Writer(*stream);
+            writer.write_all(bytes).and_then(|()| writer.flush())?;
+        }
+        Ok(())
+    }
+
+    fn push(&mut self, stream: Stream, bytes: &[u8]) {
+        match self.0.last_mut() {
+            Some((last, buffer)) if *last == stream => buffer.extend_from_slice(bytes),
+            _ => self.0.push((stream, bytes.to_vec())),
+        }
+    }
+}
+
+impl Write for Writer {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let is_captured = CAPTURED.with(|captured| {
+            captured.borrow_mut().as_mut().map_or(false, |captured| {
+                captured.push(self.0, buf);
+                true
+            })
+        });
+
+        if is_captured {
+            Ok(buf.len())
+        } else {
+            match self.0 {
+                Stream::Stdout => io::stdout().write(buf),
+                Stream::Stderr => io::stderr().write(buf),
+            }
+        }
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        match self.0 {
+            Stream::Stdout => io::stdout().flush(),
+            Stream::Stderr => io::stderr().flush(),
+        }
+    }
+}
+
+/// Run `func` capturing all output of the current thread to the [`stdout`] and [`stderr`] writers
+///
+/// Nested calls are not supported. The output of an inner `capture` would be missing in the
+/// returned output of the outer `capture`.
+pub fn capture<F, T>(func: F) -> (T, Captured)
+where
+    F: FnOnce() -> T,
+{
+    CAPTURED.with(|captured| *captured.borrow_mut() = Some(Captured::default()));
+    let result = func();
+    let captured = CAPTURED
+        .with(|captured| captured.borrow_mut().take())
+        .unwrap_or_default();
+    (result, captured)
+}
+
+/// Return true if the output of the current thread is captured
+///
+/// Child processes can't write into the captured output directly, so their `stdout` and `stderr`
+/// have to be piped and written to the [`stdout`] and [`stderr`] writers instead of being
+/// inherited.
+pub fn is_captured() -> bool {
+    CAPTURED.with(|captured| captured.borrow().is_some())
+}
+
+/// Return a [`Writer`] to `stdout`
+pub fn stdout() -> Writer {
+    Writer(Stream::Stdout)
+}
+
+/// Return a [`Writer`] to `stderr`
+pub fn stderr() -> Writer {
+    Writer(Stream::Stderr)
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    #[test]
+    fn test_capture() {
+        let ((), actual) = capture(|| {
+            write!(stdout(), "out").unwrap();
+            writeln!(stdout(), " more").unwrap();
+            writeln!(stderr(), "err").unwrap();
+            writeln!(stdout(), "out again").unwrap();
+        });
+
+        assert_eq!(
+            actual,
+            Captured(vec![
+                (Stream::Stdout, b"out more\n".to_vec()),
+                (Stream::Stderr, b"err\n".to_vec()),
+                (Stream::Stdout, b"out again\n".to_vec()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_capture_is_per_thread() {
+        let ((), actual) = capture(|| {
+            writeln!(stdout(), "captured").unwrap();
+            std::thread::spawn(|| {
+                let ((), inner) = capture(|| writeln!(stdout(), "other thread").unwrap());
+                assert_eq!(
+                    inner,
+                    Captured(vec![(Stream::Stdout, b"other thread\n".to_vec())])
+                );
+            })
+            .join()
+            .unwrap();
+        });
+
+        assert_eq!(
+            actual,
+            Captured(vec![(Stream::Stdout, b"captured\n".to_vec())])
+        );
+    }
+}
@@ -0,0 +1,422 @@
+//! The `JUnit` XML report
+//!
+//! Each benchmark is a `testcase` with the `module_path` of the benchmark as `classname`. All
+//! regressions of a benchmark are reported in a single `failure` of the `testcase`.
+
+use std::borrow::Cow;
+use std::io::{self, Write};
+
+use anyhow::Result;
+use quick_xml::events::{BytesDecl, BytesText, Event};
+use quick_xml::Writer;
+
+use crate::runner::summary::{
+    BenchmarkSummary, CallgrindLimit, CallgrindRegression, DhatRegression, ErrorRegression,
+    ToolRegression,
+};
+use crate::runner::sweep::SweepSummary;
+use crate::runner::tool::ValgrindTool;
+use crate::util::to_string_signed_short;
+
+/// The `JUnit` XML report of all benchmarks of a benchmark target
+#[derive(Debug)]
+pub struct JunitReport<'a> {
+    name: &'a str,
+    summaries: &'a [BenchmarkSummary],
+}
+
+/// A single regression of a benchmark with the metric kind and the description of the regression
+struct Failure {
+    metric_kind: String,
+    description: String,
+}
+
+impl<'a> JunitReport<'a> {
+    /// Create a new `JunitReport` with the `name` of the test suite
+    pub fn new(name: &'a str, summaries: &'a [BenchmarkSummary]) -> Self {
+        Self { name, summaries }
+    }
+
+    /// Write this report as `JUnit` XML into the `writer`
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let testcases = self
+            .summaries
+            .iter()
+            .map(|summary| (summary, failures(summary)))
+            .collect::<Vec<_>>();
+        let tests = testcases.len().to_string();
+        let num_failures = testcases
+            .iter()
+            .filter(|(_, failures)| !failures.is_empty())
+            .count()
+            .to_string();
+        let name = sanitize(self.name);
+
+        let mut writer = Writer::new_with_indent(writer, b' ', 2);
+        writer.write_event(Event::Decl(BytesDecl::new("1.0", Some("UTF-8"), None)))?;
+        writer
+            .create_element("testsuites")
+            .with_attributes([
+                ("name", name.as_ref()),
+                ("tests", &tests),
+                ("failures", &num_failures),
+                ("errors", "0"),
+            ])
+            .write_inner_content(|writer| {
+                writer
+                    .create_element("testsuite")
+                    .with_attributes([
+                        ("name", name.as_ref()),
+                        ("tests", &tests),
+                        ("failures", &num_failures),
+                        ("errors", "0"),
+                        ("skipped", "0"),
+                    ])
+                    .write_inner_content(|writer| {
+                        for (summary, failures) in &testcases {
+                            write_testcase(writer, summary, failures)?;
+                        }
+                        Ok(())
+                    })?;
+                Ok(())
+            })?;
+        writeln!(writer.get_mut())?;
+
+        Ok(())
+    }
+}
+
+/// Write a single `testcase` with a `failure` if the benchmark has any regressions
+fn write_testcase<W: Write>(
+    writer: &mut Writer<W>,
+    summary: &BenchmarkSummary,
+    failures: &[Failure],
+) -> io::Result<()> {
+    let name = sanitize(summary.id.as_ref().unwrap_or(&summary.function_name));
+    let classname = sanitize(&summary.module_path);
+    let testcase = writer
+        .create_element("testcase")
+        .with_attributes([("name", name.as_ref()), ("classname", classname.as_ref())]);
+
+    if failures.is_empty() {
+        testcase.write_empty()?;
+        return Ok(());
+    }
+
+    let message = format!(
+        "Performance regressed: {}",
+        failures
+            .iter()
+            .map(|f| f.metric_kind.as_str())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    let description = failures
+        .iter()
+        .map(|f| f.description.as_str())
+        .collect::<Vec<_>>()
+        .join("\n");
+
+    testcase.write_inner_content(|writer| {
+        writer
+            .create_element("failure")
+            .with_attributes([
+                ("message", sanitize(&message).as_ref()),
+                ("type", "PerformanceRegression"),
+            ])
+            .write_text_content(BytesText::new(&sanitize(&description)))?;
+        Ok(())
+    })?;
+
+    Ok(())
+}
+
+/// Collect the regressions of the default tool and all other tools of the `summary`
+fn failures(summary: &BenchmarkSummary) -> Vec<Failure> {
+    let mut failures = vec![];
+
+    let default_tool = if summary.cachegrind_summary.is_some() {
+        ValgrindTool::Cachegrind
+    } else {
+        ValgrindTool::Callgrind
+    };
+    if let Some(run) = summary.default_tool_run() {
+        failures.extend(
+            run.total
+                .regressions
+                .iter()
+                .map(|regression| callgrind_failure(default_tool, regression)),
+        );
+    }
+
+    for tool_summary in &summary.tool_summaries {
+        failures.extend(
+            tool_summary
+                .regressions
+                .iter()
+                .map(|regression| match regression {
+                    ToolRegression::Dhat(regression) => dhat_failure(tool_summary.tool, regression),
+                    ToolRegression::Error(regression) => {
+                        error_failure(tool_summary.tool, regression)
+                    }
+                }),
+        );
+    }
+
+    if let Some(sweep) = summary.sweep.as_ref().filter(|sweep| sweep.regressed) {
+        failures.extend(sweep_failure(default_tool, sweep));
+    }
+
+    failures
+}
+
+fn callgrind_failure(tool: ValgrindTool, regression: &CallgrindRegression) -> Failure {
+    let CallgrindRegression {
+        event_kind,
+        new,
+        old,
+        diff_pct,
+        limit,
+    } = regression;
+
+    let description = match (limit, old, diff_pct) {
+        (CallgrindLimit::Percentage(limit), Some(old), Some(diff_pct)) => format!(
+            "{tool}: {event_kind}: new {new}, old {old} ({}%) exceeded the limit of {}%",
+            to_string_signed_short(*diff_pct),
+            to_string_signed_short(*limit)
+        ),
+        (CallgrindLimit::Absolute(limit), Some(old), _) => format!(
+            "{tool}: {event_kind}: new {new}, old {old} ({:+}) exceeded the absolute limit of \
+             {limit:+}",
+            i128::from(*new) - i128::from(*old)
+        ),
+        (CallgrindLimit::Hard(limit), ..) => {
+            format!("{tool}: {event_kind}: new {new} exceeded the hard limit of {limit}")
+        }
+        _ => unreachable!("Regressions of percentage and absolute limits always have an old value"),
+    };
+
+    Failure {
+        metric_kind: event_kind.to_string(),
+        description,
+    }
+}
+
+fn sweep_failure(tool: ValgrindTool, sweep: &SweepSummary) -> Option<Failure> {
+    let (new, old) = sweep.new.zip(sweep.old)?;
+    Some(Failure {
+        metric_kind: "Growth class".to_owned(),
+        description: format!(
+            "{tool}: {}: growth class of {} increased from {old} to {new}",
+            sweep.event_kind, sweep.function_name
+        ),
+    })
+}
+
+fn dhat_failure(tool: ValgrindTool, regression: &DhatRegression) -> Failure {
+    let DhatRegression {
+        metric_kind,
+        new,
+        old,
+        diff_pct,
+        limit,
+    } = regression;
+
+    Failure {
+        metric_kind: metric_kind.to_string(),
+        description: format!(
+            "{tool}: {metric_kind}: new {new}, old {old} ({}%) exceeded the limit of {}%",
+            to_string_signed_short(*diff_pct),
+            to_string_signed_short(*limit)
+        ),
+    }
+}
+
+fn error_failure(tool: ValgrindTool, regression: &ErrorRegression) -> Failure {
+    let ErrorRegression {
+        metric_kind,
+        new,
+        old,
+        limit,
+    } = regression;
+
+    let description = match (limit, old) {
+        (Some(limit), _) => {
+            format!("{tool}: {metric_kind}: new {new} exceeded the error limit of {limit}")
+        }
+        (None, Some(old)) => format!("{tool}: {metric_kind}: new {new} increased from old {old}"),
+        (None, None) => format!("{tool}: {metric_kind}: new {new} increased"),
+    };
+
+    Failure {
+        metric_kind: metric_kind.to_string(),
+        description,
+    }
+}
+
+/// Replace the characters which are not allowed in XML 1.0 with the replacement character
+///
+/// These are the control characters except for tab, newline and carriage return and the
+/// noncharacters `U+FFFE` and `U+FFFF`. The escaping of the markup characters like `<` is done by
+/// the [`Writer`].
+fn sanitize(value: &str) -> Cow<'_, str> {
+    let is_forbidden = |c: char| {
+        (c < ' ' && !matches!(c, '\t' | '\n' | '\r')) || matches!(c, '\u{fffe}' | '\u{ffff}')
+    };
+
+    if value.contains(is_forbidden) {
+        Cow::Owned(
+            value
+                .chars()
+                .map(|c| if is_forbidden(c) { '\u{fffd}' } else { c })
+                .collect(),
+        )
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::api::EventKind;
+    use crate::runner::common::ModulePath;
+    use crate::runner::summary::{BenchmarkKind, CallgrindSummary};
+    use crate::runner::sweep::Complexity;
+
+    fn summary_fixture(
+        module_path: &str,
+        id: Option<&str>,
+        regressions: Vec<CallgrindRegression>,
+    ) -> BenchmarkSummary {
+        let mut summary = BenchmarkSummary::new(
+            BenchmarkKind::LibraryBenchmark,
+            PathBuf::from("/project"),
+            PathBuf::from("/project"),
+            PathBuf::from("benches/my_bench.rs"),
+            PathBuf::from("/project/target/release/deps/my_bench"),
+            &ModulePath::new(module_path),
+            module_path.rsplit("::").next().unwrap(),
+            id.map(ToOwned::to_owned),
+            None,
+            None,
+        );
+        let mut callgrind_summary = CallgrindSummary::new(vec![], vec![]);
+        callgrind_summary.callgrind_run.total.regressions = regressions;
+        summary.callgrind_summary = Some(callgrind_summary);
+        summary
+    }
+
+    #[test]
+    fn test_junit_report() {
+        let summaries = [
+            summary_fixture("my_bench::my_group::bench_fib", Some("short"), vec![]),
+            summary_fixture(
+                "my_bench::my_group::bench_fib",
+                Some("long"),
+                vec![
+                    CallgrindRegression {
+                        event_kind: EventKind::Ir,
+                        new: 2000,
+                        old: Some(1000),
+                        diff_pct: Some(100.0f64),
+                        limit: CallgrindLimit::Percentage(5.0),
+                    },
+                    CallgrindRegression {
+                        event_kind: EventKind::EstimatedCycles,
+                        new: 3000,
+                        old: None,
+                        diff_pct: None,
+                        limit: CallgrindLimit::Hard(2500),
+                    },
+                ],
+            ),
+            summary_fixture("my_bench::other_group::bench_<T>", None, vec![]),
+        ];
+
+        let mut actual = vec![];
+        JunitReport::new("my_bench", &summaries)
+            .write(&mut actual)
+            .unwrap();
+
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="my_bench" tests="3" failures="1" errors="0">
+  <testsuite name="my_bench" tests="3" failures="1" errors="0" skipped="0">
+    <testcase name="short" classname="my_bench::my_group::bench_fib"/>
+    <testcase name="long" classname="my_bench::my_group::bench_fib">
+      <failure message="Performance regressed: Instructions, Estimated Cycles" type="PerformanceRegression">callgrind: Instructions: new 2000, old 1000 (+100.000%) exceeded the limit of +5.00000%
+callgrind: Estimated Cycles: new 3000 exceeded the hard limit of 2500</failure>
+    </testcase>
+    <testcase name="bench_&lt;T&gt;" classname="my_bench::other_group::bench_&lt;T&gt;"/>
+  </testsuite>
+</testsuites>
+"#;
+        assert_eq!(String::from_utf8(actual).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_junit_report_when_sweep_regressed() {
+        let mut summary = summary_fixture("my_bench::my_group::bench_sort", Some("large"), vec![]);
+        summary.sweep = Some(SweepSummary {
+            function_name: "bench_sort".to_owned(),
+            event_kind: EventKind::Ir,
+            points: vec![],
+            new: Some(Complexity::Quadratic),
+            old: Some(Complexity::Linear),
+            regressed: true,
+        });
+
+        let mut actual = vec![];
+        JunitReport::new("my_bench", &[summary])
+            .write(&mut actual)
+            .unwrap();
+
+        let expected = r#"<?xml version="1.0" encoding="UTF-8"?>
+<testsuites name="my_bench" tests="1" failures="1" errors="0">
+  <testsuite name="my_bench" tests="1" failures="1" errors="0" skipped="0">
+    <testcase name="large" classname="my_bench::my_group::bench_sort">
+      <failure message="Performance regressed: Growth class" type="PerformanceRegression">callgrind: Instructions: growth class of bench_sort increased from O(n) to O(n^2)</failure>
+    </testcase>
+  </testsuite>
+</testsuites>
+"#;
+        assert_eq!(String::from_utf8(actual).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_junit_report_when_forbidden_characters() {
+        let summaries = [summary_fixture(
+            "my_bench::group::fib",
+            Some("with_\u{1}_\u{1b}[0m_\u{fffe}"),
+            vec![],
+        )];
+
+        let mut actual = vec![];
+        JunitReport::new("my\u{0}bench", &summaries)
+            .write(&mut actual)
+            .unwrap();
+
+        let expected = "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<testsuites name=\"my\u{fffd}bench\" tests=\"1\" failures=\"0\" errors=\"0\">
+  <testsuite name=\"my\u{fffd}bench\" tests=\"1\" failures=\"0\" errors=\"0\" skipped=\"0\">
+    <testcase name=\"with_\u{fffd}_\u{fffd}[0m_\u{fffd}\" classname=\"my_bench::group::fib\"/>
+  </testsuite>
+</testsuites>
+";
+        assert_eq!(String::from_utf8(actual).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case::nothing_to_replace("some::path\twith\nwhitespace\r", "some::path\twith\nwhitespace\r")]
+    #[case::control_characters("a\u{0}b\u{8}c\u{1f}", "a\u{fffd}b\u{fffd}c\u{fffd}")]
+    #[case::noncharacters("a\u{fffe}b\u{ffff}", "a\u{fffd}b\u{fffd}")]
+    #[case::markup("<a & 'b'>", "<a & 'b'>")]
+    fn test_sanitize(#[case] value: &str, #[case] expected: &str) {
+        assert_eq!(sanitize(value), expected);
+    }
+}
@@ -0,0 +1,219 @@
+//! The Markdown report
+//!
+//! The report is a table with a row for each `EventKind` of each benchmark, which can be posted as
+//! it is for example as comment in a pull request.
+
+use std::borrow::Cow;
+use std::io::Write;
+
+use anyhow::Result;
+
+use crate::runner::format::{NOT_AVAILABLE, NO_CHANGE};
+use crate::runner::summary::{BenchmarkSummary, MetricsDiff};
+use crate::util::{to_string_signed_short, EitherOrBoth};
+
+/// The marker of a regressed `EventKind`
+const REGRESSION_MARKER: &str = ":x:";
+
+/// The Markdown report of all benchmarks of a benchmark target
+#[derive(Debug)]
+pub struct MarkdownReport<'a> {
+    name: &'a str,
+    summaries: &'a [BenchmarkSummary],
+}
+
+impl<'a> MarkdownReport<'a> {
+    /// Create a new `MarkdownReport` with the `name` as heading
+    pub fn new(name: &'a str, summaries: &'a [BenchmarkSummary]) -> Self {
+        Self { name, summaries }
+    }
+
+    /// Write this report as Markdown into the `writer`
+    pub fn write<W: Write>(&self, writer: &mut W) -> Result<()> {
+        let regressed = self
+            .summaries
+            .iter()
+            .filter(|summary| summary.is_regressed())
+            .count();
+
+        writeln!(writer, "# {}", escape(self.name))?;
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "**{regressed} of {} benchmarks regressed**",
+            self.summaries.len()
+        )?;
+        writeln!(writer)?;
+        writeln!(
+            writer,
+            "| Benchmark | Id | Event | New | Old | Diff | Regressed |"
+        )?;
+        writeln!(writer, "| --- | --- | --- | ---: | ---: | ---: | :---: |")?;
+
+        for summary in self.summaries {
+            let Some(run) = summary.default_tool_run() else {
+                continue;
+            };
+
+            let module_path = escape(&summary.module_path);
+            let id = summary.id.as_deref().map_or(Cow::Borrowed(""), escape);
+            for (event_kind, diff) in run.total.summary.all_diffs() {
+                let (new, old, diff) = columns(diff);
+                let marker = if run
+                    .total
+                    .regressions
+                    .iter()
+                    .any(|regression| regression.event_kind == *event_kind)
+                {
+                    REGRESSION_MARKER
+                } else {
+                    ""
+                };
+
+                writeln!(
+                    writer,
+                    "| `{module_path}` | {id} | {event_kind} | {new} | {old} | {diff} | {marker} |"
+                )?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Return the new, old and diff column of the `MetricsDiff`
+fn columns(diff: &MetricsDiff) -> (String, String, String) {
+    match (&diff.metrics, &diff.diffs) {
+        (EitherOrBoth::Left(new), _) => (
+            new.to_string(),
+            NOT_AVAILABLE.to_owned(),
+            NOT_AVAILABLE.to_owned(),
+        ),
+        (EitherOrBoth::Right(old), _) => (
+            NOT_AVAILABLE.to_owned(),
+            old.to_string(),
+            NOT_AVAILABLE.to_owned(),
+        ),
+        (EitherOrBoth::Both(new, old), Some(diffs)) if new != old => (
+            new.to_string(),
+            old.to_string(),
+            format!("{}%", to_string_signed_short(diffs.diff_pct)),
+        ),
+        (EitherOrBoth::Both(new, old), _) => {
+            (new.to_string(), old.to_string(), NO_CHANGE.to_owned())
+        }
+    }
+}
+
+/// Escape the characters which would break the table
+fn escape(value: &str) -> Cow<'_, str> {
+    if value.contains('|') {
+        Cow::Owned(value.replace('|', "\\|"))
+    } else {
+        Cow::Borrowed(value)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::api::EventKind;
+    use crate::runner::common::ModulePath;
+    use crate::runner::metrics::Metrics;
+    use crate::runner::summary::{
+        BenchmarkKind, CallgrindLimit, CallgrindRegression, CallgrindSummary, MetricsSummary,
+    };
+
+    fn summary_fixture(
+        id: Option<&str>,
+        new: &[u64],
+        old: Option<&[u64]>,
+        regressions: Vec<CallgrindRegression>,
+    ) -> BenchmarkSummary {
+        let mut summary = BenchmarkSummary::new(
+            BenchmarkKind::LibraryBenchmark,
+            PathBuf::from("/project"),
+            PathBuf::from("/project"),
+            PathBuf::from("benches/my_bench.rs"),
+            PathBuf::from("/project/target/release/deps/my_bench"),
+            &ModulePath::new("my_bench::my_group::bench_fib"),
+            "bench_fib",
+            id.map(ToOwned::to_owned),
+            None,
+            None,
+        );
+
+        let metrics = |costs: &[u64]| {
+            Metrics::with_metric_kinds(
+                [EventKind::Ir, EventKind::Dr]
+                    .into_iter()
+                    .zip(costs.iter().copied()),
+            )
+        };
+        let metrics = match old {
+            Some(old) => EitherOrBoth::Both(metrics(new), metrics(old)),
+            None => EitherOrBoth::Left(metrics(new)),
+        };
+
+        let mut callgrind_summary = CallgrindSummary::new(vec![], vec![]);
+        callgrind_summary.callgrind_run.total.summary = MetricsSummary::new(metrics);
+        callgrind_summary.callgrind_run.total.regressions = regressions;
+        summary.callgrind_summary = Some(callgrind_summary);
+        summary
+    }
+
+    #[test]
+    fn test_markdown_report() {
+        let summaries = [
+            summary_fixture(Some("short"), &[1000, 300], Some(&[1000, 300]), vec![]),
+            summary_fixture(
+                Some("long"),
+                &[2000, 300],
+                Some(&[1000, 400]),
+                vec![CallgrindRegression {
+                    event_kind: EventKind::Ir,
+                    new: 2000,
+                    old: Some(1000),
+                    diff_pct: Some(100.0f64),
+                    limit: CallgrindLimit::Percentage(5.0f64),
+                }],
+            ),
+            summary_fixture(Some("a|b"), &[10, 20], None, vec![]),
+            summary_fixture(None, &[10, 20], Some(&[10, 20]), vec![]),
+        ];
+
+        let mut actual = vec![];
+        MarkdownReport::new("my_bench", &summaries)
+            .write(&mut actual)
+            .unwrap();
+
+        let expected = r"# my_bench
+
+**1 of 4 benchmarks regressed**
+
+| Benchmark | Id | Event | New | Old | Diff | Regressed |
+| --- | --- | --- | ---: | ---: | ---: | :---: |
+| `my_bench::my_group::bench_fib` | short | Instructions | 1000 | 1000 | No change |  |
+| `my_bench::my_group::bench_fib` | short | Dr | 300 | 300 | No change |  |
+| `my_bench::my_group::bench_fib` | long | Instructions | 2000 | 1000 | +100.000% | :x: |
+| `my_bench::my_group::bench_fib` | long | Dr | 300 | 400 | -25.0000% |  |
+| `my_bench::my_group::bench_fib` | a\|b | Instructions | 10 | N/A | N/A |  |
+| `my_bench::my_group::bench_fib` | a\|b | Dr | 20 | N/A | N/A |  |
+| `my_bench::my_group::bench_fib` |  | Instructions | 10 | 10 | No change |  |
+| `my_bench::my_group::bench_fib` |  | Dr | 20 | 20 | No change |  |
+";
+        assert_eq!(String::from_utf8(actual).unwrap(), expected);
+    }
+
+    #[rstest]
+    #[case::nothing_to_escape("some::path", "some::path")]
+    #[case::pipe("a|b|c", "a\\|b\\|c")]
+    fn test_escape(#[case] value: &str, #[case] expected: &str) {
+        assert_eq!(escape(value), expected);
+    }
+}
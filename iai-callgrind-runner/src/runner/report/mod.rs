@@ -0,0 +1,79 @@
+//! Reports aggregate the [`BenchmarkSummary`]s of all benchmarks of a benchmark target into a
+//! single file
+
+pub mod junit;
+pub mod markdown;
+
+use std::fs::File;
+use std::io::{BufWriter, Write};
+use std::path::PathBuf;
+
+use anyhow::{Context, Result};
+use log::debug;
+
+use self::junit::JunitReport;
+use self::markdown::MarkdownReport;
+use super::common::Config;
+use super::summary::BenchmarkSummary;
+
+/// The format of an aggregated report
+#[derive(Debug, Clone, Copy, PartialEq, Eq, clap::ValueEnum)]
+pub enum ReportFormat {
+    /// A `JUnit` XML report with a testcase for each benchmark
+    Junit,
+    /// A Markdown table with the metrics of all benchmarks for example to post in a pull request
+    Markdown,
+}
+
+impl ReportFormat {
+    /// The file name of the report with this format
+    pub fn file_name(self) -> &'static str {
+        match self {
+            ReportFormat::Junit => "report.junit.xml",
+            ReportFormat::Markdown => "report.md",
+        }
+    }
+
+    /// Return the path to the report file of the benchmark target
+    ///
+    /// The report is stored in the directory of the benchmark target, for example
+    /// `target/iai/my_package/my_bench/report.junit.xml`.
+    pub fn path(self, config: &Config) -> PathBuf {
+        config
+            .meta
+            .target_dir
+            .join(config.module_path.to_string())
+            .join(self.file_name())
+    }
+}
+
+/// Save all reports requested on the command-line with `--save-report`
+///
+/// The `summaries` are the summaries of all benchmarks of the benchmark target in the order of
+/// their execution.
+pub fn save_reports(config: &Config, summaries: &[BenchmarkSummary]) -> Result<()> {
+    for format in &config.meta.args.save_report {
+        let path = format.path(config);
+        debug!("Saving report to '{}'", path.display());
+
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent).with_context(|| {
+                format!("Failed to create report directory '{}'", parent.display())
+            })?;
+        }
+
+        let file = File::create(&path)
+            .with_context(|| format!("Failed to create report file '{}'", path.display()))?;
+        let mut writer = BufWriter::new(file);
+
+        let name = config.module_path.to_string();
+        match format {
+            ReportFormat::Junit => JunitReport::new(&name, summaries).write(&mut writer),
+            ReportFormat::Markdown => MarkdownReport::new(&name, summaries).write(&mut writer),
+        }
+        .and_then(|()| writer.flush().map_err(Into::into))
+        .with_context(|| format!("Failed to write report file '{}'", path.display()))?;
+    }
+
+    Ok(())
+}
@@ -3,14 +3,16 @@ use std::ffi::OsString;
 use std::fmt::{Debug, Display};
 use std::fs::File;
 use std::hash::Hash;
-use std::io::stdout;
+use std::io::{BufReader, BufWriter, Write};
 use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::str::FromStr;
 
 use anyhow::{anyhow, Context, Result};
 use derive_more::AsRef;
 use glob::glob;
 use indexmap::{indexmap, IndexMap, IndexSet};
+use log::debug;
 #[cfg(feature = "schema")]
 use schemars::JsonSchema;
 use serde::{Deserialize, Serialize};
@@ -18,13 +20,30 @@ use serde::{Deserialize, Serialize};
 use super::callgrind::Summaries;
 use super::common::ModulePath;
 use super::format::{Formatter, OutputFormat, OutputFormatKind, VerticalFormatter};
+use super::meta::Metadata;
 use super::metrics::Metrics;
+use super::output::stdout;
+use super::sweep::SweepSummary;
 use super::tool::ValgrindTool;
-use crate::api::{DhatMetricKind, ErrorMetricKind, EventKind};
+use crate::api::{DhatMetricKind, ErrorMetricKind, EventKind, MassifMetricKind};
 use crate::error::Error;
 use crate::runner::metrics::Summarize;
 use crate::util::{factor_diff, make_absolute, percentage_diff, EitherOrBoth};
 
+/// The `AllocationSiteSummary` of a single DHAT allocation site (program point)
+///
+/// The metrics of the new allocation site are compared to the metrics of the old allocation site
+/// with the same frames if present.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct AllocationSiteSummary {
+    /// The frames of the allocation site starting with the innermost frame. The addresses of the
+    /// frames are removed.
+    pub frames: Vec<String>,
+    /// The `MetricsSummary` of the allocation site
+    pub metrics_summary: MetricsSummary<DhatMetricKind>,
+}
+
 /// A `Baseline` depending on the [`BaselineKind`] which points to the corresponding path
 ///
 /// This baseline is used for comparisons with the new output of valgrind tools.
@@ -51,6 +70,21 @@ pub enum BaselineKind {
     Name(BaselineName),
 }
 
+/// The `BaselineStatus` describing if the new run of a benchmark was compared with an old run
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub enum BaselineStatus {
+    /// The new run was compared with the `*.old` output files or a named baseline
+    Compared,
+    /// The named baseline given with `--baseline` is missing for the default tool or one of the
+    /// enabled tools, for example because the benchmark is new
+    New,
+    /// No comparison was requested and there was nothing to compare with. This is the case if
+    /// the benchmark runs the first time without `--baseline` or `--save-baseline` creates a new
+    /// baseline
+    NotRequested,
+}
+
 /// The `BenchmarkKind`, differentiating between library and binary benchmarks
 #[derive(Debug, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
@@ -92,10 +126,33 @@ pub struct BenchmarkSummary {
     pub id: Option<String>,
     /// More details describing this benchmark run
     pub details: Option<String>,
+    /// Whether the new run was compared with an old run or baseline
+    pub baseline_status: BaselineStatus,
     /// The summary of the callgrind run
     pub callgrind_summary: Option<CallgrindSummary>,
+    /// The summary of the cachegrind run if cachegrind is the default tool
+    pub cachegrind_summary: Option<CachegrindSummary>,
     /// The summary of other valgrind tool runs
     pub tool_summaries: Vec<ToolSummary>,
+    /// The parameter sweep of the benchmark function (`LibraryBenchmarkConfig::sweep`). Only
+    /// present in the summary of the last bench of the benchmark function.
+    pub sweep: Option<SweepSummary>,
+}
+
+/// The `CachegrindSummary` contains the cachegrind run and the paths to the segments of the
+/// cachegrind run.
+///
+/// The metrics of cachegrind are event based like the metrics of callgrind, so the segments and
+/// total costs are a `CallgrindRun`, too.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct CachegrindSummary {
+    /// The paths to the `*.log` files
+    pub log_paths: Vec<PathBuf>,
+    /// The paths to the `*.out` files
+    pub out_paths: Vec<PathBuf>,
+    /// The summary of all cachegrind segments is a `CallgrindRun`
+    pub cachegrind_run: CallgrindRun,
 }
 
 /// The `CallgrindRegression` describing a single event based performance regression
@@ -106,18 +163,32 @@ pub struct CallgrindRegression {
     pub event_kind: EventKind,
     /// The value of the new benchmark run
     pub new: u64,
-    /// The value of the old benchmark run
-    pub old: u64,
-    /// The difference between new and old in percent. Serialized as string to preserve infinity
-    /// values and avoid null in json.
-    #[serde(with = "crate::serde::float_64")]
-    #[cfg_attr(feature = "schema", schemars(with = "String"))]
-    pub diff_pct: f64,
-    /// The value of the limit which was exceeded to cause a performance regression. Serialized as
-    /// string to preserve infinity values and avoid null in json.
-    #[serde(with = "crate::serde::float_64")]
-    #[cfg_attr(feature = "schema", schemars(with = "String"))]
-    pub limit: f64,
+    /// The value of the old benchmark run if there was any
+    pub old: Option<u64>,
+    /// The difference between new and old in percent if there was an old value. Serialized as
+    /// string to preserve infinity values.
+    #[serde(with = "crate::serde::option_float_64")]
+    #[cfg_attr(feature = "schema", schemars(with = "Option<String>"))]
+    pub diff_pct: Option<f64>,
+    /// The limit which was exceeded to cause a performance regression
+    pub limit: CallgrindLimit,
+}
+
+/// The `CallgrindLimit` which was exceeded to cause a [`CallgrindRegression`]
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub enum CallgrindLimit {
+    /// The limit of the difference between new and old in percent. Serialized as string to
+    /// preserve infinity values and avoid null in json.
+    Percentage(
+        #[serde(with = "crate::serde::float_64")]
+        #[cfg_attr(feature = "schema", schemars(with = "String"))]
+        f64,
+    ),
+    /// The hard limit (the maximum) of the new value
+    Hard(u64),
+    /// The limit of the absolute difference between new and old
+    Absolute(i64),
 }
 
 /// The `CallgrindRun` contains all `CallgrindRunSegments` and their total costs in a
@@ -159,6 +230,8 @@ pub struct CallgrindTotal {
     pub summary: MetricsSummary,
     /// All detected regressions for the total metrics
     pub regressions: Vec<CallgrindRegression>,
+    /// The noise of the total metrics if the benchmark was run repeatedly with `--repeat`
+    pub noise: Option<NoiseSummary>,
 }
 
 /// The `CallgrindSummary` contains the callgrind run, flamegraph paths and other paths to the
@@ -174,6 +247,91 @@ pub struct CallgrindSummary {
     pub flamegraphs: Vec<FlamegraphSummary>,
     /// The summary of all callgrind segments is a `CallgrindRun`
     pub callgrind_run: CallgrindRun,
+    /// The top functions of all callgrind segments sorted by the difference in the exclusive
+    /// metric of the chosen [`EventKind`] (empty if not requested)
+    pub functions: Vec<FunctionSummary>,
+}
+
+/// The `FunctionSummary` of a single function of the callgrind output files
+///
+/// The inclusive and exclusive metrics of the new function are compared to the metrics of the old
+/// function with the same name, source file and object file if present.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct FunctionSummary {
+    /// The name of the function
+    pub function: String,
+    /// The source file of the function if known. Paths in the project are relative to the project
+    /// root.
+    pub file: Option<PathBuf>,
+    /// The object file of the function if known
+    pub object: Option<PathBuf>,
+    /// The `MetricsSummary` of the inclusive metrics including the metrics of the called functions
+    pub inclusive: MetricsSummary,
+    /// The `MetricsSummary` of the exclusive (self) metrics of the function
+    pub exclusive: MetricsSummary,
+}
+
+/// The `DhatRegression` describing a single dhat metric based performance regression
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct DhatRegression {
+    /// The [`DhatMetricKind`] which is affected by a performance regression
+    pub metric_kind: DhatMetricKind,
+    /// The value of the new benchmark run
+    pub new: u64,
+    /// The value of the old benchmark run
+    pub old: u64,
+    /// The difference between new and old in percent. Serialized as string to preserve infinity
+    /// values and avoid null in json.
+    #[serde(with = "crate::serde::float_64")]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub diff_pct: f64,
+    /// The value of the limit which was exceeded to cause a performance regression. Serialized as
+    /// string to preserve infinity values and avoid null in json.
+    #[serde(with = "crate::serde::float_64")]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub limit: f64,
+}
+
+/// The `ErrorRegression` describing an exceeded error budget of a tool which reports errors
+///
+/// The tools which report errors are memcheck, helgrind and drd. If the `limit` is present, the new
+/// value exceeded this absolute limit. Otherwise, the new value increased compared to the `old`
+/// value of the baseline.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ErrorRegression {
+    /// The [`ErrorMetricKind`] which is affected by the regression
+    pub metric_kind: ErrorMetricKind,
+    /// The value of the new benchmark run
+    pub new: u64,
+    /// The value of the old benchmark run if there was any
+    pub old: Option<u64>,
+    /// The absolute limit which was exceeded or `None` if the value increased compared to `old`
+    pub limit: Option<u64>,
+}
+
+/// The `ErrorContextSummary` describes a distinct error (error context) of memcheck, helgrind or
+/// drd parsed from the xml output of the tool
+///
+/// An error context is identified by its `kind` and the functions of its `stack`, so it can be
+/// compared between different builds of the benchmark. If only the `new` count is present, the
+/// error is new. If only the `old` count is present, the error was fixed.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct ErrorContextSummary {
+    /// The kind of the error like `InvalidRead` or `Race`
+    pub kind: String,
+    /// The description of the error
+    pub what: String,
+    /// The frames of the stack of the error starting with the innermost frame. The addresses of
+    /// the frames are removed.
+    pub stack: Vec<String>,
+    /// The name of the suppression if the tool generated a suppression for this error
+    pub suppression: Option<String>,
+    /// How often the error occurred in the new, old or both benchmark runs
+    pub counts: EitherOrBoth<u64>,
 }
 
 /// The `MetricsDiff` describes the difference between a `new` and `old` metric as percentage and
@@ -192,15 +350,17 @@ pub struct MetricsDiff {
 
 /// The metrics distinguished per tool class
 ///
-/// The tool classes are: dhat, error metrics from memcheck, drd, helgrind and callgrind
+/// The tool classes are: dhat, massif, error metrics from memcheck, drd, helgrind and callgrind
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub enum ToolMetrics {
-    /// If there were no metrics extracted from a tool (currently massif, bbv)
+    /// If there were no metrics extracted from a tool (currently bbv)
     #[default]
     None,
     /// The metrics of a dhat benchmark
     DhatMetrics(Metrics<DhatMetricKind>),
+    /// The metrics of a massif benchmark
+    MassifMetrics(Metrics<MassifMetricKind>),
     /// The metrics of a tool run which reports errors (memcheck, helgrind, drd)
     ErrorMetrics(Metrics<ErrorMetricKind>),
     /// The metrics of a callgrind benchmark
@@ -216,17 +376,29 @@ pub struct MetricsSummary<K: Hash + Eq = EventKind>(IndexMap<K, MetricsDiff>);
 #[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub enum ToolMetricSummary {
-    /// If there are no metrics extracted (currently massif, bbv)
+    /// If there are no metrics extracted (currently bbv)
     #[default]
     None,
     /// The error summary of tools which reports errors (memcheck, helgrind, drd)
     ErrorSummary(MetricsSummary<ErrorMetricKind>),
     /// The dhat summary
     DhatSummary(MetricsSummary<DhatMetricKind>),
+    /// The massif summary
+    MassifSummary(MetricsSummary<MassifMetricKind>),
     /// The callgrind summary
     CallgrindSummary(MetricsSummary<EventKind>),
 }
 
+/// The `ToolRegression` describing a performance regression of a valgrind tool other than callgrind
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub enum ToolRegression {
+    /// A regression of a dhat metric
+    Dhat(DhatRegression),
+    /// An exceeded error budget of memcheck, helgrind or drd
+    Error(ErrorRegression),
+}
+
 /// The differences between two `Metrics` as percentage and factor
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
@@ -291,6 +463,86 @@ pub struct SummaryOutput {
     path: PathBuf,
 }
 
+/// The minimum, median and maximum of a metric over repeated runs of a benchmark
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct NoiseMetric {
+    /// The minimum over all runs
+    pub min: u64,
+    /// The median over all runs (the lower median for an even number of runs)
+    pub median: u64,
+    /// The maximum over all runs
+    pub max: u64,
+    /// The spread (`max - min`) over all runs
+    pub spread: u64,
+}
+
+/// The `NoiseSummary` of the total metrics of a benchmark which was run repeatedly (`--repeat`)
+///
+/// The spread of a metric is the noise band in which changes are not considered regressions.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct NoiseSummary {
+    /// The number of runs
+    pub runs: usize,
+    /// The `NoiseMetric` of each `EventKind`
+    pub metrics: IndexMap<EventKind, NoiseMetric>,
+}
+
+/// The `RunSummary` of all benchmarks of a single `cargo bench` invocation
+///
+/// Contrary to the [`BenchmarkSummary`], which describes a single benchmark, this summary
+/// aggregates the [`BenchmarkSummary`]s of all benchmark targets and packages run by `cargo bench`
+/// together with some metadata of the run.
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct RunSummary {
+    /// The version of this format. Only backwards incompatible changes cause an increase of the
+    /// version
+    pub version: String,
+    /// The identifier of the `cargo bench` invocation if it could be determined. Summaries of
+    /// benchmark targets with the same invocation are aggregated into the same `RunSummary`.
+    pub invocation: Option<String>,
+    /// The version of valgrind as reported by `valgrind --version`
+    pub valgrind_version: Option<String>,
+    /// The architecture of the host
+    pub arch: String,
+    /// The baselines of this run
+    pub baselines: RunBaselines,
+    /// The command-line arguments passed to the benchmark harness for example with `cargo bench
+    /// -- ARGS`
+    pub command_line_args: Vec<String>,
+    /// The totals of all benchmarks
+    pub totals: RunTotals,
+    /// The [`BenchmarkSummary`]s of all benchmarks in the order of their execution
+    pub benchmarks: Vec<BenchmarkSummary>,
+}
+
+/// The baselines as given on the command-line with `--save-baseline`, `--baseline` and
+/// `--load-baseline`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct RunBaselines {
+    /// The name of the baseline which is saved (`--save-baseline`)
+    pub save_baseline: Option<BaselineName>,
+    /// The name of the baseline to compare against (`--baseline`)
+    pub baseline: Option<BaselineName>,
+    /// The name of the baseline which is loaded as new data (`--load-baseline`)
+    pub load_baseline: Option<BaselineName>,
+}
+
+/// The `RunTotals` of all benchmarks of a [`RunSummary`]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct RunTotals {
+    /// The total amount of benchmarks
+    pub benchmarks: u64,
+    /// The amount of benchmarks with performance regressions
+    pub regressed: u64,
+    /// The amount of benchmarks without performance regressions
+    pub passed: u64,
+}
+
 /// Some additional and necessary information about the tool run segment
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize, AsRef)]
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
@@ -350,6 +602,14 @@ pub struct ToolSummary {
     pub out_paths: Vec<PathBuf>,
     /// The metrics and details about the tool run
     pub summaries: ToolRun,
+    /// All detected performance regressions for the total metrics of the tool run
+    pub regressions: Vec<ToolRegression>,
+    /// The top allocation sites of a DHAT run sorted by the difference in the total bytes (empty
+    /// if not requested or for other tools)
+    pub allocation_sites: Vec<AllocationSiteSummary>,
+    /// The distinct errors of a memcheck, helgrind or drd run compared to the baseline (empty for
+    /// other tools)
+    pub errors: Vec<ErrorContextSummary>,
 }
 
 impl FromStr for BaselineName {
@@ -374,6 +634,17 @@ impl Display for BaselineName {
     }
 }
 
+impl BaselineStatus {
+    /// Return the `BaselineStatus` of a run with the `baseline_kind` if the old run `exists`
+    pub fn new(baseline_kind: &BaselineKind, exists: bool) -> Self {
+        match baseline_kind {
+            _ if exists => Self::Compared,
+            BaselineKind::Old => Self::NotRequested,
+            BaselineKind::Name(_) => Self::New,
+        }
+    }
+}
+
 impl BenchmarkSummary {
     /// Create a new `BenchmarkSummary`
     ///
@@ -391,7 +662,7 @@ impl BenchmarkSummary {
         output: Option<SummaryOutput>,
     ) -> Self {
         Self {
-            version: "3".to_owned(),
+            version: "4".to_owned(),
             kind,
             benchmark_file: make_absolute(&project_root, benchmark_file),
             benchmark_exe: make_absolute(&project_root, benchmark_exe),
@@ -399,8 +670,11 @@ impl BenchmarkSummary {
             function_name: function_name.to_owned(),
             id,
             details,
+            baseline_status: BaselineStatus::NotRequested,
             callgrind_summary: None,
+            cachegrind_summary: None,
             tool_summaries: vec![],
+            sweep: None,
             summary_output: output,
             project_root,
             package_dir,
@@ -418,17 +692,15 @@ impl BenchmarkSummary {
         let result = match output_format {
             OutputFormatKind::Default => Ok(()),
             OutputFormatKind::Json => {
-                let output = stdout();
-                let writer = output.lock();
-                let result = serde_json::to_writer(writer, &value);
-                println!();
+                let mut writer = stdout();
+                let result = serde_json::to_writer(&mut writer, &value);
+                writeln!(writer).unwrap();
                 result
             }
             OutputFormatKind::PrettyJson => {
-                let output = stdout();
-                let writer = output.lock();
-                let result = serde_json::to_writer_pretty(writer, &value);
-                println!();
+                let mut writer = stdout();
+                let result = serde_json::to_writer_pretty(&mut writer, &value);
+                writeln!(writer).unwrap();
                 result
             }
         };
@@ -460,41 +732,55 @@ impl BenchmarkSummary {
     ///
     /// If the regressions are configured to be `fail_fast` an error is returned
     pub fn check_regression(&self, is_regressed: &mut bool, fail_fast: bool) -> Result<()> {
-        if let Some(callgrind_summary) = &self.callgrind_summary {
-            let benchmark_is_regressed = callgrind_summary.is_regressed();
-            if benchmark_is_regressed && fail_fast {
-                return Err(Error::RegressionError(true).into());
-            }
+        let benchmark_is_regressed = self.is_regressed();
 
-            *is_regressed |= benchmark_is_regressed;
+        if benchmark_is_regressed && fail_fast {
+            return Err(Error::RegressionError(true).into());
         }
 
+        *is_regressed |= benchmark_is_regressed;
+
         Ok(())
     }
 
+    /// Return true if any tool of this `BenchmarkSummary` has recorded performance regressions
+    pub fn is_regressed(&self) -> bool {
+        self.callgrind_summary
+            .as_ref()
+            .map_or(false, CallgrindSummary::is_regressed)
+            || self
+                .cachegrind_summary
+                .as_ref()
+                .map_or(false, CachegrindSummary::is_regressed)
+            || self.tool_summaries.iter().any(ToolSummary::is_regressed)
+            || self.sweep.as_ref().map_or(false, |sweep| sweep.regressed)
+    }
+
+    /// Return the `CallgrindRun` of the default tool (callgrind or cachegrind) if present
+    pub fn default_tool_run(&self) -> Option<&CallgrindRun> {
+        self.callgrind_summary
+            .as_ref()
+            .map(|summary| &summary.callgrind_run)
+            .or_else(|| {
+                self.cachegrind_summary
+                    .as_ref()
+                    .map(|summary| &summary.cachegrind_run)
+            })
+    }
+
     pub fn compare_and_print(
         &self,
         id: &str,
         other: &Self,
         output_format: &OutputFormat,
     ) -> Result<()> {
-        if let (Some(callgrind_summary), Some(other_callgrind_summary)) =
-            (&self.callgrind_summary, &other.callgrind_summary)
-        {
+        if let (Some(run), Some(other_run)) = (self.default_tool_run(), other.default_tool_run()) {
             if let (
                 EitherOrBoth::Left(new) | EitherOrBoth::Both(new, _),
                 EitherOrBoth::Left(other_new) | EitherOrBoth::Both(other_new, _),
             ) = (
-                callgrind_summary
-                    .callgrind_run
-                    .total
-                    .summary
-                    .extract_costs(),
-                other_callgrind_summary
-                    .callgrind_run
-                    .total
-                    .summary
-                    .extract_costs(),
+                run.total.summary.extract_costs(),
+                other_run.total.summary.extract_costs(),
             ) {
                 let new_summary = MetricsSummary::new(EitherOrBoth::Both(new, other_new));
                 VerticalFormatter::new(*output_format).print_comparison(
@@ -510,23 +796,39 @@ impl BenchmarkSummary {
     }
 }
 
-impl CallgrindSummary {
-    /// Create a new `CallgrindSummary`
-    pub fn new(log_paths: Vec<PathBuf>, out_paths: Vec<PathBuf>) -> CallgrindSummary {
+impl CachegrindSummary {
+    /// Create a new `CachegrindSummary`
+    pub fn new(log_paths: Vec<PathBuf>, out_paths: Vec<PathBuf>) -> CachegrindSummary {
         Self {
             log_paths,
             out_paths,
-            flamegraphs: Vec::default(),
-            callgrind_run: CallgrindRun::default(),
+            cachegrind_run: CallgrindRun::default(),
         }
     }
 
-    /// Return true if there are any recorded regressions in this `CallgrindSummary`
+    /// Return true if there are any recorded regressions in this `CachegrindSummary`
     pub fn is_regressed(&self) -> bool {
-        self.callgrind_run
-            .segments
-            .iter()
-            .any(|r| !r.regressions.is_empty())
+        self.cachegrind_run.is_regressed()
+    }
+
+    pub fn add_summaries(
+        &mut self,
+        bench_bin: &Path,
+        bench_args: &[OsString],
+        baselines: &(Option<String>, Option<String>),
+        summaries: Summaries,
+        regressions: Vec<CallgrindRegression>,
+    ) {
+        self.cachegrind_run
+            .add_summaries(bench_bin, bench_args, baselines, summaries, regressions);
+    }
+}
+
+impl CallgrindRun {
+    /// Return true if there are any recorded regressions in this `CallgrindRun`
+    pub fn is_regressed(&self) -> bool {
+        !self.total.regressions.is_empty()
+            || self.segments.iter().any(|r| !r.regressions.is_empty())
     }
 
     pub fn add_summaries(
@@ -562,7 +864,7 @@ impl CallgrindSummary {
                 }),
             };
 
-            self.callgrind_run.segments.push(CallgrindRunSegment {
+            self.segments.push(CallgrindRunSegment {
                 command: command.clone(),
                 baseline: old_baseline,
                 events: summary.metrics_summary,
@@ -570,8 +872,38 @@ impl CallgrindSummary {
             });
         }
 
-        self.callgrind_run.total.summary = summaries.total.clone();
-        self.callgrind_run.total.regressions = regressions;
+        self.total.summary = summaries.total.clone();
+        self.total.regressions = regressions;
+    }
+}
+
+impl CallgrindSummary {
+    /// Create a new `CallgrindSummary`
+    pub fn new(log_paths: Vec<PathBuf>, out_paths: Vec<PathBuf>) -> CallgrindSummary {
+        Self {
+            log_paths,
+            out_paths,
+            flamegraphs: Vec::default(),
+            callgrind_run: CallgrindRun::default(),
+            functions: Vec::default(),
+        }
+    }
+
+    /// Return true if there are any recorded regressions in this `CallgrindSummary`
+    pub fn is_regressed(&self) -> bool {
+        self.callgrind_run.is_regressed()
+    }
+
+    pub fn add_summaries(
+        &mut self,
+        bench_bin: &Path,
+        bench_args: &[OsString],
+        baselines: &(Option<String>, Option<String>),
+        summaries: Summaries,
+        regressions: Vec<CallgrindRegression>,
+    ) {
+        self.callgrind_run
+            .add_summaries(bench_bin, bench_args, baselines, summaries, regressions);
     }
 }
 
@@ -787,6 +1119,182 @@ impl FlamegraphSummary {
     }
 }
 
+impl NoiseMetric {
+    /// Create a new `NoiseMetric` from the `values` of all runs
+    ///
+    /// Returns `None` if there are no `values`.
+    pub fn new(mut values: Vec<u64>) -> Option<Self> {
+        values.sort_unstable();
+        let (min, max) = (*values.first()?, *values.last()?);
+        Some(Self {
+            min,
+            median: values[(values.len() - 1) / 2],
+            max,
+            spread: max - min,
+        })
+    }
+
+    /// Return the spread in percent of the median
+    pub fn spread_pct(&self) -> f64 {
+        if self.median == 0 {
+            0f64
+        } else {
+            #[allow(clippy::cast_precision_loss)]
+            let pct = self.spread as f64 / self.median as f64 * 100f64;
+            pct
+        }
+    }
+}
+
+impl NoiseSummary {
+    /// Create a new `NoiseSummary` from the total `Metrics` of all runs
+    pub fn new(totals: &[Metrics<EventKind>]) -> Self {
+        let mut metrics = IndexMap::new();
+        if let Some(first) = totals.first() {
+            for event_kind in first.0.keys() {
+                let values = totals
+                    .iter()
+                    .filter_map(|total| total.metric_by_kind(event_kind))
+                    .collect();
+                if let Some(metric) = NoiseMetric::new(values) {
+                    metrics.insert(*event_kind, metric);
+                }
+            }
+        }
+
+        Self {
+            runs: totals.len(),
+            metrics,
+        }
+    }
+
+    /// Return true if the change of the `regression` is within the noise band
+    ///
+    /// Only changes compared to the old metrics can be noise, so exceeded hard limits never are.
+    pub fn is_noise(&self, regression: &CallgrindRegression) -> bool {
+        match (&regression.limit, regression.old) {
+            (CallgrindLimit::Percentage(_) | CallgrindLimit::Absolute(_), Some(old)) => self
+                .metrics
+                .get(&regression.event_kind)
+                .map_or(false, |metric| {
+                    regression.new.abs_diff(old) <= metric.spread
+                }),
+            _ => false,
+        }
+    }
+}
+
+impl RunSummary {
+    /// The file name of the `RunSummary` in the `HOME` directory (per default `target/iai`)
+    pub const FILE_NAME: &'static str = "run-summary.json";
+
+    /// Create a new `RunSummary` with the [`Metadata`] of the benchmark target
+    pub fn new(
+        meta: &Metadata,
+        invocation: Option<String>,
+        valgrind_version: Option<String>,
+        benchmarks: Vec<BenchmarkSummary>,
+    ) -> Self {
+        let mut summary = Self {
+            version: "1".to_owned(),
+            invocation,
+            valgrind_version,
+            arch: meta.arch.clone(),
+            baselines: RunBaselines {
+                save_baseline: meta.args.save_baseline.clone(),
+                baseline: meta.args.baseline.clone(),
+                load_baseline: meta.args.load_baseline.clone(),
+            },
+            command_line_args: meta.command_line_args.clone(),
+            totals: RunTotals::default(),
+            benchmarks,
+        };
+        summary.update_totals();
+        summary
+    }
+
+    /// Save the `benchmarks` of a benchmark target in the `RunSummary` file of the `HOME`
+    /// directory
+    ///
+    /// If the existing `RunSummary` file stems from the same `cargo bench` invocation, the
+    /// `benchmarks` are appended to the existing benchmarks. Otherwise, the file is overwritten.
+    pub fn save(
+        meta: &Metadata,
+        format: SummaryFormat,
+        benchmarks: Vec<BenchmarkSummary>,
+    ) -> Result<()> {
+        let path = meta.home_dir.join(Self::FILE_NAME);
+        let new = Self::new(
+            meta,
+            invocation_id(),
+            valgrind_version(&meta.valgrind.bin),
+            benchmarks,
+        );
+
+        let summary = match Self::load(&path) {
+            Some(old) => old.merge(new),
+            None => new,
+        };
+
+        debug!("Saving run summary to '{}'", path.display());
+        std::fs::create_dir_all(&meta.home_dir)
+            .with_context(|| format!("Failed to create directory '{}'", meta.home_dir.display()))?;
+        let file = File::create(&path)
+            .with_context(|| format!("Failed to create run summary file '{}'", path.display()))?;
+        let writer = BufWriter::new(file);
+
+        let result = if matches!(format, SummaryFormat::PrettyJson) {
+            serde_json::to_writer_pretty(writer, &summary)
+        } else {
+            serde_json::to_writer(writer, &summary)
+        };
+        result.with_context(|| format!("Failed to write run summary to file: {}", path.display()))
+    }
+
+    /// Load an existing `RunSummary` from the file at `path`
+    ///
+    /// Return `None` if there is no such file or if it can't be deserialized, for example, because
+    /// it was written by an incompatible version of iai-callgrind.
+    fn load(path: &Path) -> Option<Self> {
+        let file = File::open(path).ok()?;
+        serde_json::from_reader(BufReader::new(file))
+            .map_err(|error| debug!("Ignoring run summary '{}': {error}", path.display()))
+            .ok()
+    }
+
+    /// Merge the `new` `RunSummary` into this `RunSummary` if both stem from the same invocation
+    ///
+    /// If the invocations differ or are unknown, the `new` `RunSummary` replaces this one.
+    pub fn merge(mut self, new: Self) -> Self {
+        if self.version != new.version
+            || self.invocation.is_none()
+            || self.invocation != new.invocation
+        {
+            return new;
+        }
+
+        self.benchmarks.extend(new.benchmarks);
+        self.update_totals();
+        self
+    }
+
+    /// Recalculate the [`RunTotals`] from the benchmarks of this `RunSummary`
+    pub fn update_totals(&mut self) {
+        let benchmarks = self.benchmarks.len() as u64;
+        let regressed = self
+            .benchmarks
+            .iter()
+            .filter(|summary| summary.is_regressed())
+            .count() as u64;
+
+        self.totals = RunTotals {
+            benchmarks,
+            regressed,
+            passed: benchmarks - regressed,
+        };
+    }
+}
+
 impl SummaryOutput {
     /// Create a new `SummaryOutput` with `dir` as base dir and an extension fitting the
     /// [`SummaryFormat`]
@@ -829,6 +1337,9 @@ impl ToolMetricSummary {
             (ToolMetricSummary::DhatSummary(this), ToolMetricSummary::DhatSummary(other)) => {
                 this.add(other);
             }
+            (ToolMetricSummary::MassifSummary(this), ToolMetricSummary::MassifSummary(other)) => {
+                this.add(other);
+            }
             (
                 ToolMetricSummary::CallgrindSummary(this),
                 ToolMetricSummary::CallgrindSummary(other),
@@ -845,6 +1356,9 @@ impl ToolMetricSummary {
             ToolMetrics::DhatMetrics(metrics) => ToolMetricSummary::DhatSummary(
                 MetricsSummary::new(EitherOrBoth::Left(metrics.clone())),
             ),
+            ToolMetrics::MassifMetrics(metrics) => ToolMetricSummary::MassifSummary(
+                MetricsSummary::new(EitherOrBoth::Left(metrics.clone())),
+            ),
             ToolMetrics::ErrorMetrics(metrics) => ToolMetricSummary::ErrorSummary(
                 MetricsSummary::new(EitherOrBoth::Left(metrics.clone())),
             ),
@@ -859,6 +1373,9 @@ impl ToolMetricSummary {
             ToolMetrics::DhatMetrics(metrics) => ToolMetricSummary::DhatSummary(
                 MetricsSummary::new(EitherOrBoth::Right(metrics.clone())),
             ),
+            ToolMetrics::MassifMetrics(metrics) => ToolMetricSummary::MassifSummary(
+                MetricsSummary::new(EitherOrBoth::Right(metrics.clone())),
+            ),
             ToolMetrics::ErrorMetrics(metrics) => ToolMetricSummary::ErrorSummary(
                 MetricsSummary::new(EitherOrBoth::Right(metrics.clone())),
             ),
@@ -881,6 +1398,11 @@ impl ToolMetricSummary {
                     EitherOrBoth::Both(new_metrics.clone(), old_metrics.clone()),
                 )))
             }
+            (ToolMetrics::MassifMetrics(new_metrics), ToolMetrics::MassifMetrics(old_metrics)) => {
+                Ok(ToolMetricSummary::MassifSummary(MetricsSummary::new(
+                    EitherOrBoth::Both(new_metrics.clone(), old_metrics.clone()),
+                )))
+            }
             (ToolMetrics::ErrorMetrics(new_metrics), ToolMetrics::ErrorMetrics(old_metrics)) => {
                 Ok(ToolMetricSummary::ErrorSummary(MetricsSummary::new(
                     EitherOrBoth::Both(new_metrics.clone(), old_metrics.clone()),
@@ -920,6 +1442,7 @@ impl ToolRunSegment {
         match &self.metrics_summary {
             ToolMetricSummary::None
             | ToolMetricSummary::DhatSummary(_)
+            | ToolMetricSummary::MassifSummary(_)
             | ToolMetricSummary::CallgrindSummary(_) => false,
             ToolMetricSummary::ErrorSummary(metrics) => metrics
                 .diff_by_kind(&ErrorMetricKind::Errors)
@@ -931,6 +1454,71 @@ impl ToolRunSegment {
     }
 }
 
+impl ToolSummary {
+    /// Return true if there are any recorded regressions in this `ToolSummary`
+    pub fn is_regressed(&self) -> bool {
+        !self.regressions.is_empty()
+    }
+}
+
+/// The `ProcStat` of a process as far as it is needed to identify the process
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ProcStat {
+    comm: String,
+    ppid: u32,
+    start_time: u64,
+}
+
+impl ProcStat {
+    /// Parse the content of a `/proc/<pid>/stat` file
+    ///
+    /// The command name is enclosed in parentheses and may itself contain spaces and parentheses,
+    /// so the remaining fields are parsed after the last closing parenthesis.
+    fn parse(stat: &str) -> Option<Self> {
+        let (head, tail) = stat.rsplit_once(')')?;
+        let (_, comm) = head.split_once('(')?;
+        let mut fields = tail.split_whitespace();
+
+        // The fields after the command name start with the state (3rd field). The parent pid is
+        // the 4th field and the start time the 22nd field
+        let ppid = fields.nth(1)?.parse().ok()?;
+        let start_time = fields.nth(17)?.parse().ok()?;
+
+        Some(Self {
+            comm: comm.to_owned(),
+            ppid,
+            start_time,
+        })
+    }
+
+    fn read(pid: u32) -> Option<Self> {
+        std::fs::read_to_string(format!("/proc/{pid}/stat"))
+            .ok()
+            .as_deref()
+            .and_then(Self::parse)
+    }
+}
+
+/// Return an identifier of the `cargo bench` invocation which runs this benchmark target
+///
+/// The runner is executed by the benchmark binary, which is executed by `cargo`. The pid of
+/// `cargo` together with its start time identifies the invocation. This only works on systems
+/// with a `/proc` filesystem, otherwise `None` is returned.
+fn invocation_id() -> Option<String> {
+    let parent = ProcStat::read(std::os::unix::process::parent_id())?;
+    let cargo = ProcStat::read(parent.ppid)?;
+    (cargo.comm == "cargo").then(|| format!("{}-{}", parent.ppid, cargo.start_time))
+}
+
+/// Return the version of valgrind for example `valgrind-3.23.0`
+pub fn valgrind_version(valgrind: &Path) -> Option<String> {
+    let output = Command::new(valgrind).arg("--version").output().ok()?;
+    output
+        .status
+        .success()
+        .then(|| String::from_utf8_lossy(&output.stdout).trim().to_owned())
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -938,6 +1526,7 @@ mod tests {
     use EventKind::*;
 
     use super::*;
+    use crate::runner::sweep::Complexity;
 
     fn expected_metrics_diff<D>(metrics: EitherOrBoth<u64>, diffs: D) -> MetricsDiff
     where
@@ -1009,6 +1598,27 @@ mod tests {
         MetricsSummary(map)
     }
 
+    #[rstest]
+    #[case::old_compared(BaselineKind::Old, true, BaselineStatus::Compared)]
+    #[case::old_missing(BaselineKind::Old, false, BaselineStatus::NotRequested)]
+    #[case::name_compared(
+        BaselineKind::Name(BaselineName("main".to_owned())),
+        true,
+        BaselineStatus::Compared
+    )]
+    #[case::name_missing(
+        BaselineKind::Name(BaselineName("main".to_owned())),
+        false,
+        BaselineStatus::New
+    )]
+    fn test_baseline_status_new(
+        #[case] baseline_kind: BaselineKind,
+        #[case] exists: bool,
+        #[case] expected: BaselineStatus,
+    ) {
+        assert_eq!(BaselineStatus::new(&baseline_kind, exists), expected);
+    }
+
     #[rstest]
     #[case::new_zero(EitherOrBoth::Left(0), None)]
     #[case::new_one(EitherOrBoth::Left(1), None)]
@@ -1240,4 +1850,203 @@ mod tests {
 
         assert_eq!(actual, expected_metrics_summary);
     }
+
+    #[rstest]
+    #[case::none(false, false, false)]
+    #[case::segment(true, false, true)]
+    #[case::total(false, true, true)]
+    fn test_callgrind_summary_is_regressed(
+        #[case] segment_regressed: bool,
+        #[case] total_regressed: bool,
+        #[case] expected: bool,
+    ) {
+        let regression = CallgrindRegression {
+            event_kind: Ir,
+            new: 2,
+            old: Some(1),
+            diff_pct: Some(100f64),
+            limit: CallgrindLimit::Percentage(10f64),
+        };
+        let regressions = |is_regressed: bool| {
+            if is_regressed {
+                vec![regression.clone()]
+            } else {
+                vec![]
+            }
+        };
+
+        let mut summary = CallgrindSummary::new(vec![], vec![]);
+        summary.callgrind_run.segments.push(CallgrindRunSegment {
+            command: "some".to_owned(),
+            baseline: None,
+            events: MetricsSummary::default(),
+            regressions: regressions(segment_regressed),
+        });
+        summary.callgrind_run.total.regressions = regressions(total_regressed);
+
+        assert_eq!(summary.is_regressed(), expected);
+    }
+
+    fn run_summary_fixture(invocation: Option<&str>, regressed: &[bool]) -> RunSummary {
+        let benchmarks = regressed
+            .iter()
+            .map(|is_regressed| {
+                let mut summary = BenchmarkSummary::new(
+                    BenchmarkKind::LibraryBenchmark,
+                    PathBuf::from("/project"),
+                    PathBuf::from("/project"),
+                    PathBuf::from("benches/my_bench.rs"),
+                    PathBuf::from("/project/target/release/deps/my_bench"),
+                    &ModulePath::new("my_bench::my_group::bench"),
+                    "bench",
+                    None,
+                    None,
+                    None,
+                );
+                let mut callgrind_summary = CallgrindSummary::new(vec![], vec![]);
+                if *is_regressed {
+                    callgrind_summary.callgrind_run.total.regressions = vec![CallgrindRegression {
+                        event_kind: Ir,
+                        new: 2,
+                        old: Some(1),
+                        diff_pct: Some(100.0f64),
+                        limit: CallgrindLimit::Percentage(5.0f64),
+                    }];
+                }
+                summary.callgrind_summary = Some(callgrind_summary);
+                summary
+            })
+            .collect();
+
+        let mut summary = RunSummary {
+            version: "1".to_owned(),
+            invocation: invocation.map(ToOwned::to_owned),
+            valgrind_version: None,
+            arch: "x86_64".to_owned(),
+            baselines: RunBaselines::default(),
+            command_line_args: vec![],
+            totals: RunTotals::default(),
+            benchmarks,
+        };
+        summary.update_totals();
+        summary
+    }
+
+    #[rstest]
+    #[case::not_regressed(false, false)]
+    #[case::regressed(true, false)]
+    #[case::regressed_fail_fast(true, true)]
+    fn test_benchmark_summary_check_regression_when_sweep(
+        #[case] regressed: bool,
+        #[case] fail_fast: bool,
+    ) {
+        let mut summary = run_summary_fixture(None, &[false]).benchmarks.remove(0);
+        summary.sweep = Some(SweepSummary {
+            function_name: "bench".to_owned(),
+            event_kind: Ir,
+            points: vec![],
+            new: Some(Complexity::Quadratic),
+            old: Some(Complexity::Linear),
+            regressed,
+        });
+
+        let mut is_regressed = false;
+        let result = summary.check_regression(&mut is_regressed, fail_fast);
+
+        assert_eq!(summary.is_regressed(), regressed);
+        assert_eq!(result.is_err(), regressed && fail_fast);
+        assert_eq!(is_regressed, regressed && !fail_fast);
+    }
+
+    #[rstest]
+    #[case::same_invocation(Some("1-2"), Some("1-2"), 3, 1)]
+    #[case::different_invocation(Some("1-2"), Some("1-3"), 1, 0)]
+    #[case::unknown_old_invocation(None, Some("1-2"), 1, 0)]
+    #[case::unknown_invocations(None, None, 1, 0)]
+    fn test_run_summary_merge(
+        #[case] old: Option<&str>,
+        #[case] new: Option<&str>,
+        #[case] expected_benchmarks: u64,
+        #[case] expected_regressed: u64,
+    ) {
+        let old = run_summary_fixture(old, &[true, false]);
+        let new = run_summary_fixture(new, &[false]);
+
+        let actual = old.merge(new);
+
+        assert_eq!(actual.benchmarks.len() as u64, expected_benchmarks);
+        assert_eq!(
+            actual.totals,
+            RunTotals {
+                benchmarks: expected_benchmarks,
+                regressed: expected_regressed,
+                passed: expected_benchmarks - expected_regressed
+            }
+        );
+    }
+
+    #[rstest]
+    #[case::simple(
+        "1234 (cargo) S 1000 1234 1000 34816 1234 4194560 1 2 3 4 5 6 7 8 20 0 1 0 98765 1 2",
+        Some(("cargo", 1000, 98765))
+    )]
+    #[case::comm_with_parentheses(
+        "42 (my (bench) 1) R 7 42 7 0 -1 4194304 1 2 3 4 5 6 7 8 20 0 1 0 123 1 2",
+        Some(("my (bench) 1", 7, 123))
+    )]
+    #[case::too_short("42 (cargo) S 7 42", None)]
+    #[case::no_comm("42 S 7 42 7 0 -1 4194304 1 2 3 4 5 6 7 8 20 0 1 0 123 1 2", None)]
+    fn test_proc_stat_parse(#[case] stat: &str, #[case] expected: Option<(&str, u32, u64)>) {
+        let expected = expected.map(|(comm, ppid, start_time)| ProcStat {
+            comm: comm.to_owned(),
+            ppid,
+            start_time,
+        });
+        assert_eq!(ProcStat::parse(stat), expected);
+    }
+
+    fn noise_fixture() -> NoiseSummary {
+        NoiseSummary::new(&[
+            Metrics::with_metric_kinds([(Ir, 1010), (Dr, 100)]),
+            Metrics::with_metric_kinds([(Ir, 1000), (Dr, 100)]),
+            Metrics::with_metric_kinds([(Ir, 1004), (Dr, 100)]),
+        ])
+    }
+
+    #[rstest]
+    #[case::empty(vec![], None)]
+    #[case::single(vec![5], Some((5, 5, 5)))]
+    #[case::odd(vec![7, 3, 5], Some((3, 5, 7)))]
+    #[case::even(vec![8, 2, 6, 4], Some((2, 4, 8)))]
+    fn test_noise_metric_new(#[case] values: Vec<u64>, #[case] expected: Option<(u64, u64, u64)>) {
+        let expected = expected.map(|(min, median, max)| NoiseMetric {
+            min,
+            median,
+            max,
+            spread: max - min,
+        });
+        assert_eq!(NoiseMetric::new(values), expected);
+    }
+
+    #[rstest]
+    #[case::within_noise(CallgrindLimit::Percentage(0.1), 1010, Some(1000), true)]
+    #[case::above_noise(CallgrindLimit::Percentage(0.1), 1011, Some(1000), false)]
+    #[case::absolute_within_noise(CallgrindLimit::Absolute(5), 990, Some(1000), true)]
+    #[case::hard_limit(CallgrindLimit::Hard(1000), 1004, Some(1000), false)]
+    #[case::no_old(CallgrindLimit::Percentage(0.1), 1004, None, false)]
+    fn test_noise_summary_is_noise(
+        #[case] limit: CallgrindLimit,
+        #[case] new: u64,
+        #[case] old: Option<u64>,
+        #[case] expected: bool,
+    ) {
+        let regression = CallgrindRegression {
+            event_kind: Ir,
+            new,
+            old,
+            diff_pct: None,
+            limit,
+        };
+        assert_eq!(noise_fixture().is_noise(&regression), expected);
+    }
 }
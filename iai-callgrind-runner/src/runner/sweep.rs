@@ -0,0 +1,449 @@
+//! Estimate the complexity of benchmarks which are a parameter sweep
+//!
+//! The benches of a benchmark function with `LibraryBenchmarkConfig::sweep` enabled measure the
+//! same function with a different parameter, for example the size of the input. The parameter of
+//! a bench is the single number literal in its arguments. The first metric of the default tool
+//! (usually `Ir`) is fitted to the growth classes `O(1)`, `O(n)`, `O(n log n)` and `O(n^2)` for
+//! the new and, if present, the old metrics, so a change of the growth class compared to the
+//! baseline can be flagged. An increase of the growth class is a performance regression if
+//! regressions are checked.
+
+use std::fmt::Display;
+use std::io::Write;
+
+use colored::Colorize;
+use log::warn;
+#[cfg(feature = "schema")]
+use schemars::JsonSchema;
+use serde::{Deserialize, Serialize};
+
+use super::format::{OutputFormat, VerticalFormatter};
+use super::output::stderr;
+use super::summary::BenchmarkSummary;
+use crate::api::EventKind;
+use crate::util::EitherOrBoth;
+
+/// The minimum amount of different parameters needed to estimate the [`Complexity`]
+pub const MIN_PARAMETERS: usize = 3;
+
+/// If the fitted growth over the whole parameter range is less than this fraction of the mean of
+/// the metric, the metric is considered to be constant
+const CONSTANT_TOLERANCE: f64 = 0.05;
+
+/// The growth class of a metric depending on the parameter `n`
+///
+/// The growth classes are ordered from the slowest to the fastest growth.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub enum Complexity {
+    /// `O(1)`
+    Constant,
+    /// `O(n)`
+    Linear,
+    /// `O(n log n)`
+    Linearithmic,
+    /// `O(n^2)`
+    Quadratic,
+}
+
+/// The metric of a single bench of a sweep
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct SweepPoint {
+    /// The parameter parsed from the arguments of the bench. Serialized as string to preserve
+    /// infinity values and avoid null in json.
+    #[serde(with = "crate::serde::float_64")]
+    #[cfg_attr(feature = "schema", schemars(with = "String"))]
+    pub parameter: f64,
+    /// The new, old or both values of the metric
+    pub metrics: EitherOrBoth<u64>,
+}
+
+/// The metrics of all benches of a sweep and the estimated [`Complexity`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[cfg_attr(feature = "schema", derive(JsonSchema))]
+pub struct SweepSummary {
+    /// The name of the benchmark function
+    pub function_name: String,
+    /// The metric which is used to estimate the `Complexity`
+    pub event_kind: EventKind,
+    /// The `SweepPoint`s sorted by parameter
+    pub points: Vec<SweepPoint>,
+    /// The `Complexity` of the new metrics
+    pub new: Option<Complexity>,
+    /// The `Complexity` of the old metrics if all benches have old metrics
+    pub old: Option<Complexity>,
+    /// True if regressions are checked and the new `Complexity` is greater than the old one
+    pub regressed: bool,
+}
+
+impl Complexity {
+    /// The growth classes which are fitted to the metrics, from the slowest to the fastest growth
+    const GROWING: [Self; 3] = [Self::Linear, Self::Linearithmic, Self::Quadratic];
+
+    /// Return the value of the growth function at `n`
+    fn apply(self, n: f64) -> f64 {
+        match self {
+            Self::Constant => 1.0,
+            Self::Linear => n,
+            Self::Linearithmic => n * n.ln(),
+            Self::Quadratic => n * n,
+        }
+    }
+}
+
+impl Display for Complexity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(match self {
+            Self::Constant => "O(1)",
+            Self::Linear => "O(n)",
+            Self::Linearithmic => "O(n log n)",
+            Self::Quadratic => "O(n^2)",
+        })
+    }
+}
+
+impl SweepSummary {
+    /// Create a new `SweepSummary` from the arguments and [`BenchmarkSummary`] of each bench
+    ///
+    /// Benches without a parameter in their arguments or without metrics of the default tool are
+    /// not part of the sweep. Returns `None` if no bench is left. An increase of the `Complexity`
+    /// is only a regression if `check_regression` is true.
+    #[allow(clippy::cast_precision_loss)]
+    pub fn new(
+        function_name: &str,
+        benches: &[(Option<&str>, &BenchmarkSummary)],
+        check_regression: bool,
+    ) -> Option<Self> {
+        let mut event_kind = None;
+        let mut points = vec![];
+        for (args, summary) in benches {
+            let Some(parameter) = args.and_then(parse_parameter) else {
+                warn!(
+                    "Sweep of '{function_name}': Ignoring bench '{}' without a single number in \
+                     its arguments",
+                    summary.id.as_deref().unwrap_or(function_name)
+                );
+                continue;
+            };
+            let Some(run) = summary.default_tool_run() else {
+                continue;
+            };
+            let event_kind = *event_kind
+                .get_or_insert_with(|| run.total.summary.all_diffs().next().map(|(k, _)| *k));
+            if let Some(diff) = event_kind.and_then(|e| run.total.summary.diff_by_kind(&e)) {
+                points.push(SweepPoint {
+                    parameter,
+                    metrics: diff.metrics.clone(),
+                });
+            }
+        }
+
+        let event_kind = event_kind.flatten()?;
+        if points.is_empty() {
+            return None;
+        }
+        points.sort_by(|a, b| a.parameter.total_cmp(&b.parameter));
+
+        let new = points
+            .iter()
+            .filter_map(|p| p.metrics.left().map(|v| (p.parameter, *v as f64)))
+            .collect::<Vec<_>>();
+        let old = points
+            .iter()
+            .map(|p| p.metrics.right().map(|v| (p.parameter, *v as f64)))
+            .collect::<Option<Vec<_>>>();
+
+        let new = fit(&new);
+        if new.is_none() {
+            warn!(
+                "Sweep of '{function_name}': At least {MIN_PARAMETERS} different parameters are \
+                 needed to estimate the complexity"
+            );
+        }
+
+        let old = old.as_deref().and_then(fit);
+        Some(Self {
+            function_name: function_name.to_owned(),
+            event_kind,
+            points,
+            new,
+            old,
+            regressed: check_regression
+                && matches!((new, old), (Some(new), Some(old)) if new > old),
+        })
+    }
+
+    /// Return true if the `Complexity` of the new metrics differs from the old `Complexity`
+    pub fn is_changed(&self) -> bool {
+        matches!((self.new, self.old), (Some(new), Some(old)) if new != old)
+    }
+}
+
+/// Parse the parameter of a bench from its `args`
+///
+/// The `args` need to contain exactly one integer or float literal, for example `10`,
+/// `1_000usize` or `100.to_string()`. A type suffix is ignored.
+pub fn parse_parameter(args: &str) -> Option<f64> {
+    let chars = args.chars().collect::<Vec<_>>();
+    let is_ident = |c: char| c.is_alphanumeric() || c == '_';
+
+    let mut numbers = vec![];
+    let mut index = 0;
+    while index < chars.len() {
+        if !chars[index].is_ascii_digit() || (index > 0 && is_ident(chars[index - 1])) {
+            index += 1;
+            continue;
+        }
+
+        let start = index;
+        while index < chars.len()
+            && (chars[index].is_ascii_digit() || chars[index] == '_' || chars[index] == '.')
+        {
+            index += 1;
+        }
+        let literal = chars[start..index]
+            .iter()
+            .filter(|c| **c != '_')
+            .collect::<String>();
+        // Skip a type suffix like `u64` or a method call like `.to_string()`
+        while index < chars.len() && is_ident(chars[index]) {
+            index += 1;
+        }
+
+        numbers.push(literal.trim_end_matches('.').parse::<f64>().ok()?);
+    }
+
+    match numbers.as_slice() {
+        [number] => Some(*number),
+        _ => None,
+    }
+}
+
+/// Estimate the [`Complexity`] of the `points` (parameter, metric)
+///
+/// Each growing `Complexity` `g` is fitted with the least squares method to `a + b * g(n)` and the
+/// one with the smallest residual sum of squares is chosen. If the fitted growth over the whole
+/// parameter range is insignificant, the `Complexity` is [`Complexity::Constant`]. Returns `None`
+/// if there are less than [`MIN_PARAMETERS`] different parameters or a parameter is smaller than
+/// `1`.
+pub fn fit(points: &[(f64, f64)]) -> Option<Complexity> {
+    let mut parameters = points.iter().map(|(n, _)| *n).collect::<Vec<_>>();
+    parameters.sort_by(f64::total_cmp);
+    parameters.dedup();
+    if parameters.len() < MIN_PARAMETERS || parameters[0] < 1.0f64 {
+        return None;
+    }
+    let min = parameters[0];
+    let max = parameters[parameters.len() - 1];
+
+    #[allow(clippy::cast_precision_loss)]
+    let len = points.len() as f64;
+    let y_mean = points.iter().map(|(_, y)| y).sum::<f64>() / len;
+
+    let mut best: Option<(Complexity, f64, f64)> = None;
+    for complexity in Complexity::GROWING {
+        let x_mean = points
+            .iter()
+            .map(|(n, _)| complexity.apply(*n))
+            .sum::<f64>()
+            / len;
+        let (mut sxx, mut sxy) = (0.0f64, 0.0f64);
+        for (n, y) in points {
+            let dx = complexity.apply(*n) - x_mean;
+            sxx += dx * dx;
+            sxy += dx * (y - y_mean);
+        }
+        let slope = sxy / sxx;
+        let intercept = slope.mul_add(-x_mean, y_mean);
+        let rss = points
+            .iter()
+            .map(|(n, y)| y - slope.mul_add(complexity.apply(*n), intercept))
+            .map(|residual| residual * residual)
+            .sum::<f64>();
+
+        if best.map_or(true, |(_, best_rss, _)| rss < best_rss) {
+            best = Some((complexity, rss, slope));
+        }
+    }
+
+    let (complexity, _, slope) = best?;
+    let growth = slope * (complexity.apply(max) - complexity.apply(min));
+    if growth < CONSTANT_TOLERANCE * y_mean.abs() {
+        Some(Complexity::Constant)
+    } else {
+        Some(complexity)
+    }
+}
+
+/// Print the [`SweepSummary`] if the `output_format` is the default format
+///
+/// Like the regressions of the metrics, an increase of the growth class is printed with any
+/// `output_format`.
+pub fn print(sweep: &SweepSummary, output_format: &OutputFormat) {
+    if output_format.is_default() {
+        let mut formatter = VerticalFormatter::new(*output_format);
+        formatter.format_sweep(sweep);
+        formatter.print_buffer();
+    }
+
+    if let (true, Some(new), Some(old)) = (sweep.regressed, sweep.new, sweep.old) {
+        writeln!(
+            stderr(),
+            "Performance has {0}: The growth class of {1} ({2}) increased from {old} to {new}",
+            "regressed".bold().bright_red(),
+            sweep.function_name.bold(),
+            sweep.event_kind
+        )
+        .unwrap();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+    use crate::runner::common::ModulePath;
+    use crate::runner::metrics::Metrics;
+    use crate::runner::summary::{BenchmarkKind, CallgrindSummary, MetricsSummary};
+
+    fn summary_fixture(new: u64, old: Option<u64>) -> BenchmarkSummary {
+        let mut summary = BenchmarkSummary::new(
+            BenchmarkKind::LibraryBenchmark,
+            PathBuf::from("/project"),
+            PathBuf::from("/project"),
+            PathBuf::from("benches/my_bench.rs"),
+            PathBuf::from("/project/target/release/deps/my_bench"),
+            &ModulePath::new("my_bench::my_group::bench_sort"),
+            "bench_sort",
+            None,
+            None,
+            None,
+        );
+        let new = Metrics::with_metric_kinds([(EventKind::Ir, new)]);
+        let metrics = match old {
+            Some(old) => {
+                EitherOrBoth::Both(new, Metrics::with_metric_kinds([(EventKind::Ir, old)]))
+            }
+            None => EitherOrBoth::Left(new),
+        };
+        let mut callgrind_summary = CallgrindSummary::new(vec![], vec![]);
+        callgrind_summary.callgrind_run.total.summary = MetricsSummary::new(metrics);
+        summary.callgrind_summary = Some(callgrind_summary);
+        summary
+    }
+
+    fn points<F>(parameters: &[f64], func: F) -> Vec<(f64, f64)>
+    where
+        F: Fn(f64) -> f64,
+    {
+        parameters.iter().map(|n| (*n, func(*n))).collect()
+    }
+
+    #[rstest]
+    #[case::integer("10", Some(10.0f64))]
+    #[case::underscores("1_000", Some(1000.0f64))]
+    #[case::suffix("100usize", Some(100.0f64))]
+    #[case::float("2.5f64", Some(2.5f64))]
+    #[case::method("100.to_string()", Some(100.0f64))]
+    #[case::vec("vec! [0u8 ; 1000]", None)]
+    #[case::identifier("size_10", None)]
+    #[case::no_number("vec! []", None)]
+    #[case::two_numbers("10, 20", None)]
+    fn test_parse_parameter(#[case] args: &str, #[case] expected: Option<f64>) {
+        assert_eq!(parse_parameter(args), expected);
+    }
+
+    #[rstest]
+    #[case::constant(Complexity::Constant)]
+    #[case::linear(Complexity::Linear)]
+    #[case::linearithmic(Complexity::Linearithmic)]
+    #[case::quadratic(Complexity::Quadratic)]
+    fn test_fit(#[case] complexity: Complexity) {
+        let points = points(&[10.0f64, 100.0f64, 1000.0f64, 10000.0f64], |n| {
+            complexity.apply(n).mul_add(7.0f64, 500.0f64)
+        });
+        assert_eq!(fit(&points), Some(complexity));
+    }
+
+    #[test]
+    fn test_fit_when_constant_with_noise() {
+        let points = vec![
+            (10.0f64, 1000.0f64),
+            (100.0f64, 1010.0f64),
+            (1000.0f64, 1003.0f64),
+        ];
+        assert_eq!(fit(&points), Some(Complexity::Constant));
+    }
+
+    #[rstest]
+    #[case::empty(&[])]
+    #[case::too_few(&[10.0f64, 100.0f64])]
+    #[case::duplicates(&[10.0f64, 10.0f64, 100.0f64])]
+    #[case::zero(&[0.0f64, 10.0f64, 100.0f64])]
+    fn test_fit_when_not_possible(#[case] parameters: &[f64]) {
+        let points = points(parameters, |n| n);
+        assert_eq!(fit(&points), None);
+    }
+
+    #[rstest]
+    #[case::increased(Complexity::Quadratic, Complexity::Linear, true, true)]
+    #[case::increased_not_checked(Complexity::Quadratic, Complexity::Linear, false, false)]
+    #[case::decreased(Complexity::Linear, Complexity::Quadratic, true, false)]
+    #[case::unchanged(Complexity::Linear, Complexity::Linear, true, false)]
+    fn test_sweep_summary_new_regressed(
+        #[case] new: Complexity,
+        #[case] old: Complexity,
+        #[case] check_regression: bool,
+        #[case] expected: bool,
+    ) {
+        #[allow(clippy::cast_possible_truncation, clippy::cast_sign_loss)]
+        let value =
+            |complexity: Complexity, n: f64| complexity.apply(n).mul_add(7.0f64, 500.0f64) as u64;
+        let summaries = [10.0f64, 100.0f64, 1000.0f64, 10000.0f64]
+            .into_iter()
+            .map(|n| summary_fixture(value(new, n), Some(value(old, n))))
+            .collect::<Vec<_>>();
+        let args = ["10", "100", "1_000", "10_000usize"];
+        let benches = args
+            .iter()
+            .map(|args| Some(*args))
+            .zip(&summaries)
+            .collect::<Vec<_>>();
+
+        let sweep = SweepSummary::new("bench_sort", &benches, check_regression).unwrap();
+        assert_eq!(sweep.event_kind, EventKind::Ir);
+        assert_eq!(sweep.new, Some(new));
+        assert_eq!(sweep.old, Some(old));
+        assert_eq!(sweep.is_changed(), new != old);
+        assert_eq!(sweep.regressed, expected);
+    }
+
+    #[test]
+    fn test_sweep_summary_new_when_no_old_then_not_regressed() {
+        let summaries = [10, 100, 1000]
+            .into_iter()
+            .map(|n| summary_fixture(n * n, None))
+            .collect::<Vec<_>>();
+        let benches = ["10", "100", "1000"]
+            .iter()
+            .map(|args| Some(*args))
+            .zip(&summaries)
+            .collect::<Vec<_>>();
+
+        let sweep = SweepSummary::new("bench_sort", &benches, true).unwrap();
+        assert_eq!(sweep.new, Some(Complexity::Quadratic));
+        assert_eq!(sweep.old, None);
+        assert!(!sweep.regressed);
+    }
+
+    #[test]
+    fn test_complexity_display() {
+        assert_eq!(Complexity::Linearithmic.to_string(), "O(n log n)");
+        assert_eq!(Complexity::Quadratic.to_string(), "O(n^2)");
+    }
+}
@@ -26,6 +26,8 @@ pub struct ToolArgs {
     pub verbose: bool,
     pub trace_children: bool,
     pub fair_sched: FairSched,
+    /// If true, the errors are written to a separate xml output file instead of the log file
+    pub xml: bool,
     pub other: Vec<String>,
 }
 
@@ -66,6 +68,7 @@ impl ToolArgs {
                     "201".to_owned()
                 }
                 ValgrindTool::Callgrind
+                | ValgrindTool::Cachegrind
                 | ValgrindTool::Massif
                 | ValgrindTool::DHAT
                 | ValgrindTool::BBV => "0".to_owned(),
@@ -74,6 +77,7 @@ impl ToolArgs {
             other: Vec::default(),
             trace_children: true,
             fair_sched: FairSched::Try,
+            xml: false,
         };
 
         for arg in raw_args.0 {
@@ -84,9 +88,20 @@ impl ToolArgs {
             {
                 Some(("--tool", _)) => warn!("Ignoring {} argument '{arg}'", tool.id()),
                 Some((
-                    "--dhat-out-file" | "--massif-out-file" | "--bb-out-file" | "--pc-out-file"
-                    | "--log-file" | "--log-fd" | "--log-socket" | "--xml" | "--xml-file"
-                    | "--xml-fd" | "--xml-socket" | "--xml-user-comment",
+                    "--callgrind-out-file"
+                    | "--cachegrind-out-file"
+                    | "--dhat-out-file"
+                    | "--massif-out-file"
+                    | "--bb-out-file"
+                    | "--pc-out-file"
+                    | "--log-file"
+                    | "--log-fd"
+                    | "--log-socket"
+                    | "--xml"
+                    | "--xml-file"
+                    | "--xml-fd"
+                    | "--xml-socket"
+                    | "--xml-user-comment",
                     _,
                 )) => warn!(
                     "Ignoring {} argument '{arg}': Output/Log files of tools are managed by \
@@ -146,6 +161,18 @@ impl ToolArgs {
                 arg.push(callgrind_out_path.to_path());
                 self.output_paths.push(arg);
             }
+            ValgrindTool::Cachegrind => {
+                let mut arg = OsString::from("--cachegrind-out-file=");
+                let cachegrind_out_path = if let Some(modifier) = modifier {
+                    output_path.with_modifiers([modifier.as_ref()])
+                } else if self.trace_children {
+                    output_path.with_modifiers(["#%p"])
+                } else {
+                    output_path.clone()
+                };
+                arg.push(cachegrind_out_path.to_path());
+                self.output_paths.push(arg);
+            }
             ValgrindTool::Massif => {
                 let mut arg = OsString::from("--massif-out-file=");
                 let massif_out_path = if let Some(modifier) = modifier {
@@ -194,8 +221,24 @@ impl ToolArgs {
                 self.output_paths.push(bb_arg);
                 self.output_paths.push(pc_arg);
             }
-            // The other tools don't have an outfile
-            _ => {}
+            // With `--xml=yes`, valgrind doesn't write the errors to the log file anymore, so the
+            // xml output is only written on request
+            ValgrindTool::Memcheck | ValgrindTool::Helgrind | ValgrindTool::DRD => {
+                if !self.xml {
+                    return;
+                }
+                let mut arg = OsString::from("--xml-file=");
+                let xml_out_path = if let Some(modifier) = modifier {
+                    output_path.with_modifiers([modifier.as_ref()])
+                } else if self.trace_children {
+                    output_path.with_modifiers(["#%p"])
+                } else {
+                    output_path.clone()
+                };
+                arg.push(xml_out_path.to_path());
+                self.output_paths.push(OsString::from("--xml=yes"));
+                self.output_paths.push(arg);
+            }
         }
     }
 
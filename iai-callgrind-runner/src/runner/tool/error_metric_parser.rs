@@ -1,7 +1,7 @@
 // spell-checker:ignore suppr ctxts
 use std::fs::File;
 use std::io::{BufRead, BufReader};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 use anyhow::{Context, Result};
 use lazy_static::lazy_static;
@@ -10,6 +10,7 @@ use regex::Regex;
 use super::logfile_parser::{
     parse_header, Logfile, LogfileParser, EMPTY_LINE_RE, EXTRACT_FIELDS_RE, STRIP_PREFIX_RE,
 };
+use super::xml_parser;
 use crate::api::ErrorMetricKind;
 use crate::runner::metrics::Metrics;
 use crate::runner::summary::ToolMetrics;
@@ -112,6 +113,15 @@ impl LogfileParser for ErrorMetricLogfileParser {
             }
         }
 
+        // With `--xml=yes`, valgrind doesn't print the error summary line to the log file, but the
+        // error counts can be extracted from the xml output file.
+        if metrics.is_none() {
+            metrics = to_xml_path(&path)
+                .filter(|xml_path| xml_path.exists())
+                .map(|xml_path| xml_parser::parse(&xml_path).map(|output| output.metrics()))
+                .transpose()?;
+        }
+
         Ok(Logfile {
             header,
             path,
@@ -123,6 +133,21 @@ impl LogfileParser for ErrorMetricLogfileParser {
     }
 }
 
+/// Return the path to the xml output file which belongs to the log file at `path`
+///
+/// The file names of the log file and xml output file only differ in the `log` and `out` part of
+/// the extension, for example `memcheck.bench.log.old` and `memcheck.bench.out.old`.
+fn to_xml_path(path: &Path) -> Option<PathBuf> {
+    let file_name = path.file_name()?.to_str()?;
+    let index = file_name
+        .strip_suffix(".log")
+        .map(str::len)
+        .or_else(|| file_name.rfind(".log."))?;
+    let mut xml_file_name = file_name.to_owned();
+    xml_file_name.replace_range(index..index + 4, ".out");
+    Some(path.with_file_name(xml_file_name))
+}
+
 #[cfg(test)]
 mod tests {
     use pretty_assertions::assert_eq;
@@ -212,4 +237,21 @@ mod tests {
 
         assert_eq!(actual_errors, expected_errors);
     }
+
+    #[rstest]
+    #[case::log("memcheck.bench.log", Some("memcheck.bench.out"))]
+    #[case::old("memcheck.bench.log.old", Some("memcheck.bench.out.old"))]
+    #[case::base(
+        "memcheck.bench.log.base@default",
+        Some("memcheck.bench.out.base@default")
+    )]
+    #[case::pid("memcheck.bench.log.#1234", Some("memcheck.bench.out.#1234"))]
+    #[case::log_in_name("memcheck.some.log.log", Some("memcheck.some.log.out"))]
+    #[case::no_log("memcheck.bench.out", None)]
+    fn test_to_xml_path(#[case] log_path: &str, #[case] expected: Option<&str>) {
+        assert_eq!(
+            to_xml_path(&PathBuf::from("/some").join(log_path)),
+            expected.map(|e| PathBuf::from("/some").join(e))
+        );
+    }
 }
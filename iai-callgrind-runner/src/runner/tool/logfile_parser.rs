@@ -11,6 +11,7 @@ use super::generic_parser::GenericLogfileParser;
 use super::{ToolOutputPath, ValgrindTool};
 use crate::error::Error;
 use crate::runner::dhat::logfile_parser::DhatLogfileParser;
+use crate::runner::massif::logfile_parser::MassifLogfileParser;
 use crate::runner::summary::{
     SegmentDetails, ToolMetricSummary, ToolMetrics, ToolRun, ToolRunSegment,
 };
@@ -258,6 +259,7 @@ pub fn parse_header(path: &Path, mut lines: impl Iterator<Item = String>) -> Res
 pub fn parser_factory(tool: ValgrindTool, root_dir: PathBuf) -> Box<dyn LogfileParser> {
     match tool {
         ValgrindTool::DHAT => Box::new(DhatLogfileParser { root_dir }),
+        ValgrindTool::Massif => Box::new(MassifLogfileParser { root_dir }),
         ValgrindTool::Memcheck | ValgrindTool::DRD | ValgrindTool::Helgrind => {
             Box::new(ErrorMetricLogfileParser { root_dir })
         }
@@ -3,19 +3,21 @@ pub mod args;
 pub mod error_metric_parser;
 pub mod generic_parser;
 pub mod logfile_parser;
+pub mod suppressions;
+pub mod xml_parser;
 
 use std::collections::HashMap;
 use std::ffi::OsString;
 use std::fmt::{Display, Write as FmtWrite};
 use std::fs::{DirEntry, File};
-use std::io::{stderr, BufRead, BufReader, Write};
+use std::io::{BufRead, BufReader, Write};
 use std::os::unix::fs::MetadataExt;
 use std::path::{Path, PathBuf};
-use std::process::{Child, Command, ExitStatus, Output};
+use std::process::{Child, Command, ExitStatus, Output, Stdio};
 
 use anyhow::{anyhow, Context, Result};
 use lazy_static::lazy_static;
-use log::{debug, error, log_enabled};
+use log::{debug, error, log_enabled, warn};
 use logfile_parser::Logfile;
 use regex::Regex;
 #[cfg(feature = "schema")]
@@ -26,13 +28,19 @@ use self::args::ToolArgs;
 use super::args::NoCapture;
 use super::bin_bench::Delay;
 use super::callgrind::parser::parse_header;
+use super::callgrind::RegressionConfig;
 use super::common::{Assistant, Config, ModulePath, Sandbox};
+use super::dhat::json_parser;
+use super::dry_run::Invocation;
 use super::format::{print_no_capture_footer, Formatter, OutputFormat, VerticalFormatter};
 use super::meta::Metadata;
-use super::summary::{BaselineKind, ToolRun, ToolSummary};
+use super::output::{self, stderr};
+use super::summary::{
+    AllocationSiteSummary, BaselineKind, ErrorContextSummary, ToolRun, ToolSummary,
+};
 use crate::api::{self, ExitWith, Stream};
 use crate::error::Error;
-use crate::util::{self, resolve_binary_path, truncate_str_utf8, EitherOrBoth};
+use crate::util::{self, make_relative, resolve_binary_path, truncate_str_utf8, EitherOrBoth};
 
 lazy_static! {
     // This regex matches the original file name without the prefix as it is created by callgrind.
@@ -74,6 +82,7 @@ pub struct ToolConfig {
     pub is_enabled: bool,
     pub args: ToolArgs,
     pub outfile_modifier: Option<String>,
+    pub gen_suppressions: bool,
 }
 
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -116,6 +125,7 @@ pub enum ToolOutputPathKind {
 #[cfg_attr(feature = "schema", derive(JsonSchema))]
 pub enum ValgrindTool {
     Callgrind,
+    Cachegrind,
     Memcheck,
     Helgrind,
     DRD,
@@ -152,38 +162,29 @@ impl ToolCommand {
         self
     }
 
-    pub fn run(
-        mut self,
+    /// Configure the valgrind command with the `config`, the environment and the output paths
+    ///
+    /// Returns the resolved path of the `executable`.
+    fn configure(
+        &mut self,
         config: ToolConfig,
         executable: &Path,
         executable_args: &[OsString],
-        run_options: RunOptions,
+        run_options: &RunOptions,
         output_path: &ToolOutputPath,
-        module_path: &ModulePath,
-        mut child: Option<Child>,
-    ) -> Result<ToolOutput> {
+    ) -> Result<PathBuf> {
         debug!(
             "{}: Running with executable '{}'",
             self.tool.id(),
             executable.display()
         );
 
-        let RunOptions {
-            env_clear,
-            current_dir,
-            exit_with,
-            envs,
-            stdin,
-            stdout,
-            stderr,
-        } = run_options;
-
-        if env_clear {
+        if run_options.env_clear {
             debug!("Clearing environment variables");
             self.env_clear();
         }
 
-        if let Some(dir) = current_dir {
+        if let Some(dir) = &run_options.current_dir {
             debug!(
                 "{}: Setting current directory to '{}'",
                 self.tool.id(),
@@ -208,12 +209,72 @@ impl ToolCommand {
         );
 
         self.command
-            .args(tool_args.to_vec())
+            .args(args)
             .arg(&executable)
             .args(executable_args)
-            .envs(envs);
+            .envs(run_options.envs.iter().cloned());
+
+        Ok(executable)
+    }
+
+    /// Return the [`Invocation`] of the valgrind command without running it
+    ///
+    /// The command is configured exactly like in [`ToolCommand::run`].
+    pub fn to_invocation(
+        mut self,
+        config: ToolConfig,
+        executable: &Path,
+        executable_args: &[OsString],
+        run_options: &RunOptions,
+        output_path: &ToolOutputPath,
+    ) -> Result<Invocation> {
+        self.configure(
+            config,
+            executable,
+            executable_args,
+            run_options,
+            output_path,
+        )?;
+        Ok(Invocation::from_command(
+            self.tool,
+            &self.command,
+            run_options.env_clear,
+        ))
+    }
+
+    pub fn run(
+        mut self,
+        config: ToolConfig,
+        executable: &Path,
+        executable_args: &[OsString],
+        run_options: RunOptions,
+        output_path: &ToolOutputPath,
+        module_path: &ModulePath,
+        mut child: Option<Child>,
+    ) -> Result<ToolOutput> {
+        let executable = self.configure(
+            config,
+            executable,
+            executable_args,
+            &run_options,
+            output_path,
+        )?;
+
+        let RunOptions {
+            exit_with,
+            stdin,
+            stdout,
+            stderr,
+            ..
+        } = run_options;
 
-        if self.tool == ValgrindTool::Callgrind {
+        // Child processes can't write into the captured output of benchmarks which are run in
+        // parallel, so explicitly inherited streams are piped and passed on after the run
+        let is_captured = output::is_captured();
+        let pipe_stdout = is_captured && stdout == Some(api::Stdio::Inherit);
+        let pipe_stderr = is_captured && stderr == Some(api::Stdio::Inherit);
+
+        if self.tool.is_default_capable() {
             debug!("Applying --nocapture options");
             self.nocapture.apply(&mut self.command);
         }
@@ -221,9 +282,7 @@ impl ToolCommand {
         if let Some(stdin) = stdin {
             stdin
                 .apply(&mut self.command, Stream::Stdin, child.as_mut())
-                .map_err(|error| {
-                    Error::BenchmarkError(ValgrindTool::Callgrind, module_path.clone(), error)
-                })?;
+                .map_err(|error| Error::BenchmarkError(self.tool, module_path.clone(), error))?;
         }
 
         if let Some(stdout) = stdout {
@@ -238,9 +297,16 @@ impl ToolCommand {
                 .map_err(|error| Error::BenchmarkError(self.tool, module_path.clone(), error))?;
         }
 
+        if pipe_stdout {
+            self.command.stdout(Stdio::piped());
+        }
+        if pipe_stderr {
+            self.command.stderr(Stdio::piped());
+        }
+
         let output = match self.nocapture {
             NoCapture::True | NoCapture::Stderr | NoCapture::Stdout
-                if self.tool == ValgrindTool::Callgrind =>
+                if self.tool.is_default_capable() =>
             {
                 self.command
                     .status()
@@ -265,7 +331,8 @@ impl ToolCommand {
                 .map_err(|error| {
                     Error::LaunchError(PathBuf::from("valgrind"), error.to_string()).into()
                 })
-                .and_then(|output| {
+                .and_then(|mut output| {
+                    pass_on_piped(&mut output, pipe_stdout, pipe_stderr)?;
                     let status = output.status;
                     check_exit(
                         self.tool,
@@ -311,6 +378,7 @@ impl ToolConfig {
             is_enabled,
             args: args.into(),
             outfile_modifier: modifier,
+            gen_suppressions: false,
         }
     }
 
@@ -331,6 +399,9 @@ impl ToolConfig {
             log_paths: log_path.real_paths()?,
             out_paths: out_path.map_or_else(|| Ok(Vec::default()), ToolOutputPath::real_paths)?,
             summaries,
+            regressions: vec![],
+            allocation_sites: vec![],
+            errors: vec![],
         })
     }
 }
@@ -339,17 +410,80 @@ impl TryFrom<api::Tool> for ToolConfig {
     type Error = anyhow::Error;
 
     fn try_from(value: api::Tool) -> std::result::Result<Self, Self::Error> {
-        let tool = value.kind.into();
-        ToolArgs::try_from_raw_args(tool, value.raw_args).map(|args| Self {
-            tool,
-            is_enabled: value.enable.unwrap_or(true),
-            args,
-            outfile_modifier: None,
+        let tool: ValgrindTool = value.kind.into();
+        let gen_suppressions = match value.gen_suppressions {
+            Some(true) if !tool.is_error_tool() => {
+                warn!(
+                    "Ignoring the generation of suppressions for '{tool}': Only memcheck, \
+                     helgrind and drd support suppressions"
+                );
+                false
+            }
+            value => value.unwrap_or(false),
+        };
+        let xml = match value.xml {
+            Some(true) if !tool.is_error_tool() => {
+                warn!(
+                    "Ignoring the xml output for '{tool}': Only memcheck, helgrind and drd \
+                     support the xml output"
+                );
+                false
+            }
+            value => value.unwrap_or(false),
+        };
+
+        ToolArgs::try_from_raw_args(tool, value.raw_args).map(|mut args| {
+            if gen_suppressions {
+                args.other.push("--gen-suppressions=all".to_owned());
+            }
+            args.xml = xml;
+            Self {
+                tool,
+                is_enabled: value.enable.unwrap_or(true),
+                args,
+                outfile_modifier: None,
+                gen_suppressions,
+            }
         })
     }
 }
 
 impl ToolConfigs {
+    /// Create new `ToolConfigs` from the `tools` of the benchmark configuration
+    ///
+    /// The `valgrind_args` are applied to all tools before the arguments of the tool. The
+    /// `default_tool` is run separately as the main benchmarking tool, so a tool configuration for
+    /// the `default_tool` is ignored.
+    pub fn new(
+        tools: api::Tools,
+        valgrind_args: &api::RawArgs,
+        default_tool: ValgrindTool,
+    ) -> Result<Self> {
+        tools
+            .0
+            .into_iter()
+            .filter(|t| {
+                let is_default_tool = ValgrindTool::from(t.kind) == default_tool;
+                if is_default_tool {
+                    warn!(
+                        "Ignoring the tool configuration of '{default_tool}': '{default_tool}' is \
+                         already the default tool"
+                    );
+                }
+                !is_default_tool
+            })
+            .map(|mut t| {
+                if !valgrind_args.is_empty() {
+                    let mut new_args = valgrind_args.clone();
+                    new_args.extend_ignore_flag(t.raw_args.0.iter());
+                    t.raw_args = new_args;
+                }
+                t.try_into()
+            })
+            .collect::<Result<Vec<_>, _>>()
+            .map(Self)
+    }
+
     pub fn has_tools_enabled(&self) -> bool {
         self.0.iter().any(|t| t.is_enabled)
     }
@@ -362,6 +496,24 @@ impl ToolConfigs {
             .collect()
     }
 
+    /// Return the default tool and the enabled tools which have no output to compare with
+    ///
+    /// The `output_path` is the output path of the default tool. The other tools are compared by
+    /// their log files.
+    pub fn missing_baselines(&self, output_path: &ToolOutputPath) -> Vec<ValgrindTool> {
+        let mut tools = vec![];
+        if !output_path.to_base_path().exists() {
+            tools.push(output_path.tool);
+        }
+        tools.extend(
+            self.output_paths(output_path)
+                .iter()
+                .filter(|path| !path.to_log_output().to_base_path().exists())
+                .map(|path| path.tool),
+        );
+        tools
+    }
+
     fn print_headline(tool_config: &ToolConfig, output_format: &OutputFormat) {
         if output_format.is_default() {
             let mut formatter = VerticalFormatter::new(*output_format);
@@ -374,6 +526,40 @@ impl ToolConfigs {
         VerticalFormatter::new(*output_format).print(config, (None, None), tool_run)
     }
 
+    fn print_allocation_sites(output_format: &OutputFormat, sites: &[AllocationSiteSummary]) {
+        if output_format.is_default() && !sites.is_empty() {
+            let mut formatter = VerticalFormatter::new(*output_format);
+            formatter.format_allocation_sites(sites);
+            formatter.print_buffer();
+        }
+    }
+
+    /// Return true if the allocation sites of the DHAT output files should be parsed
+    fn has_allocation_sites(tool: ValgrindTool, output_format: &OutputFormat) -> bool {
+        tool == ValgrindTool::DHAT && output_format.show_allocation_sites > 0
+    }
+
+    fn print_suppressions(
+        output_format: &OutputFormat,
+        meta: &Metadata,
+        path: &Path,
+        count: usize,
+    ) {
+        if output_format.is_default() {
+            let mut formatter = VerticalFormatter::new(*output_format);
+            formatter.format_suppressions(&make_relative(&meta.project_root, path), count);
+            formatter.print_buffer();
+        }
+    }
+
+    fn print_errors(output_format: &OutputFormat, errors: &[ErrorContextSummary], has_old: bool) {
+        if output_format.is_default() && !errors.is_empty() {
+            let mut formatter = VerticalFormatter::new(*output_format);
+            formatter.format_error_contexts(errors, has_old);
+            formatter.print_buffer();
+        }
+    }
+
     pub fn parse(
         tool_config: &ToolConfig,
         meta: &Metadata,
@@ -396,6 +582,9 @@ impl ToolConfigs {
             log_paths: log_path.real_paths()?,
             out_paths: out_path.map_or_else(|| Ok(Vec::default()), ToolOutputPath::real_paths)?,
             summaries,
+            regressions: vec![],
+            allocation_sites: vec![],
+            errors: vec![],
         })
     }
 
@@ -403,6 +592,7 @@ impl ToolConfigs {
         &self,
         config: &Config,
         output_path: &ToolOutputPath,
+        regression_config: Option<&RegressionConfig>,
         output_format: &OutputFormat,
     ) -> Result<Vec<ToolSummary>> {
         let mut tool_summaries = vec![];
@@ -414,12 +604,35 @@ impl ToolConfigs {
             let output_path = output_path.to_tool_output(tool);
             let log_path = output_path.to_log_output();
 
-            let tool_summary = tool_config.parse_load(config, &log_path, None)?;
+            let mut tool_summary = tool_config.parse_load(config, &log_path, None)?;
 
             Self::print(config, output_format, &tool_summary.summaries)?;
 
+            if Self::has_allocation_sites(tool, output_format) {
+                tool_summary.allocation_sites = json_parser::summarize_allocation_sites(
+                    &json_parser::parse_allocation_sites(&output_path)?,
+                    &json_parser::parse_allocation_sites(&output_path.to_base_path())?,
+                    output_format.show_allocation_sites,
+                );
+                Self::print_allocation_sites(output_format, &tool_summary.allocation_sites);
+            }
+
+            if tool_config.args.xml {
+                let base_path = output_path.to_base_path();
+                tool_summary.errors = xml_parser::summarize_error_contexts(
+                    &xml_parser::parse_error_contexts(&output_path)?,
+                    &xml_parser::parse_error_contexts(&base_path)?,
+                );
+                Self::print_errors(output_format, &tool_summary.errors, base_path.exists());
+            }
+
             log_path.dump_log(log::Level::Info, &mut stderr())?;
 
+            if let Some(regression_config) = regression_config {
+                tool_summary.regressions =
+                    regression_config.check_and_print_tool(tool, &tool_summary.summaries);
+            }
+
             tool_summaries.push(tool_summary);
         }
 
@@ -439,6 +652,7 @@ impl ToolConfigs {
         setup: Option<&Assistant>,
         teardown: Option<&Assistant>,
         delay: Option<&Delay>,
+        regression_config: Option<&RegressionConfig>,
         output_format: &OutputFormat,
     ) -> Result<Vec<ToolSummary>> {
         let mut tool_summaries = vec![];
@@ -457,6 +671,15 @@ impl ToolConfigs {
             let parser = logfile_parser::parser_factory(tool, config.meta.project_root.clone());
 
             let old_summaries = parser.parse(&log_path.to_base_path())?;
+            let old_allocation_sites = Self::has_allocation_sites(tool, output_format)
+                .then(|| json_parser::parse_allocation_sites(&output_path.to_base_path()))
+                .transpose()?;
+            let old_errors = tool_config
+                .args
+                .xml
+                .then(|| xml_parser::parse_error_contexts(&output_path.to_base_path()))
+                .transpose()?;
+            let has_old_errors = output_path.to_base_path().exists();
             if save_baseline {
                 output_path.clear()?;
                 log_path.clear()?;
@@ -504,7 +727,7 @@ impl ToolConfigs {
                 sandbox.reset()?;
             }
 
-            let tool_summary = Self::parse(
+            let mut tool_summary = Self::parse(
                 tool_config,
                 &config.meta,
                 &log_path,
@@ -514,9 +737,37 @@ impl ToolConfigs {
 
             Self::print(config, output_format, &tool_summary.summaries)?;
 
+            if let Some(old_allocation_sites) = old_allocation_sites {
+                tool_summary.allocation_sites = json_parser::summarize_allocation_sites(
+                    &json_parser::parse_allocation_sites(&output_path)?,
+                    &old_allocation_sites,
+                    output_format.show_allocation_sites,
+                );
+                Self::print_allocation_sites(output_format, &tool_summary.allocation_sites);
+            }
+
+            if let Some(old_errors) = old_errors {
+                tool_summary.errors = xml_parser::summarize_error_contexts(
+                    &xml_parser::parse_error_contexts(&output_path)?,
+                    &old_errors,
+                );
+                Self::print_errors(output_format, &tool_summary.errors, has_old_errors);
+            }
+
+            if tool_config.gen_suppressions {
+                if let Some((path, count)) = suppressions::generate(&output_path)? {
+                    Self::print_suppressions(output_format, &config.meta, &path, count);
+                }
+            }
+
             output.dump_log(log::Level::Info);
             log_path.dump_log(log::Level::Info, &mut stderr())?;
 
+            if let Some(regression_config) = regression_config {
+                tool_summary.regressions =
+                    regression_config.check_and_print_tool(tool, &tool_summary.summaries);
+            }
+
             tool_summaries.push(tool_summary);
         }
 
@@ -650,6 +901,19 @@ impl ToolOutputPath {
         self.real_paths().map_or(false, |p| p.len() > 1)
     }
 
+    /// Return the path to the suppression file generated with [`suppressions::generate`]
+    pub fn to_suppressions_path(&self) -> PathBuf {
+        self.dir
+            .join(format!("{}.{}.supp", self.tool.id(), self.name))
+    }
+
+    /// Return the path to the annotate file of callgrind written with
+    /// [`crate::runner::callgrind::annotate::annotate_and_write`]
+    pub fn to_annotate_path(&self) -> PathBuf {
+        self.dir
+            .join(format!("{}.{}.annotate", self.tool.id(), self.name))
+    }
+
     pub fn to_base_path(&self) -> Self {
         Self {
             kind: match (&self.kind, &self.baseline_kind) {
@@ -1346,6 +1610,7 @@ impl ValgrindTool {
         match self {
             ValgrindTool::DHAT => "dhat".to_owned(),
             ValgrindTool::Callgrind => "callgrind".to_owned(),
+            ValgrindTool::Cachegrind => "cachegrind".to_owned(),
             ValgrindTool::Memcheck => "memcheck".to_owned(),
             ValgrindTool::Helgrind => "helgrind".to_owned(),
             ValgrindTool::DRD => "drd".to_owned(),
@@ -1354,12 +1619,59 @@ impl ValgrindTool {
         }
     }
 
+    /// Return true if the tool produces an output file in addition to the log file
+    ///
+    /// The output file of the tools which report errors is the xml output.
     pub fn has_output_file(&self) -> bool {
         matches!(
             self,
-            ValgrindTool::Callgrind | ValgrindTool::DHAT | ValgrindTool::BBV | ValgrindTool::Massif
+            ValgrindTool::Callgrind
+                | ValgrindTool::Cachegrind
+                | ValgrindTool::DHAT
+                | ValgrindTool::BBV
+                | ValgrindTool::Massif
+                | ValgrindTool::Memcheck
+                | ValgrindTool::Helgrind
+                | ValgrindTool::DRD
         )
     }
+
+    /// Return true if the tool reports errors (memcheck, helgrind and drd)
+    pub fn is_error_tool(&self) -> bool {
+        matches!(
+            self,
+            ValgrindTool::Memcheck | ValgrindTool::Helgrind | ValgrindTool::DRD
+        )
+    }
+
+    /// Return the default tool of a benchmark
+    ///
+    /// The `--default-tool` command-line argument takes precedence over the `default_tool` of the
+    /// benchmark configuration. If neither is present, the default tool is callgrind.
+    pub fn try_default_tool(tool: Option<api::ValgrindTool>, meta: &Metadata) -> Result<Self> {
+        let tool = meta
+            .args
+            .default_tool
+            .or_else(|| tool.map(Into::into))
+            .unwrap_or(ValgrindTool::Callgrind);
+
+        if tool.is_default_capable() {
+            Ok(tool)
+        } else {
+            Err(anyhow!(
+                "Invalid default tool '{tool}': Only callgrind and cachegrind can be the default \
+                 tool"
+            ))
+        }
+    }
+
+    /// Return true if this tool can be the default tool of a benchmark
+    ///
+    /// The metrics of the default tool are shown first and are used for the baselines and
+    /// regression checks.
+    pub fn is_default_capable(&self) -> bool {
+        matches!(self, ValgrindTool::Callgrind | ValgrindTool::Cachegrind)
+    }
 }
 
 impl Display for ValgrindTool {
@@ -1371,6 +1683,8 @@ impl Display for ValgrindTool {
 impl From<api::ValgrindTool> for ValgrindTool {
     fn from(value: api::ValgrindTool) -> Self {
         match value {
+            api::ValgrindTool::Callgrind => ValgrindTool::Callgrind,
+            api::ValgrindTool::Cachegrind => ValgrindTool::Cachegrind,
             api::ValgrindTool::Memcheck => ValgrindTool::Memcheck,
             api::ValgrindTool::Helgrind => ValgrindTool::Helgrind,
             api::ValgrindTool::DRD => ValgrindTool::DRD,
@@ -1388,6 +1702,7 @@ impl TryFrom<&str> for ValgrindTool {
         match value {
             "dhat" => Ok(ValgrindTool::DHAT),
             "callgrind" => Ok(ValgrindTool::Callgrind),
+            "cachegrind" => Ok(ValgrindTool::Cachegrind),
             "memcheck" => Ok(ValgrindTool::Memcheck),
             "helgrind" => Ok(ValgrindTool::Helgrind),
             "drd" => Ok(ValgrindTool::DRD),
@@ -1398,6 +1713,21 @@ impl TryFrom<&str> for ValgrindTool {
     }
 }
 
+/// Pass the piped `stdout` and `stderr` of the `child_output` on to the output of the runner
+fn pass_on_piped(
+    child_output: &mut Output,
+    is_stdout: bool,
+    is_stderr: bool,
+) -> std::io::Result<()> {
+    if is_stdout {
+        output::stdout().write_all(&std::mem::take(&mut child_output.stdout))?;
+    }
+    if is_stderr {
+        stderr().write_all(&std::mem::take(&mut child_output.stderr))?;
+    }
+    Ok(())
+}
+
 pub fn check_exit(
     tool: ValgrindTool,
     executable: &Path,
@@ -1497,4 +1827,127 @@ mod tests {
     fn test_bbv_filename_regex(#[case] haystack: &str) {
         assert!(BBV_ORIG_FILENAME_RE.is_match(haystack));
     }
+
+    #[rstest]
+    #[case::memcheck(api::ValgrindTool::Memcheck, Some(true), true)]
+    #[case::drd(api::ValgrindTool::DRD, Some(true), true)]
+    #[case::disabled(api::ValgrindTool::Helgrind, Some(false), false)]
+    #[case::default(api::ValgrindTool::Memcheck, None, false)]
+    #[case::not_supported(api::ValgrindTool::DHAT, Some(true), false)]
+    fn test_tool_config_gen_suppressions(
+        #[case] kind: api::ValgrindTool,
+        #[case] gen_suppressions: Option<bool>,
+        #[case] expected: bool,
+    ) {
+        let tool = api::Tool {
+            kind,
+            enable: None,
+            raw_args: api::RawArgs::new(vec!["--gen-suppressions=yes".to_owned()]),
+            show_log: None,
+            gen_suppressions,
+            xml: None,
+        };
+
+        let config = ToolConfig::try_from(tool).unwrap();
+        assert_eq!(config.gen_suppressions, expected);
+        assert_eq!(
+            config.args.other.last().map(String::as_str),
+            Some(if expected {
+                "--gen-suppressions=all"
+            } else {
+                "--gen-suppressions=yes"
+            })
+        );
+    }
+
+    #[rstest]
+    #[case::memcheck(api::ValgrindTool::Memcheck, Some(true), true)]
+    #[case::drd(api::ValgrindTool::DRD, Some(true), true)]
+    #[case::disabled(api::ValgrindTool::Helgrind, Some(false), false)]
+    #[case::default(api::ValgrindTool::Memcheck, None, false)]
+    #[case::not_supported(api::ValgrindTool::DHAT, Some(true), false)]
+    fn test_tool_config_xml(
+        #[case] kind: api::ValgrindTool,
+        #[case] xml: Option<bool>,
+        #[case] expected: bool,
+    ) {
+        let tool = api::Tool {
+            kind,
+            enable: None,
+            raw_args: api::RawArgs::default(),
+            show_log: None,
+            gen_suppressions: None,
+            xml,
+        };
+        let mut config = ToolConfig::try_from(tool).unwrap();
+        let output_path = ToolOutputPath::new(
+            ToolOutputPathKind::Out,
+            config.tool,
+            &BaselineKind::Old,
+            Path::new("/tmp"),
+            &ModulePath::new("my_bench"),
+            "bench",
+        );
+
+        config.args.set_output_arg(&output_path, None::<String>);
+
+        assert_eq!(config.args.xml, expected);
+        assert_eq!(
+            config
+                .args
+                .output_paths
+                .iter()
+                .any(|arg| arg == "--xml=yes"),
+            expected
+        );
+    }
+
+    #[rstest]
+    #[case::all(&["callgrind", "dhat", "memcheck"], &[])]
+    #[case::default_tool(&["dhat", "memcheck"], &[ValgrindTool::Callgrind])]
+    #[case::tool(&["callgrind", "dhat"], &[ValgrindTool::Memcheck])]
+    #[case::none(&[], &[ValgrindTool::Callgrind, ValgrindTool::DHAT, ValgrindTool::Memcheck])]
+    fn test_tool_configs_missing_baselines(
+        #[case] baselines: &[&str],
+        #[case] expected: &[ValgrindTool],
+    ) {
+        let tools = ToolConfigs(
+            [api::ValgrindTool::DHAT, api::ValgrindTool::Memcheck]
+                .into_iter()
+                .map(|kind| {
+                    ToolConfig::try_from(api::Tool {
+                        kind,
+                        enable: None,
+                        raw_args: api::RawArgs::default(),
+                        show_log: None,
+                        gen_suppressions: None,
+                        xml: None,
+                    })
+                    .unwrap()
+                })
+                .collect(),
+        );
+
+        let root = tempfile::tempdir().unwrap();
+        let output_path = ToolOutputPath::new(
+            ToolOutputPathKind::Out,
+            ValgrindTool::Callgrind,
+            &BaselineKind::Name("main".parse().unwrap()),
+            root.path(),
+            &ModulePath::new("my_bench::my_group"),
+            "bench_fn",
+        );
+        std::fs::create_dir_all(&output_path.dir).unwrap();
+        for tool in baselines {
+            let kind = if *tool == "callgrind" { "out" } else { "log" };
+            std::fs::File::create(
+                output_path
+                    .dir
+                    .join(format!("{tool}.bench_fn.{kind}.base@main")),
+            )
+            .unwrap();
+        }
+
+        assert_eq!(tools.missing_baselines(&output_path), expected);
+    }
 }
@@ -0,0 +1,241 @@
+//! Generate a suppression file from the `--gen-suppressions=all` output of memcheck, helgrind and
+//! drd
+//!
+//! The suppressions are collected from the log files and if present from the xml output files of
+//! the tool. Suppressions which are generated for multiple errors or processes are written only
+//! once to the suppression file `<tool>.<name>.supp` next to the log files. Each suppression gets a
+//! unique name, so the file can be passed to later runs with `--suppressions=<file>`.
+
+use std::fs::File;
+use std::io::{BufRead, BufReader, BufWriter, Write};
+use std::path::{Path, PathBuf};
+
+use anyhow::{Context, Result};
+use indexmap::IndexSet;
+use log::debug;
+
+use super::logfile_parser::STRIP_PREFIX_RE;
+use super::{xml_parser, ToolOutputPath};
+
+/// The body of a suppression without the braces and the name
+///
+/// The first line is the kind of the suppression like `Memcheck:Leak` followed by the optional
+/// extra line (like `match-leak-kinds: definite`) and the frames (`fun:...`, `obj:...`).
+pub type Suppression = Vec<String>;
+
+/// Parse the suppressions in the `reader`
+///
+/// A suppression starts with a line consisting of `{` and ends with a line consisting of `}`. The
+/// first line within the braces is the name of the suppression and is not part of the returned
+/// [`Suppression`]. Lines with the `==<pid>==` prefix of valgrind are stripped of this prefix.
+pub fn parse_reader<R: BufRead>(reader: R) -> Result<Vec<Suppression>> {
+    let mut suppressions = vec![];
+    let mut current: Option<Vec<String>> = None;
+
+    for line in reader.lines() {
+        let line = line?;
+        let line = STRIP_PREFIX_RE
+            .captures(&line)
+            .map_or(line.as_str(), |caps| caps.name("rest").unwrap().as_str())
+            .trim();
+
+        match (line, current.as_mut()) {
+            ("{", _) => current = Some(vec![]),
+            ("}", Some(_)) => {
+                let lines = current.take().unwrap();
+                // Skip the name
+                if lines.len() > 2 {
+                    suppressions.push(lines.into_iter().skip(1).collect());
+                }
+            }
+            (line, Some(lines)) if !line.is_empty() => lines.push(line.to_owned()),
+            _ => {}
+        }
+    }
+
+    Ok(suppressions)
+}
+
+/// Collect the deduplicated suppressions of all log and xml output files of the `output_path`
+pub fn collect(output_path: &ToolOutputPath) -> Result<IndexSet<Suppression>> {
+    let mut suppressions = IndexSet::new();
+
+    if let Ok(paths) = output_path.to_log_output().real_paths() {
+        for path in paths {
+            let file = File::open(&path)
+                .with_context(|| format!("Error opening log file '{}'", path.display()))?;
+            suppressions.extend(parse_reader(BufReader::new(file))?);
+        }
+    }
+
+    if let Ok(paths) = output_path.real_paths() {
+        for path in paths {
+            for error in xml_parser::parse(&path)?.errors {
+                if let Some(raw) = error.raw_suppression {
+                    suppressions.extend(parse_reader(raw.as_bytes())?);
+                }
+            }
+        }
+    }
+
+    Ok(suppressions)
+}
+
+/// Write the `suppressions` to the `writer` in the format of a valgrind suppression file
+///
+/// The suppressions are named `<prefix>.<number>` starting with `1`.
+pub fn write<W: Write>(
+    mut writer: W,
+    prefix: &str,
+    suppressions: &IndexSet<Suppression>,
+) -> Result<()> {
+    for (index, suppression) in suppressions.iter().enumerate() {
+        writeln!(writer, "{{")?;
+        writeln!(writer, "   {prefix}.{}", index + 1)?;
+        for line in suppression {
+            writeln!(writer, "   {line}")?;
+        }
+        writeln!(writer, "}}")?;
+    }
+
+    Ok(())
+}
+
+/// Generate the suppression file of the tool run at `output_path`
+///
+/// Returns the path to the suppression file and the number of suppressions in it. If there are no
+/// suppressions, a suppression file of a previous run is removed and `None` is returned.
+pub fn generate(output_path: &ToolOutputPath) -> Result<Option<(PathBuf, usize)>> {
+    let path = output_path.to_suppressions_path();
+    let suppressions = collect(output_path)?;
+
+    if suppressions.is_empty() {
+        remove(&path)?;
+        return Ok(None);
+    }
+
+    debug!(
+        "{}: Writing {} suppressions to '{}'",
+        output_path.tool.id(),
+        suppressions.len(),
+        path.display()
+    );
+    let file = File::create(&path)
+        .with_context(|| format!("Error creating suppression file '{}'", path.display()))?;
+    let mut writer = BufWriter::new(file);
+    write(
+        &mut writer,
+        &format!("{}.{}", output_path.tool.id(), output_path.name),
+        &suppressions,
+    )
+    .and_then(|()| writer.flush().map_err(Into::into))
+    .with_context(|| format!("Error writing suppression file '{}'", path.display()))?;
+
+    Ok(Some((path, suppressions.len())))
+}
+
+/// Remove the suppression file at `path` if it exists
+fn remove(path: &Path) -> Result<()> {
+    if path.exists() {
+        std::fs::remove_file(path)
+            .with_context(|| format!("Error removing suppression file '{}'", path.display()))?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+
+    use super::*;
+
+    const LOG: &str = "==1234== Memcheck, a memory error detector
+==1234== Invalid read of size 4
+==1234==    at 0x10916D: bench::bad_memory (lib.rs:7)
+==1234==
+{
+   <insert_a_suppression_name_here>
+   Memcheck:Addr4
+   fun:bench::bad_memory
+   fun:main
+}
+==1234== 16 bytes in 1 blocks are definitely lost in loss record 1 of 1
+{
+   <insert_a_suppression_name_here>
+   Memcheck:Leak
+   match-leak-kinds: definite
+   fun:malloc
+   fun:bench::leak
+}
+{
+   <insert_a_suppression_name_here>
+   Memcheck:Addr4
+   fun:bench::bad_memory
+   fun:main
+}
+==1234== ERROR SUMMARY: 3 errors from 3 contexts (suppressed: 0 from 0)
+";
+
+    fn suppression(lines: &[&str]) -> Suppression {
+        lines.iter().map(|l| (*l).to_owned()).collect()
+    }
+
+    #[test]
+    fn test_parse_reader() {
+        let expected = vec![
+            suppression(&["Memcheck:Addr4", "fun:bench::bad_memory", "fun:main"]),
+            suppression(&[
+                "Memcheck:Leak",
+                "match-leak-kinds: definite",
+                "fun:malloc",
+                "fun:bench::leak",
+            ]),
+            suppression(&["Memcheck:Addr4", "fun:bench::bad_memory", "fun:main"]),
+        ];
+        assert_eq!(parse_reader(LOG.as_bytes()).unwrap(), expected);
+    }
+
+    #[test]
+    fn test_parse_reader_when_prefixed() {
+        let log = "==1234== {\n==1234==    <name>\n==1234==    Helgrind:Race\n==1234==    \
+                   fun:main\n==1234== }\n";
+        assert_eq!(
+            parse_reader(log.as_bytes()).unwrap(),
+            vec![suppression(&["Helgrind:Race", "fun:main"])]
+        );
+    }
+
+    #[test]
+    fn test_parse_reader_when_unterminated() {
+        let log = "{\n   <name>\n   Memcheck:Leak\n   fun:main\n";
+        assert!(parse_reader(log.as_bytes()).unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_write_deduplicated() {
+        let suppressions = parse_reader(LOG.as_bytes())
+            .unwrap()
+            .into_iter()
+            .collect::<IndexSet<_>>();
+
+        let mut actual = vec![];
+        write(&mut actual, "memcheck.bench", &suppressions).unwrap();
+
+        let expected = "{
+   memcheck.bench.1
+   Memcheck:Addr4
+   fun:bench::bad_memory
+   fun:main
+}
+{
+   memcheck.bench.2
+   Memcheck:Leak
+   match-leak-kinds: definite
+   fun:malloc
+   fun:bench::leak
+}
+";
+        assert_eq!(String::from_utf8(actual).unwrap(), expected);
+    }
+}
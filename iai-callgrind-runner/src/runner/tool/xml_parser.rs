@@ -0,0 +1,559 @@
+//! Parse the xml output (`--xml=yes`) of the tools which report errors (memcheck, helgrind, drd)
+//!
+//! Unlike the log files, which only provide the error counts in the `ERROR SUMMARY` line, the xml
+//! output contains the kind, the description, the stack and possibly the generated suppression of
+//! each distinct error.
+
+// spell-checker:ignore sname suppcounts errorcounts xwhat rawtext
+use std::collections::HashMap;
+use std::fmt::Display;
+use std::io::BufRead;
+use std::path::Path;
+
+use anyhow::{Context, Result};
+use indexmap::IndexMap;
+use log::debug;
+use quick_xml::escape::resolve_predefined_entity;
+use quick_xml::events::Event;
+use quick_xml::Reader;
+
+use super::ToolOutputPath;
+use crate::api::ErrorMetricKind;
+use crate::runner::metrics::Metrics;
+use crate::runner::summary::ErrorContextSummary;
+use crate::util::EitherOrBoth;
+
+/// The error contexts identified by the kind of the error and the functions of the stack
+pub type ErrorContexts = IndexMap<(String, Vec<String>), (XmlError, u64)>;
+
+/// A single frame of the stack of an [`XmlError`]
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Frame {
+    /// The function name
+    pub function: Option<String>,
+    /// The object (binary or shared library) of the function
+    pub obj: Option<String>,
+    /// The source file of the function
+    pub file: Option<String>,
+    /// The line in the source `file`
+    pub line: Option<u64>,
+}
+
+/// A single distinct error in the xml output
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XmlError {
+    /// The unique id of the error within the xml output file
+    pub unique: String,
+    /// The kind of the error like `InvalidRead`
+    pub kind: String,
+    /// The description of the error
+    pub what: String,
+    /// The frames of the primary stack of the error starting with the innermost frame
+    pub stack: Vec<Frame>,
+    /// The name of the suppression if the tool generated a suppression
+    pub suppression: Option<String>,
+    /// The generated suppression as it would appear in a suppression file
+    pub raw_suppression: Option<String>,
+}
+
+/// The parsed xml output file of a tool
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct XmlOutput {
+    /// All errors in the order of their occurrence
+    pub errors: Vec<XmlError>,
+    /// The counts of the errors by their unique id
+    pub error_counts: HashMap<String, u64>,
+    /// The names of the used suppressions and how many errors they suppressed
+    pub suppression_counts: Vec<(String, u64)>,
+}
+
+impl Frame {
+    /// Return the function name or if not present the object or `???`
+    ///
+    /// The id doesn't contain the source file and line, so it doesn't change if unrelated code is
+    /// changed.
+    pub fn id(&self) -> String {
+        self.function
+            .as_ref()
+            .or(self.obj.as_ref())
+            .map_or_else(|| "???".to_owned(), Clone::clone)
+    }
+}
+
+impl Display for Frame {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.function.as_deref().unwrap_or("???"))?;
+        match (&self.file, self.line, &self.obj) {
+            (Some(file), Some(line), _) => write!(f, " ({file}:{line})"),
+            (Some(file), None, _) => write!(f, " ({file})"),
+            (None, _, Some(obj)) => write!(f, " (in {obj})"),
+            (None, _, None) => Ok(()),
+        }
+    }
+}
+
+impl XmlError {
+    /// Return the key which identifies this error in the [`ErrorContexts`]
+    pub fn key(&self) -> (String, Vec<String>) {
+        (
+            self.kind.clone(),
+            self.stack.iter().map(Frame::id).collect(),
+        )
+    }
+}
+
+impl XmlOutput {
+    /// Return how often the `error` occurred
+    ///
+    /// Errors which are not listed in the error counts (like memory leaks) occurred once.
+    pub fn count(&self, error: &XmlError) -> u64 {
+        self.error_counts.get(&error.unique).copied().unwrap_or(1)
+    }
+
+    /// Return the error metrics as they would appear in the `ERROR SUMMARY` line of the log file
+    pub fn metrics(&self) -> Metrics<ErrorMetricKind> {
+        let errors = self.errors.iter().map(|e| self.count(e)).sum();
+        let suppressed = self.suppression_counts.iter().map(|(_, c)| c).sum();
+        Metrics::with_metric_kinds([
+            (ErrorMetricKind::Errors, errors),
+            (ErrorMetricKind::Contexts, self.errors.len() as u64),
+            (ErrorMetricKind::SuppressedErrors, suppressed),
+            (
+                ErrorMetricKind::SuppressedContexts,
+                self.suppression_counts.len() as u64,
+            ),
+        ])
+    }
+}
+
+/// Parse the xml output of the `reader`
+pub fn parse_reader<R: BufRead>(reader: R) -> Result<XmlOutput> {
+    let mut reader = Reader::from_reader(reader);
+
+    let mut output = XmlOutput::default();
+    let mut path: Vec<String> = vec![];
+    let mut error: Option<XmlError> = None;
+    let mut stacks: usize = 0;
+    let mut frame: Option<Frame> = None;
+    let mut pair: (Option<String>, Option<u64>) = (None, None);
+    // The text of an element is split into multiple events at each entity reference
+    let mut text = String::new();
+
+    let mut buf = vec![];
+    loop {
+        match reader.read_event_into(&mut buf)? {
+            Event::Start(start) => {
+                text.clear();
+                let name = String::from_utf8_lossy(start.name().as_ref()).into_owned();
+                match (path.last().map(String::as_str), name.as_str()) {
+                    (Some("valgrindoutput"), "error") => {
+                        error = Some(XmlError::default());
+                        stacks = 0;
+                    }
+                    (Some("error"), "stack") => stacks += 1,
+                    (Some("stack"), "frame") if error.is_some() && stacks == 1 => {
+                        frame = Some(Frame::default());
+                    }
+                    (Some("errorcounts" | "suppcounts"), "pair") => pair = (None, None),
+                    _ => {}
+                }
+                path.push(name);
+            }
+            Event::End(_) => {
+                let trimmed = text.trim();
+                if !trimmed.is_empty() {
+                    handle_text(
+                        &path,
+                        trimmed.to_owned(),
+                        error.as_mut(),
+                        frame.as_mut(),
+                        &mut pair,
+                    )?;
+                }
+                text.clear();
+
+                let name = path.pop();
+                match (name.as_deref(), path.last().map(String::as_str)) {
+                    (Some("error"), Some("valgrindoutput")) => {
+                        output.errors.extend(error.take());
+                    }
+                    (Some("frame"), Some("stack")) => {
+                        if let (Some(error), Some(frame)) = (error.as_mut(), frame.take()) {
+                            error.stack.push(frame);
+                        }
+                    }
+                    (Some("pair"), Some("errorcounts")) => {
+                        if let (Some(unique), Some(count)) = pair.clone() {
+                            output.error_counts.insert(unique, count);
+                        }
+                    }
+                    (Some("pair"), Some("suppcounts")) => {
+                        if let (Some(name), Some(count)) = pair.clone() {
+                            output.suppression_counts.push((name, count));
+                        }
+                    }
+                    _ => {}
+                }
+            }
+            Event::Text(bytes) => text.push_str(&bytes.xml_content()?),
+            Event::GeneralRef(reference) => {
+                if let Some(ch) = reference.resolve_char_ref()? {
+                    text.push(ch);
+                } else {
+                    let name = reference.decode()?;
+                    let resolved = resolve_predefined_entity(&name)
+                        .with_context(|| format!("Unknown entity reference '&{name};'"))?;
+                    text.push_str(resolved);
+                }
+            }
+            Event::CData(cdata) => text.push_str(&String::from_utf8_lossy(&cdata.into_inner())),
+            Event::Eof => break,
+            _ => {}
+        }
+        buf.clear();
+    }
+
+    Ok(output)
+}
+
+/// Store the `text` of the innermost element of the `path` in the `error`, `frame` or `pair`
+fn handle_text(
+    path: &[String],
+    text: String,
+    error: Option<&mut XmlError>,
+    frame: Option<&mut Frame>,
+    pair: &mut (Option<String>, Option<u64>),
+) -> Result<()> {
+    let tail = path
+        .iter()
+        .skip(path.len().saturating_sub(3))
+        .map(String::as_str)
+        .collect::<Vec<_>>();
+
+    if let Some(frame) = frame {
+        match tail.as_slice() {
+            [.., "frame", "fn"] => frame.function = Some(text),
+            [.., "frame", "obj"] => frame.obj = Some(text),
+            [.., "frame", "file"] => frame.file = Some(text),
+            [.., "frame", "line"] => frame.line = text.parse().ok(),
+            _ => {}
+        }
+    } else if let Some(error) = error {
+        match tail.as_slice() {
+            [.., "error", "unique"] => error.unique = text,
+            [.., "error", "kind"] => error.kind = text,
+            [.., "error", "what"] | ["error", "xwhat", "text"] => error.what = text,
+            ["error", "suppression", "sname"] => error.suppression = Some(text),
+            ["error", "suppression", "rawtext"] => {
+                error.raw_suppression = Some(text.trim().to_owned());
+            }
+            _ => {}
+        }
+    } else {
+        match tail.as_slice() {
+            [.., "pair", "unique" | "name"] => pair.0 = Some(text),
+            [.., "pair", "count"] => {
+                pair.1 = Some(
+                    text.parse()
+                        .with_context(|| format!("Invalid count in xml output: '{text}'"))?,
+                );
+            }
+            _ => {}
+        }
+    }
+
+    Ok(())
+}
+
+/// Parse a single xml output file
+pub fn parse(path: &Path) -> Result<XmlOutput> {
+    let file = std::fs::File::open(path)
+        .with_context(|| format!("Error opening xml output file '{}'", path.display()))?;
+
+    parse_reader(std::io::BufReader::new(file))
+        .with_context(|| format!("Error parsing xml output file '{}'", path.display()))
+}
+
+/// Parse the error contexts of all xml output files of the `output_path`
+///
+/// Errors with the same kind and the same functions in the stack are summed up, also if they come
+/// from different processes. If there are no output files, the returned `ErrorContexts` are empty.
+pub fn parse_error_contexts(output_path: &ToolOutputPath) -> Result<ErrorContexts> {
+    let mut contexts = ErrorContexts::new();
+
+    let Ok(paths) = output_path.real_paths() else {
+        return Ok(contexts);
+    };
+
+    for path in paths {
+        debug!("Parsing xml output file '{}'", path.display());
+        let output = parse(&path)?;
+        for error in &output.errors {
+            let count = output.count(error);
+            contexts
+                .entry(error.key())
+                .and_modify(|(_, c)| *c += count)
+                .or_insert_with(|| (error.clone(), count));
+        }
+    }
+
+    Ok(contexts)
+}
+
+/// Return the [`ErrorContextSummary`]s of the `new` and `old` error contexts
+///
+/// The new error contexts come first in the order of their occurrence followed by the error
+/// contexts which are only present in the `old` error contexts.
+pub fn summarize_error_contexts(
+    new: &ErrorContexts,
+    old: &ErrorContexts,
+) -> Vec<ErrorContextSummary> {
+    new.iter()
+        .map(|(key, (error, count))| {
+            let counts = match old.get(key) {
+                Some((_, old_count)) => EitherOrBoth::Both(*count, *old_count),
+                None => EitherOrBoth::Left(*count),
+            };
+            (error, counts)
+        })
+        .chain(
+            old.iter()
+                .filter(|(key, _)| !new.contains_key(*key))
+                .map(|(_, (error, count))| (error, EitherOrBoth::Right(*count))),
+        )
+        .map(|(error, counts)| ErrorContextSummary {
+            kind: error.kind.clone(),
+            what: error.what.clone(),
+            stack: error.stack.iter().map(ToString::to_string).collect(),
+            suppression: error.suppression.clone(),
+            counts,
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use pretty_assertions::assert_eq;
+    use rstest::rstest;
+
+    use super::*;
+
+    const XML: &str = r#"<?xml version="1.0"?>
+<valgrindoutput>
+<protocolversion>4</protocolversion>
+<protocoltool>memcheck</protocoltool>
+<error>
+  <unique>0x0</unique>
+  <tid>1</tid>
+  <kind>InvalidRead</kind>
+  <what>Invalid read of size 4</what>
+  <stack>
+    <frame>
+      <ip>0x10916D</ip>
+      <obj>/tmp/bench</obj>
+      <fn>bench::bad_memory</fn>
+      <dir>/tmp/src</dir>
+      <file>lib.rs</file>
+      <line>7</line>
+    </frame>
+    <frame>
+      <ip>0x109190</ip>
+      <obj>/tmp/bench</obj>
+    </frame>
+  </stack>
+  <auxwhat>Address 0x4a8e050 is 0 bytes after a block of size 16 alloc'd</auxwhat>
+  <stack>
+    <frame>
+      <ip>0x48407B4</ip>
+      <fn>malloc</fn>
+    </frame>
+  </stack>
+  <suppression>
+    <sname>insert_a_suppression_name_here</sname>
+    <skind>Memcheck:Addr4</skind>
+    <rawtext>
+<![CDATA[
+{
+   <insert_a_suppression_name_here>
+   Memcheck:Addr4
+   fun:bench::bad_memory
+}
+]]>
+    </rawtext>
+  </suppression>
+</error>
+<error>
+  <unique>0x1</unique>
+  <tid>1</tid>
+  <kind>Leak_DefinitelyLost</kind>
+  <xwhat>
+    <text>16 bytes in 1 blocks are definitely lost in loss record 1 of 1</text>
+    <leakedbytes>16</leakedbytes>
+  </xwhat>
+  <stack>
+    <frame>
+      <ip>0x48407B4</ip>
+      <obj>/usr/lib/valgrind/vgpreload_memcheck-amd64-linux.so</obj>
+      <fn>malloc</fn>
+    </frame>
+  </stack>
+</error>
+<errorcounts>
+  <pair>
+    <count>3</count>
+    <unique>0x0</unique>
+  </pair>
+</errorcounts>
+<suppcounts>
+  <pair>
+    <count>2</count>
+    <name>some_suppression</name>
+  </pair>
+</suppcounts>
+</valgrindoutput>
+"#;
+
+    fn error(kind: &str, functions: &[&str]) -> XmlError {
+        XmlError {
+            kind: kind.to_owned(),
+            what: format!("{kind} error"),
+            stack: functions
+                .iter()
+                .map(|f| Frame {
+                    function: Some((*f).to_owned()),
+                    ..Default::default()
+                })
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn contexts(errors: &[(XmlError, u64)]) -> ErrorContexts {
+        errors
+            .iter()
+            .map(|(error, count)| (error.key(), (error.clone(), *count)))
+            .collect()
+    }
+
+    #[test]
+    fn test_parse_reader() {
+        let output = parse_reader(XML.as_bytes()).unwrap();
+
+        assert_eq!(output.errors.len(), 2);
+        assert_eq!(
+            output.errors[0],
+            XmlError {
+                unique: "0x0".to_owned(),
+                kind: "InvalidRead".to_owned(),
+                what: "Invalid read of size 4".to_owned(),
+                stack: vec![
+                    Frame {
+                        function: Some("bench::bad_memory".to_owned()),
+                        obj: Some("/tmp/bench".to_owned()),
+                        file: Some("lib.rs".to_owned()),
+                        line: Some(7),
+                    },
+                    Frame {
+                        function: None,
+                        obj: Some("/tmp/bench".to_owned()),
+                        file: None,
+                        line: None,
+                    }
+                ],
+                suppression: Some("insert_a_suppression_name_here".to_owned()),
+                raw_suppression: Some(
+                    "{\n   <insert_a_suppression_name_here>\n   Memcheck:Addr4\n   \
+                     fun:bench::bad_memory\n}"
+                        .to_owned()
+                ),
+            }
+        );
+        assert_eq!(
+            output.errors[1].what,
+            "16 bytes in 1 blocks are definitely lost in loss record 1 of 1"
+        );
+        assert_eq!(output.count(&output.errors[0]), 3);
+        assert_eq!(output.count(&output.errors[1]), 1);
+        assert_eq!(
+            output.suppression_counts,
+            vec![("some_suppression".to_owned(), 2)]
+        );
+        assert_eq!(
+            output.metrics(),
+            Metrics::with_metric_kinds([
+                (ErrorMetricKind::Errors, 4),
+                (ErrorMetricKind::Contexts, 2),
+                (ErrorMetricKind::SuppressedErrors, 2),
+                (ErrorMetricKind::SuppressedContexts, 1),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_parse_reader_with_entity_references() {
+        let xml = "<valgrindoutput><error><unique>0x0</unique><kind>InvalidRead</\
+                   kind><what>Invalid read &amp; write of size \
+                   &#52;</what><stack><frame><fn>drop_in_place&lt;alloc::vec::Vec&lt;u8&gt;&gt;</\
+                   fn></frame></stack></error></valgrindoutput>";
+
+        let output = parse_reader(xml.as_bytes()).unwrap();
+
+        assert_eq!(output.errors.len(), 1);
+        assert_eq!(output.errors[0].what, "Invalid read & write of size 4");
+        assert_eq!(
+            output.errors[0].stack[0].function.as_deref(),
+            Some("drop_in_place<alloc::vec::Vec<u8>>")
+        );
+    }
+
+    #[rstest]
+    #[case::file_and_line(
+        Some("main"),
+        Some("lib.rs"),
+        Some(7),
+        Some("bench"),
+        "main (lib.rs:7)"
+    )]
+    #[case::only_obj(Some("main"), None, None, Some("bench"), "main (in bench)")]
+    #[case::unknown(None, None, None, Some("bench"), "??? (in bench)")]
+    #[case::nothing(None, None, None, None, "???")]
+    fn test_frame_display(
+        #[case] function: Option<&str>,
+        #[case] file: Option<&str>,
+        #[case] line: Option<u64>,
+        #[case] obj: Option<&str>,
+        #[case] expected: &str,
+    ) {
+        let frame = Frame {
+            function: function.map(ToOwned::to_owned),
+            obj: obj.map(ToOwned::to_owned),
+            file: file.map(ToOwned::to_owned),
+            line,
+        };
+        assert_eq!(frame.to_string(), expected);
+    }
+
+    #[test]
+    fn test_summarize_error_contexts() {
+        let unchanged = error("InvalidRead", &["main"]);
+        let fixed = error("InvalidWrite", &["main"]);
+        let new = error("InvalidRead", &["other", "main"]);
+
+        let actual = summarize_error_contexts(
+            &contexts(&[(unchanged.clone(), 2), (new.clone(), 1)]),
+            &contexts(&[(fixed.clone(), 5), (unchanged.clone(), 3)]),
+        );
+
+        let expected_counts = [
+            ("InvalidRead", EitherOrBoth::Both(2, 3)),
+            ("InvalidRead", EitherOrBoth::Left(1)),
+            ("InvalidWrite", EitherOrBoth::Right(5)),
+        ];
+        assert_eq!(actual.len(), expected_counts.len());
+        for (summary, (kind, counts)) in actual.iter().zip(expected_counts) {
+            assert_eq!(summary.kind, kind);
+            assert_eq!(summary.counts, counts);
+        }
+        assert_eq!(actual[1].stack, vec!["other".to_owned(), "main".to_owned()]);
+    }
+}
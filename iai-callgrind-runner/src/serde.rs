@@ -49,6 +49,74 @@ pub mod float_64 {
     }
 }
 
+/// The same as [`float_64`] but for optional f64 values. `None` is serialized as `null`.
+pub mod option_float_64 {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    use super::FromStr;
+
+    pub fn serialize<S>(input: &Option<f64>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match input {
+            Some(value) => serializer.serialize_some(&value.to_string()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<f64>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Option::<String>::deserialize(deserializer)?
+            .map(|value| {
+                f64::from_str(&value).map_err(|error| serde::de::Error::custom(error.to_string()))
+            })
+            .transpose()
+    }
+}
+
+/// Serialize bytes gzip compressed and base64 encoded as string. Unlike a string, the bytes don't
+/// have to be valid UTF-8 and the compression keeps large contents, like callgrind output files,
+/// reasonably small.
+pub mod gzip_base64 {
+    use std::io::{Read, Write};
+
+    use base64::engine::general_purpose::STANDARD;
+    use base64::Engine;
+    use flate2::read::GzDecoder;
+    use flate2::write::GzEncoder;
+    use flate2::Compression;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(input: &[u8], serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        let mut encoder = GzEncoder::new(vec![], Compression::default());
+        let compressed = encoder
+            .write_all(input)
+            .and_then(|()| encoder.finish())
+            .map_err(|error| serde::ser::Error::custom(error.to_string()))?;
+        serializer.serialize_str(&STANDARD.encode(compressed))
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Vec<u8>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let compressed = STANDARD
+            .decode(String::deserialize(deserializer)?)
+            .map_err(|error| serde::de::Error::custom(error.to_string()))?;
+        let mut bytes = vec![];
+        GzDecoder::new(compressed.as_slice())
+            .read_to_end(&mut bytes)
+            .map_err(|error| serde::de::Error::custom(error.to_string()))?;
+        Ok(bytes)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use rstest::rstest;
@@ -80,6 +148,56 @@ mod tests {
         assert!(round_trip(f64::NAN, "NaN").is_nan());
     }
 
+    #[derive(Serialize, Deserialize)]
+    struct OptionValueFixture {
+        #[serde(with = "option_float_64")]
+        value: Option<f64>,
+    }
+
+    #[rstest]
+    #[case::none(None, "null")]
+    #[case::zero(Some(0.0f64), r#""0""#)]
+    #[case::neg_two_two(Some(-2.2f64), r#""-2.2""#)]
+    #[case::pos_inf(Some(f64::INFINITY), r#""inf""#)]
+    fn test_serde_option_f64_round_trip(#[case] value: Option<f64>, #[case] expected: &str) {
+        let serialized = serde_json::to_string(&OptionValueFixture { value }).unwrap();
+        assert_eq!(serialized, format!(r#"{{"value":{expected}}}"#));
+        assert_eq!(
+            serde_json::from_str::<OptionValueFixture>(&serialized)
+                .unwrap()
+                .value,
+            value
+        );
+    }
+
+    #[derive(Serialize, Deserialize)]
+    struct BytesFixture {
+        #[serde(with = "gzip_base64")]
+        value: Vec<u8>,
+    }
+
+    #[rstest]
+    #[case::empty(b"")]
+    #[case::utf8(b"events: Ir\n")]
+    #[case::not_utf8(b"\xff\xfe\x00invalid \xc3\x28")]
+    fn test_serde_gzip_base64_round_trip(#[case] value: &[u8]) {
+        let serialized = serde_json::to_string(&BytesFixture {
+            value: value.to_vec(),
+        })
+        .unwrap();
+        assert_eq!(
+            serde_json::from_str::<BytesFixture>(&serialized)
+                .unwrap()
+                .value,
+            value
+        );
+    }
+
+    #[test]
+    fn test_serde_gzip_base64_when_invalid_base64_then_error() {
+        assert!(serde_json::from_str::<BytesFixture>(r#"{"value":"not base64!"}"#).is_err());
+    }
+
     #[track_caller]
     fn assert_round_trip_eq(value: f64, expected: &str) {
         assert_eq!(value, round_trip(value, expected));
@@ -1,6 +1,6 @@
 //! This module provides common utility functions
 use std::ffi::OsStr;
-use std::io::{self, BufWriter, Write};
+use std::io::{BufWriter, Write};
 use std::ops::Neg;
 use std::path::{Path, PathBuf};
 use std::process::Command;
@@ -13,6 +13,7 @@ use serde::{Deserialize, Serialize};
 use which::which;
 
 use crate::error::Error;
+use crate::runner::output;
 
 // # Developer notes
 //
@@ -134,32 +135,36 @@ pub fn trim(bytes: &[u8]) -> &[u8] {
 /// Dump all data to `stdout`
 pub fn write_all_to_stdout(bytes: &[u8]) {
     if !bytes.is_empty() {
-        let stdout = io::stdout();
-        let stdout = stdout.lock();
-        let mut writer = BufWriter::new(stdout);
+        let mut writer = BufWriter::new(output::stdout());
         writer
             .write_all(bytes)
+            .and_then(|()| {
+                if bytes.last().map_or(false, |l| *l == b'\n') {
+                    Ok(())
+                } else {
+                    writer.write_all(b"\n")
+                }
+            })
             .and_then(|()| writer.flush())
             .unwrap();
-        if !bytes.last().map_or(false, |l| *l == b'\n') {
-            println!();
-        }
     }
 }
 
 /// Dump all data to `stderr`
 pub fn write_all_to_stderr(bytes: &[u8]) {
     if !bytes.is_empty() {
-        let stderr = io::stderr();
-        let stderr = stderr.lock();
-        let mut writer = BufWriter::new(stderr);
+        let mut writer = BufWriter::new(output::stderr());
         writer
             .write_all(bytes)
+            .and_then(|()| {
+                if bytes.last().map_or(false, |l| *l == b'\n') {
+                    Ok(())
+                } else {
+                    writer.write_all(b"\n")
+                }
+            })
             .and_then(|()| writer.flush())
             .unwrap();
-        if !bytes.last().map_or(false, |l| *l == b'\n') {
-            eprintln!();
-        }
     }
 }
 
@@ -1,3 +1,5 @@
+mod test_annotate;
 mod test_flamegraph_parser;
+mod test_functions;
 mod test_hashmap_parser;
 mod test_summary_parser;
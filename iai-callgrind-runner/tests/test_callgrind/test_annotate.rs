@@ -0,0 +1,107 @@
+use std::path::{Path, PathBuf};
+
+use iai_callgrind_runner::api::EventKind;
+use iai_callgrind_runner::runner::callgrind::annotate::{
+    annotate, parse, write, AnnotateMap, Position,
+};
+use iai_callgrind_runner::runner::tool::{ToolOutputPathKind, ValgrindTool};
+use iai_callgrind_runner::util::EitherOrBoth;
+use pretty_assertions::assert_eq;
+
+use crate::common::Fixtures;
+
+#[test]
+fn test_parse_and_annotate_with_old() {
+    let output = Fixtures::get_tool_output_path(
+        "callgrind.out",
+        ValgrindTool::Callgrind,
+        ToolOutputPathKind::Out,
+        "annotate",
+    );
+    let base = output.to_base_path();
+
+    let functions = annotate(
+        &parse(&output).unwrap(),
+        &parse(&base).unwrap(),
+        EventKind::Ir,
+        5,
+    );
+
+    assert_eq!(functions.len(), 1);
+    let function = &functions[0];
+    assert_eq!(function.id.func, "my_lib::foo");
+    assert_eq!(function.cost, EitherOrBoth::Both(90, 70));
+    assert_eq!(
+        function.positions,
+        vec![(
+            Position {
+                file: Some("/some/project/src/lib.rs".to_owned()),
+                line: Some(7),
+                instr: Some(0x1008),
+            },
+            EitherOrBoth::Both(30, 10)
+        )]
+    );
+
+    let mut actual = vec![];
+    write(
+        &mut actual,
+        &functions,
+        EventKind::Ir,
+        &EitherOrBoth::Both(
+            PathBuf::from("/some/project/target/iai/callgrind.bench.out"),
+            PathBuf::from("/some/project/target/iai/callgrind.bench.out.old"),
+        ),
+        Path::new("/some/project"),
+    )
+    .unwrap();
+
+    let expected = "\
+--------------------------------------------------------------------------------
+Annotated self costs of the event 'Instructions'
+New: target/iai/callgrind.bench.out
+Old: target/iai/callgrind.bench.out.old
+--------------------------------------------------------------------------------
+
+Function: my_lib::foo
+File:     src/lib.rs
+Object:   target/release/deps/my-bin
+Instructions: 90|70 (+20 +28.57143%)
+--------------------------------------------------------------------------------
+           New           Old          Diff  Position
+            30            10           +20  0x1008 src/lib.rs:7
+";
+    assert_eq!(String::from_utf8(actual).unwrap(), expected);
+}
+
+#[test]
+fn test_parse_and_annotate_without_old() {
+    let output = Fixtures::get_tool_output_path(
+        "callgrind.out",
+        ValgrindTool::Callgrind,
+        ToolOutputPathKind::Out,
+        "annotate",
+    );
+
+    let functions = annotate(
+        &parse(&output).unwrap(),
+        &AnnotateMap::default(),
+        EventKind::Ir,
+        1,
+    );
+
+    assert_eq!(functions.len(), 1);
+    assert_eq!(functions[0].id.func, "my_lib::foo");
+    assert_eq!(functions[0].cost, EitherOrBoth::Left(90));
+    assert_eq!(
+        functions[0]
+            .positions
+            .iter()
+            .map(|(p, c)| (p.line, c.clone()))
+            .collect::<Vec<_>>(),
+        vec![
+            (Some(5), EitherOrBoth::Left(60)),
+            (Some(7), EitherOrBoth::Left(30))
+        ]
+    );
+}
@@ -0,0 +1,91 @@
+use std::path::{Path, PathBuf};
+
+use iai_callgrind_runner::api::EventKind;
+use iai_callgrind_runner::runner::callgrind::functions::{parse_functions, summarize_functions};
+use iai_callgrind_runner::runner::callgrind::hashmap_parser::CallgrindMap;
+use iai_callgrind_runner::runner::tool::{ToolOutputPathKind, ValgrindTool};
+use iai_callgrind_runner::util::EitherOrBoth;
+use pretty_assertions::assert_eq;
+
+use crate::common::Fixtures;
+
+#[test]
+fn test_parse_and_summarize_functions() {
+    let output = Fixtures::get_tool_output_path(
+        "callgrind.out",
+        ValgrindTool::Callgrind,
+        ToolOutputPathKind::Out,
+        "functions",
+    );
+
+    let map = parse_functions(&output, Path::new("/some/project")).unwrap();
+    let functions = summarize_functions(&map, &CallgrindMap::default(), EventKind::Ir, 5);
+
+    let actual = functions
+        .iter()
+        .map(|f| {
+            (
+                f.function.as_str(),
+                f.file.clone(),
+                f.exclusive
+                    .diff_by_kind(&EventKind::Ir)
+                    .unwrap()
+                    .metrics
+                    .clone(),
+                f.inclusive
+                    .diff_by_kind(&EventKind::Ir)
+                    .unwrap()
+                    .metrics
+                    .clone(),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    assert_eq!(
+        actual,
+        vec![
+            (
+                "my_lib::foo",
+                Some(PathBuf::from("src/lib.rs")),
+                EitherOrBoth::Left(60),
+                EitherOrBoth::Left(100)
+            ),
+            (
+                "my_lib::bar",
+                Some(PathBuf::from("src/lib.rs")),
+                EitherOrBoth::Left(40),
+                EitherOrBoth::Left(40)
+            ),
+            (
+                "main",
+                Some(PathBuf::from("src/main.rs")),
+                EitherOrBoth::Left(15),
+                EitherOrBoth::Left(115)
+            ),
+        ]
+    );
+}
+
+#[test]
+fn test_summarize_functions_against_itself() {
+    let output = Fixtures::get_tool_output_path(
+        "callgrind.out",
+        ValgrindTool::Callgrind,
+        ToolOutputPathKind::Out,
+        "functions",
+    );
+
+    let map = parse_functions(&output, Path::new("/some/project")).unwrap();
+    let functions = summarize_functions(&map, &map, EventKind::Dr, 1);
+
+    assert_eq!(functions.len(), 1);
+    assert_eq!(functions[0].function, "my_lib::foo");
+    assert_eq!(
+        functions[0]
+            .exclusive
+            .diff_by_kind(&EventKind::Dr)
+            .unwrap()
+            .metrics,
+        EitherOrBoth::Both(12, 12)
+    );
+}
@@ -1 +1,2 @@
+mod test_compare;
 mod test_version;
@@ -0,0 +1,85 @@
+use std::path::Path;
+
+use pretty_assertions::assert_eq;
+
+use crate::common::{self, Fixtures};
+
+const EXPECTED_COMPARISON: &str =
+    "  Instructions:                        1100|1000                 (+10.0000%) [+1.10000x]
+  L1 Hits:                             1600|1481                 (+8.03511%) [+1.08035x]
+  L2 Hits:                                7|6                    (+16.6667%) [+1.16667x]
+  RAM Hits:                              13|13                   (No change)
+  Total read+write:                    1620|1500                 (+8.00000%) [+1.08000x]
+  Estimated Cycles:                    2090|1966                 (+6.30722%) [+1.06307x]
+";
+
+fn copy_fixture(name: &str, dest: &Path) {
+    std::fs::copy(Fixtures::get_path_of(Path::new("compare").join(name)), dest).unwrap();
+}
+
+#[test]
+fn test_compare_files() {
+    let old = Fixtures::get_path_of("compare/callgrind.old.out");
+    let new = Fixtures::get_path_of("compare/callgrind.new.out");
+
+    common::Runner::new()
+        .args(&["compare", old.to_str().unwrap(), new.to_str().unwrap()])
+        .run()
+        .assert_stdout(&format!("{}\n{EXPECTED_COMPARISON}", new.display()))
+        .assert_stderr("");
+}
+
+#[test]
+fn test_compare_baselines() {
+    let home = tempfile::tempdir().unwrap();
+    let dir = home
+        .path()
+        .join("my_package")
+        .join("my_bench")
+        .join("my_group")
+        .join("bench_fib.short");
+    std::fs::create_dir_all(&dir).unwrap();
+    copy_fixture(
+        "callgrind.old.out",
+        &dir.join("callgrind.bench_fib.short.out.base@before"),
+    );
+    copy_fixture(
+        "callgrind.new.out",
+        &dir.join("callgrind.bench_fib.short.out.base@after"),
+    );
+
+    common::Runner::new()
+        .args(&[
+            "compare",
+            "--save-summary",
+            "--home",
+            home.path().to_str().unwrap(),
+            "before",
+            "after",
+        ])
+        .run()
+        .assert_stdout(&format!(
+            "my_bench::my_group::bench_fib short\n  Baselines:                          \
+             after|before\n{EXPECTED_COMPARISON}"
+        ))
+        .assert_stderr("");
+
+    let summary: serde_json::Value =
+        serde_json::from_reader(std::fs::File::open(dir.join("summary.json")).unwrap()).unwrap();
+    assert_eq!(summary["module_path"], "my_bench::my_group::bench_fib");
+    assert_eq!(summary["id"], "short");
+    assert_eq!(summary["kind"], "LibraryBenchmark");
+}
+
+#[test]
+fn test_compare_invalid_baseline_name() {
+    common::Runner::new()
+        .args(&["compare", "does-not-exist", "new"])
+        .run()
+        .assert_stderr(
+            "iai_callgrind_runner: Error: 'does-not-exist' is neither an existing file nor a \
+             valid baseline name: A baseline name can only consist of ascii characters which are \
+             alphanumeric or '_' but found: '-'\n",
+        )
+        .assert_stdout_is_empty();
+}
@@ -1,2 +1,7 @@
+mod test_cachegrind_summary_parser;
+mod test_dhat_json_parser;
 mod test_error_metrics_parser;
+mod test_massif_parser;
 mod test_parse_logfile_header;
+mod test_suppressions;
+mod test_xml_parser;
@@ -0,0 +1,71 @@
+use iai_callgrind_runner::api::EventKind;
+use iai_callgrind_runner::runner::cachegrind::summary_parser::SummaryParser;
+use iai_callgrind_runner::runner::callgrind::parser::CallgrindParser;
+use iai_callgrind_runner::runner::metrics::Metrics;
+use iai_callgrind_runner::runner::tool::{ToolOutputPathKind, ValgrindTool};
+use pretty_assertions::assert_eq;
+
+use crate::common::Fixtures;
+
+#[test]
+fn test_cachegrind_summary_parser() {
+    let cachegrind_output_path = Fixtures::get_tool_output_path(
+        "cachegrind",
+        ValgrindTool::Cachegrind,
+        ToolOutputPathKind::Out,
+        "simple",
+    );
+
+    let parsed = SummaryParser.parse(&cachegrind_output_path).unwrap();
+    assert_eq!(parsed.len(), 1);
+
+    let (path, properties, metrics) = &parsed[0];
+    assert_eq!(path, &cachegrind_output_path.to_path());
+    assert_eq!(properties.pid, Some(3_120_411));
+    assert_eq!(
+        properties.cmd.as_deref(),
+        Some(
+            "/home/some/workspace/target/release/deps/test_lib_bench_some-4c5214398e2f5bd1 \
+             --iai-run some_group 0 0 test_lib_bench_some::some_group::bench_some"
+        )
+    );
+    assert!(properties.desc.is_empty());
+    assert_eq!(
+        metrics,
+        &Metrics::with_metric_kinds([(EventKind::Ir, 324_013)])
+    );
+}
+
+#[test]
+fn test_cachegrind_summary_parser_when_multiple_pids() {
+    let cachegrind_output_path = Fixtures::get_tool_output_path(
+        "cachegrind",
+        ValgrindTool::Cachegrind,
+        ToolOutputPathKind::Out,
+        "multiple_pids",
+    );
+
+    let parsed = SummaryParser.parse(&cachegrind_output_path).unwrap();
+    assert_eq!(parsed.len(), 2);
+
+    assert_eq!(parsed[0].1.pid, Some(3_120_512));
+    assert_eq!(parsed[0].1.desc.len(), 3);
+    assert_eq!(
+        parsed[0].2,
+        Metrics::with_metric_kinds([
+            (EventKind::Ir, 215_390),
+            (EventKind::I1mr, 1013),
+            (EventKind::ILmr, 994),
+            (EventKind::Dr, 52002),
+            (EventKind::D1mr, 1603),
+            (EventKind::DLmr, 1280),
+            (EventKind::Dw, 29879),
+            (EventKind::D1mw, 801),
+            (EventKind::DLmw, 642),
+        ])
+    );
+
+    assert_eq!(parsed[1].1.pid, Some(3_120_513));
+    assert_eq!(parsed[1].1.cmd.as_deref(), Some("/usr/bin/echo"));
+    assert_eq!(parsed[1].2.metric_by_kind(&EventKind::Ir), Some(1102));
+}
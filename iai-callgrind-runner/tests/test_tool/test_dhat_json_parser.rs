@@ -0,0 +1,79 @@
+use iai_callgrind_runner::api::DhatMetricKind;
+use iai_callgrind_runner::runner::dhat::json_parser::parse_allocation_sites;
+use iai_callgrind_runner::runner::metrics::Metrics;
+use iai_callgrind_runner::runner::tool::{ToolOutputPathKind, ValgrindTool};
+use pretty_assertions::assert_eq;
+
+use crate::common::Fixtures;
+
+#[test]
+fn test_dhat_json_parser_allocation_sites() {
+    let dhat_output_path = Fixtures::get_tool_output_path(
+        "dhat",
+        ValgrindTool::DHAT,
+        ToolOutputPathKind::Out,
+        "simple",
+    );
+
+    let sites = parse_allocation_sites(&dhat_output_path).unwrap();
+    assert_eq!(sites.len(), 2);
+
+    let (frames, metrics) = sites.get_index(0).unwrap();
+    assert_eq!(
+        frames,
+        &vec![
+            "malloc (in /usr/lib/valgrind/vgpreload_dhat-amd64-linux.so)".to_owned(),
+            "alloc::raw_vec::RawVec<T,A>::allocate_in (raw_vec.rs:196)".to_owned(),
+            "test_lib_bench_some::main (test_lib_bench_some.rs:10)".to_owned(),
+        ]
+    );
+    assert_eq!(
+        metrics,
+        &Metrics::with_metric_kinds([
+            (DhatMetricKind::TotalBytes, 1024),
+            (DhatMetricKind::TotalBlocks, 1),
+            (DhatMetricKind::AtTGmaxBytes, 1024),
+            (DhatMetricKind::AtTGmaxBlocks, 1),
+            (DhatMetricKind::AtTEndBytes, 0),
+            (DhatMetricKind::AtTEndBlocks, 0),
+            (DhatMetricKind::ReadsBytes, 512),
+            (DhatMetricKind::WritesBytes, 1024),
+            (DhatMetricKind::TotalLifetimes, 1500),
+            (DhatMetricKind::MaximumBytes, 1024),
+            (DhatMetricKind::MaximumBlocks, 1),
+        ])
+    );
+
+    // The program points with the same frames are summed up
+    let (_, metrics) = sites.get_index(1).unwrap();
+    assert_eq!(
+        metrics,
+        &Metrics::with_metric_kinds([
+            (DhatMetricKind::TotalBytes, 64),
+            (DhatMetricKind::TotalBlocks, 4),
+            (DhatMetricKind::AtTGmaxBytes, 16),
+            (DhatMetricKind::AtTGmaxBlocks, 1),
+            (DhatMetricKind::AtTEndBytes, 0),
+            (DhatMetricKind::AtTEndBlocks, 0),
+            (DhatMetricKind::ReadsBytes, 16),
+            (DhatMetricKind::WritesBytes, 64),
+            (DhatMetricKind::TotalLifetimes, 400),
+            (DhatMetricKind::MaximumBytes, 48),
+            (DhatMetricKind::MaximumBlocks, 3),
+        ])
+    );
+}
+
+#[test]
+fn test_dhat_json_parser_when_no_output_files() {
+    let dhat_output_path = Fixtures::get_tool_output_path(
+        "dhat",
+        ValgrindTool::DHAT,
+        ToolOutputPathKind::OldOut,
+        "simple",
+    );
+
+    assert!(parse_allocation_sites(&dhat_output_path)
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,59 @@
+use std::path::PathBuf;
+
+use iai_callgrind_runner::api::MassifMetricKind;
+use iai_callgrind_runner::runner::massif::logfile_parser::MassifLogfileParser;
+use iai_callgrind_runner::runner::metrics::Metrics;
+use iai_callgrind_runner::runner::summary::ToolMetrics;
+use iai_callgrind_runner::runner::tool::logfile_parser::LogfileParser;
+use iai_callgrind_runner::runner::tool::{ToolOutputPathKind, ValgrindTool};
+use pretty_assertions::assert_eq;
+
+use crate::common::Fixtures;
+
+fn expected_metrics(expected: [u64; 4]) -> ToolMetrics {
+    ToolMetrics::MassifMetrics(Metrics::with_metric_kinds([
+        (MassifMetricKind::PeakHeapBytes, expected[0]),
+        (MassifMetricKind::PeakExtraHeapBytes, expected[1]),
+        (MassifMetricKind::PeakStacksBytes, expected[2]),
+        (MassifMetricKind::Snapshots, expected[3]),
+    ]))
+}
+
+#[test]
+fn test_massif_parser() {
+    let massif_output_path = Fixtures::get_tool_output_path(
+        "massif",
+        ValgrindTool::Massif,
+        ToolOutputPathKind::Log,
+        "simple",
+    );
+
+    let parser = MassifLogfileParser {
+        root_dir: PathBuf::from("/does/not/matter"),
+    };
+
+    let logfiles = parser.parse(&massif_output_path).unwrap();
+    assert_eq!(logfiles.len(), 1);
+    assert_eq!(logfiles[0].header.pid, 2_960_870);
+    assert_eq!(logfiles[0].metrics, expected_metrics([3072, 32, 0, 5]));
+}
+
+#[test]
+fn test_massif_parser_when_multiple_pids() {
+    let massif_output_path = Fixtures::get_tool_output_path(
+        "massif",
+        ValgrindTool::Massif,
+        ToolOutputPathKind::Log,
+        "multiple_pids",
+    );
+
+    let parser = MassifLogfileParser {
+        root_dir: PathBuf::from("/does/not/matter"),
+    };
+
+    let logfiles = parser.parse(&massif_output_path).unwrap();
+    assert_eq!(logfiles.len(), 2);
+    assert_eq!(logfiles[0].metrics, expected_metrics([3072, 32, 0, 5]));
+    assert_eq!(logfiles[1].metrics, expected_metrics([472, 16, 3520, 3]));
+    assert_eq!(logfiles[1].header.command, "/usr/bin/echo");
+}
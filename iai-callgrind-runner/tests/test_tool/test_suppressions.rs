@@ -0,0 +1,66 @@
+use iai_callgrind_runner::runner::tool::suppressions::{collect, write};
+use iai_callgrind_runner::runner::tool::{ToolOutputPathKind, ValgrindTool};
+use pretty_assertions::assert_eq;
+use rstest::rstest;
+
+use crate::common::Fixtures;
+
+// The log file of a run with `--gen-suppressions=all` contains the suppressions. With `--xml=yes`,
+// the suppressions are in the `rawtext` of the xml output file instead and the log file has no
+// details of the errors.
+#[rstest]
+#[case::log(
+    "gen_suppressions",
+    "
+{
+   memcheck.gen_suppressions.1
+   Memcheck:Addr4
+   fun:_ZN20test_lib_bench_tools10bad_memory17h5d3b8f7c2a1e9f01E
+   fun:_ZN20test_lib_bench_tools4main17h0c1d2e3f4a5b6c7dE
+}
+{
+   memcheck.gen_suppressions.2
+   Memcheck:Leak
+   match-leak-kinds: definite
+   fun:malloc
+   fun:_ZN20test_lib_bench_tools4leak17h9a8b7c6d5e4f3a2bE
+}
+"
+)]
+#[case::xml(
+    "xml_suppressions",
+    "
+{
+   memcheck.gen_suppressions.1
+   Memcheck:Addr4
+   fun:_ZN20test_lib_bench_tools10bad_memory17h5d3b8f7c2a1e9f01E
+   fun:_ZN20test_lib_bench_tools4main17h0c1d2e3f4a5b6c7dE
+}
+{
+   memcheck.gen_suppressions.2
+   Memcheck:Cond
+   fun:_ZN20test_lib_bench_tools6uninit17h1a2b3c4d5e6f7a8bE
+}
+{
+   memcheck.gen_suppressions.3
+   Memcheck:Leak
+   match-leak-kinds: definite
+   fun:malloc
+   fun:_ZN20test_lib_bench_tools4leak17h9a8b7c6d5e4f3a2bE
+}
+"
+)]
+fn test_collect_and_write_deduplicated_suppressions(#[case] name: &str, #[case] expected: &str) {
+    let output_path = Fixtures::get_tool_output_path(
+        "memcheck",
+        ValgrindTool::Memcheck,
+        ToolOutputPathKind::Out,
+        name,
+    );
+
+    let suppressions = collect(&output_path).unwrap();
+
+    let mut actual = vec![];
+    write(&mut actual, "memcheck.gen_suppressions", &suppressions).unwrap();
+    assert_eq!(String::from_utf8(actual).unwrap(), expected.trim_start());
+}
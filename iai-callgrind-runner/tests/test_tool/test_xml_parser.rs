@@ -0,0 +1,97 @@
+use std::path::PathBuf;
+
+use iai_callgrind_runner::api::ErrorMetricKind;
+use iai_callgrind_runner::runner::metrics::Metrics;
+use iai_callgrind_runner::runner::summary::{ErrorContextSummary, ToolMetrics};
+use iai_callgrind_runner::runner::tool::error_metric_parser::ErrorMetricLogfileParser;
+use iai_callgrind_runner::runner::tool::logfile_parser::LogfileParser;
+use iai_callgrind_runner::runner::tool::xml_parser::{
+    parse_error_contexts, summarize_error_contexts,
+};
+use iai_callgrind_runner::runner::tool::{ToolOutputPathKind, ValgrindTool};
+use iai_callgrind_runner::util::EitherOrBoth;
+use pretty_assertions::assert_eq;
+
+use crate::common::Fixtures;
+
+#[test]
+fn test_summarize_error_contexts_with_old() {
+    let output_path = Fixtures::get_tool_output_path(
+        "memcheck",
+        ValgrindTool::Memcheck,
+        ToolOutputPathKind::Out,
+        "xml_errors",
+    );
+
+    let new = parse_error_contexts(&output_path).unwrap();
+    let old = parse_error_contexts(&output_path.to_base_path()).unwrap();
+    let expected = vec![
+        ErrorContextSummary {
+            kind: "InvalidRead".to_owned(),
+            what: "Invalid read of size 4".to_owned(),
+            stack: vec![
+                "test_lib_bench_tools::bad_memory (test_lib_bench_tools.rs:79)".to_owned(),
+                "test_lib_bench_tools::main (test_lib_bench_tools.rs:68)".to_owned(),
+            ],
+            suppression: Some("insert_a_suppression_name_here".to_owned()),
+            counts: EitherOrBoth::Both(3, 1),
+        },
+        ErrorContextSummary {
+            kind: "Leak_DefinitelyLost".to_owned(),
+            what: "5,600,000 (3,200,000 direct, 2,400,000 indirect) bytes in 100,000 blocks are \
+                   definitely lost in loss record 2 of 2"
+                .to_owned(),
+            stack: vec![
+                "malloc (vg_replace_malloc.c:446)".to_owned(),
+                "test_lib_bench_tools::bad_memory (test_lib_bench_tools.rs:79)".to_owned(),
+            ],
+            suppression: None,
+            counts: EitherOrBoth::Left(1),
+        },
+        ErrorContextSummary {
+            kind: "UninitCondition".to_owned(),
+            what: "Conditional jump or move depends on uninitialised value(s)".to_owned(),
+            stack: vec!["test_lib_bench_tools::bad_memory (test_lib_bench_tools.rs:75)".to_owned()],
+            suppression: None,
+            counts: EitherOrBoth::Right(1),
+        },
+    ];
+
+    assert_eq!(summarize_error_contexts(&new, &old), expected);
+}
+
+#[test]
+fn test_parse_error_contexts_when_no_output_file() {
+    let output_path = Fixtures::get_tool_output_path(
+        "memcheck",
+        ValgrindTool::Memcheck,
+        ToolOutputPathKind::Out,
+        "does_not_exist",
+    );
+
+    assert!(parse_error_contexts(&output_path).unwrap().is_empty());
+}
+
+#[test]
+fn test_error_metric_parser_when_error_summary_only_in_xml() {
+    let expected_metrics = ToolMetrics::ErrorMetrics(Metrics::with_metric_kinds([
+        (ErrorMetricKind::Errors, 4),
+        (ErrorMetricKind::Contexts, 2),
+        (ErrorMetricKind::SuppressedErrors, 2),
+        (ErrorMetricKind::SuppressedContexts, 1),
+    ]));
+
+    let output_path = Fixtures::get_tool_output_path(
+        "memcheck",
+        ValgrindTool::Memcheck,
+        ToolOutputPathKind::Log,
+        "xml_errors",
+    );
+    let parser = ErrorMetricLogfileParser {
+        root_dir: PathBuf::from("/does/not/matter"),
+    };
+
+    let logfiles = parser.parse(&output_path).unwrap();
+    assert_eq!(logfiles.len(), 1);
+    assert_eq!(logfiles[0].metrics, expected_metrics);
+}
@@ -10,7 +10,7 @@ use derive_more::AsRef;
 use iai_callgrind_macros::IntoInner;
 use iai_callgrind_runner::api::RawArgs;
 
-use crate::{internal, DelayKind, Stdin, Stdio};
+use crate::{internal, DelayKind, Stdin, Stdio, ValgrindTool};
 
 /// [low level api](`crate::binary_benchmark_group`) only: Create a new benchmark id
 #[derive(Debug, Clone, PartialEq, Eq, Hash)]
@@ -1116,6 +1116,56 @@ impl BinaryBenchmarkConfig {
         self
     }
 
+    /// Pass arguments to valgrind's cachegrind
+    ///
+    /// The arguments are only applied if [`ValgrindTool::Cachegrind`] is the default tool (see
+    /// [`BinaryBenchmarkConfig::default_tool`]). It's not needed to pass the arguments with flags.
+    ///
+    /// See also [Cachegrind Command-line
+    /// Options](https://valgrind.org/docs/manual/cg-manual.html#cg-manual.cgopts) for a full
+    /// overview of possible arguments.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use iai_callgrind::{BinaryBenchmarkConfig, ValgrindTool};
+    ///
+    /// BinaryBenchmarkConfig::default()
+    ///     .default_tool(ValgrindTool::Cachegrind)
+    ///     .cachegrind_args(["cache-sim=yes", "branch-sim=yes"]);
+    /// ```
+    pub fn cachegrind_args<I, T>(&mut self, args: T) -> &mut Self
+    where
+        I: AsRef<str>,
+        T: IntoIterator<Item = I>,
+    {
+        self.0.cachegrind_args.extend_ignore_flag(args);
+        self
+    }
+
+    /// Change the default tool from callgrind to cachegrind or vice versa
+    ///
+    /// The default tool runs first and its metrics are used for the baselines and the performance
+    /// regression checks (see [`BinaryBenchmarkConfig::regression`]). Only
+    /// [`ValgrindTool::Callgrind`] and [`ValgrindTool::Cachegrind`] can be the default tool. The
+    /// default tool can also be set with the command-line argument `--default-tool` or the
+    /// environment variable `IAI_CALLGRIND_DEFAULT_TOOL`, which take precedence over this setting.
+    ///
+    /// Cachegrind is faster than callgrind but doesn't support the `--toggle-collect` option, so
+    /// the entry point is ignored. Flamegraphs are only created if callgrind is the default tool.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use iai_callgrind::{BinaryBenchmarkConfig, ValgrindTool};
+    ///
+    /// BinaryBenchmarkConfig::default().default_tool(ValgrindTool::Cachegrind);
+    /// ```
+    pub fn default_tool(&mut self, tool: ValgrindTool) -> &mut Self {
+        self.0.default_tool = Some(tool);
+        self
+    }
+
     /// Pass valgrind arguments to all tools
     ///
     /// Only core [valgrind
@@ -3,7 +3,9 @@
 use derive_more::AsRef;
 use iai_callgrind_macros::IntoInner;
 
-use super::{internal, Direction, EventKind, FlamegraphKind, ValgrindTool};
+use super::{
+    internal, DhatMetricKind, Direction, ErrorMetricKind, EventKind, FlamegraphKind, ValgrindTool,
+};
 
 /// The `FlamegraphConfig` which allows the customization of the created flamegraphs
 ///
@@ -319,6 +321,77 @@ impl OutputFormat {
         self.0.show_grid = Some(value);
         self
     }
+
+    /// Show the top `value` allocation sites of a DHAT run (Default: 0)
+    ///
+    /// Setting this option to a value greater than `0` parses the json output file of
+    /// [`crate::ValgrindTool::DHAT`] in addition to the log file. The allocation sites (program
+    /// points) are compared with the allocation sites of the old or base run which have the same
+    /// frames and sorted by the absolute difference in the total bytes. Without a baseline, the
+    /// allocation sites are sorted by their total bytes. The allocation sites are shown below the
+    /// total of the DHAT metrics and are recorded in the `summary.json` if `--save-summary` is
+    /// given.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use iai_callgrind::{main, LibraryBenchmarkConfig, OutputFormat, Tool, ValgrindTool};
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(
+    /// #    name = some_group;
+    /// #    benchmarks = some_func
+    /// # );
+    /// # fn main() {
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///         .tool(Tool::new(ValgrindTool::DHAT))
+    ///         .output_format(OutputFormat::default().show_allocation_sites(5));
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    pub fn show_allocation_sites(&mut self, value: usize) -> &mut Self {
+        self.0.show_allocation_sites = Some(value);
+        self
+    }
+
+    /// Show the top `value` functions of the callgrind run sorted by the [`EventKind`] (Default: 0)
+    ///
+    /// Setting `value` to a value greater than `0` parses the per-function metrics of the
+    /// callgrind output files. The functions are compared with the functions of the old or base
+    /// run which have the same name, source file and object file and are sorted by the absolute
+    /// difference in the exclusive (self) metric of the `event_kind`. Without a baseline, the
+    /// functions are sorted by their exclusive metric. The functions are shown with their
+    /// exclusive and inclusive metric of the `event_kind` below the total of the callgrind metrics
+    /// and are recorded with all metrics in the `summary.json` if `--save-summary` is given.
+    ///
+    /// This option has no effect if [`crate::ValgrindTool::Cachegrind`] is the default tool.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use iai_callgrind::{main, EventKind, LibraryBenchmarkConfig, OutputFormat};
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(
+    /// #    name = some_group;
+    /// #    benchmarks = some_func
+    /// # );
+    /// # fn main() {
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default()
+    ///         .output_format(OutputFormat::default().show_functions(10, EventKind::Ir));
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    pub fn show_functions(&mut self, value: usize, event_kind: EventKind) -> &mut Self {
+        self.0.show_functions = Some((value, event_kind));
+        self
+    }
 }
 
 /// Configure performance regression checks and behavior
@@ -605,6 +678,129 @@ impl RegressionConfig {
         self
     }
 
+    /// Configure hard limits (the maximum value) of [`EventKind`]s
+    ///
+    /// A performance regression is assumed if the new value of the [`EventKind`] is greater than
+    /// the hard limit. Other than the percentage [`RegressionConfig::limits`], the hard limits
+    /// are checked even if there is no baseline to compare with, so they can be used to keep a
+    /// benchmark under a fixed budget.
+    ///
+    /// If only hard limits are specified, the default limit of [`EventKind::Ir`] is not applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::{EventKind, RegressionConfig};
+    ///
+    /// let config = RegressionConfig::default().hard_limits([(EventKind::Ir, 1_500_000)]);
+    /// ```
+    pub fn hard_limits<T>(&mut self, targets: T) -> &mut Self
+    where
+        T: IntoIterator<Item = (EventKind, u64)>,
+    {
+        self.0.hard_limits.extend(targets);
+        self
+    }
+
+    /// Configure the limits of the absolute difference between the new and old value of
+    /// [`EventKind`]s
+    ///
+    /// If the limit is positive, a performance regression is assumed if the value grew by more
+    /// than the limit. If the limit is negative, a performance regression is assumed if the value
+    /// shrank by more than the limit. Like the percentage [`RegressionConfig::limits`], the
+    /// absolute limits are only checked if there is a baseline to compare with.
+    ///
+    /// If only absolute limits are specified, the default limit of [`EventKind::Ir`] is not
+    /// applied.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::{EventKind, RegressionConfig};
+    ///
+    /// let config =
+    ///     RegressionConfig::default().absolute_limits([(EventKind::EstimatedCycles, 10_000)]);
+    /// ```
+    pub fn absolute_limits<T>(&mut self, targets: T) -> &mut Self
+    where
+        T: IntoIterator<Item = (EventKind, i64)>,
+    {
+        self.0.absolute_limits.extend(targets);
+        self
+    }
+
+    /// Configure the limits percentages over/below which a performance regression of a
+    /// [`DhatMetricKind`] can be assumed
+    ///
+    /// The dhat limits are checked against the total metrics of a [`ValgrindTool::DHAT`] run, so
+    /// `DHAT` needs to be enabled with [`Tool`] for these limits to have any effect. Like in
+    /// [`RegressionConfig::limits`], a positive percentage is an upper limit and a negative
+    /// percentage is a lower limit.
+    ///
+    /// If only dhat `limits` are specified, there are no default limits for callgrind's
+    /// [`EventKind`]s.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::{DhatMetricKind, RegressionConfig};
+    ///
+    /// let config = RegressionConfig::default().dhat_limits([
+    ///     (DhatMetricKind::TotalBytes, 5f64),
+    ///     (DhatMetricKind::AtTGmaxBytes, 10f64),
+    /// ]);
+    /// ```
+    pub fn dhat_limits<T>(&mut self, targets: T) -> &mut Self
+    where
+        T: IntoIterator<Item = (DhatMetricKind, f64)>,
+    {
+        self.0.dhat_limits.extend(targets);
+        self
+    }
+
+    /// Configure absolute error budgets for the tools which report errors
+    ///
+    /// The error budgets are checked against the total error metrics of a
+    /// [`ValgrindTool::Memcheck`], [`ValgrindTool::Helgrind`] or [`ValgrindTool::DRD`] run, so at
+    /// least one of these tools needs to be enabled with [`Tool`]. A regression is assumed if the
+    /// new value of an [`ErrorMetricKind`] is greater than the budget. Unlike the other limits,
+    /// the budgets are checked even if there is no baseline to compare with.
+    ///
+    /// # Examples
+    ///
+    /// Fail if memcheck reports any errors:
+    ///
+    /// ```
+    /// use iai_callgrind::{ErrorMetricKind, RegressionConfig};
+    ///
+    /// let config = RegressionConfig::default().error_limits([(ErrorMetricKind::Errors, 0)]);
+    /// ```
+    pub fn error_limits<T>(&mut self, targets: T) -> &mut Self
+    where
+        T: IntoIterator<Item = (ErrorMetricKind, u64)>,
+    {
+        self.0.error_limits.extend(targets);
+        self
+    }
+
+    /// If set to true, an increase of the errors compared to the baseline is a regression
+    ///
+    /// This applies to [`ErrorMetricKind::Errors`] and [`ErrorMetricKind::Contexts`] of the tools
+    /// which report errors ([`ValgrindTool::Memcheck`], [`ValgrindTool::Helgrind`] and
+    /// [`ValgrindTool::DRD`]). The default is `false`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::RegressionConfig;
+    ///
+    /// let config = RegressionConfig::default().fail_on_error_increase(true);
+    /// ```
+    pub fn fail_on_error_increase(&mut self, value: bool) -> &mut Self {
+        self.0.fail_on_error_increase = Some(value);
+        self
+    }
+
     /// If set to true, then the benchmarks fail on the first encountered regression
     ///
     /// The default is `false` and the whole benchmark run fails with a regression error after all
@@ -639,6 +835,8 @@ impl Tool {
             enable: Option::default(),
             show_log: Option::default(),
             raw_args: internal::InternalRawArgs::default(),
+            gen_suppressions: Option::default(),
+            xml: Option::default(),
         })
     }
 
@@ -673,6 +871,47 @@ impl Tool {
         self.0.raw_args.extend_ignore_flag(args);
         self
     }
+
+    /// If true, generate a suppression file from the errors of this `Tool` (Default: false)
+    ///
+    /// Only `ValgrindTool::Memcheck`, `ValgrindTool::Helgrind` and `ValgrindTool::DRD` support
+    /// suppressions. The tool is run with `--gen-suppressions=all` and the generated suppressions
+    /// are deduplicated and written to the file `<tool>.<name>.supp` next to the log files of the
+    /// benchmark in the `target/iai` directory. Each suppression gets a unique name, so the file is
+    /// ready to be used in later runs with `--suppressions=<file>`.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::{Tool, ValgrindTool};
+    ///
+    /// let tool = Tool::new(ValgrindTool::Memcheck).gen_suppressions(true);
+    /// ```
+    pub fn gen_suppressions(&mut self, value: bool) -> &mut Self {
+        self.0.gen_suppressions = Some(value);
+        self
+    }
+
+    /// If true, write the errors of this `Tool` as xml and list each distinct error (Default:
+    /// false)
+    ///
+    /// Only `ValgrindTool::Memcheck`, `ValgrindTool::Helgrind` and `ValgrindTool::DRD` support the
+    /// xml output. The tool is run with `--xml=yes` and the xml output file is parsed into the
+    /// distinct errors with their kind, description and stack. The errors are shown compared to the
+    /// old or base run and are part of the summary json file. Note that with `--xml=yes`, valgrind
+    /// writes the details of the errors only to the xml output and not to the log file anymore.
+    ///
+    /// # Examples
+    ///
+    /// ```
+    /// use iai_callgrind::{Tool, ValgrindTool};
+    ///
+    /// let tool = Tool::new(ValgrindTool::Memcheck).xml(true);
+    /// ```
+    pub fn xml(&mut self, value: bool) -> &mut Self {
+        self.0.xml = Some(value);
+        self
+    }
 }
 
 /// __DEPRECATED__: A function that is opaque to the optimizer
@@ -3,7 +3,7 @@ use std::ffi::OsString;
 use derive_more::AsRef;
 use iai_callgrind_macros::IntoInner;
 
-use crate::{internal, EntryPoint};
+use crate::{internal, EntryPoint, ValgrindTool};
 
 /// The main configuration of a library benchmark.
 ///
@@ -58,7 +58,9 @@ impl LibraryBenchmarkConfig {
     {
         Self(internal::InternalLibraryBenchmarkConfig {
             env_clear: Option::default(),
+            default_tool: Option::default(),
             callgrind_args: internal::InternalRawArgs::from_iter(args),
+            cachegrind_args: internal::InternalRawArgs::default(),
             valgrind_args: internal::InternalRawArgs::default(),
             envs: Vec::default(),
             flamegraph_config: Option::default(),
@@ -67,6 +69,7 @@ impl LibraryBenchmarkConfig {
             tools_override: Option::default(),
             output_format: Option::default(),
             entry_point: Option::default(),
+            sweep: Option::default(),
         })
     }
 
@@ -235,6 +238,78 @@ impl LibraryBenchmarkConfig {
         self
     }
 
+    /// Add cachegrind arguments to this `LibraryBenchmarkConfig`
+    ///
+    /// The arguments are only applied if [`ValgrindTool::Cachegrind`] is the default tool, which is
+    /// not supported for library benchmarks yet (see [`LibraryBenchmarkConfig::default_tool`]).
+    /// Like with
+    /// [`LibraryBenchmarkConfig::callgrind_args`], the arguments don't need to start with a flag.
+    /// In contrast to callgrind, no arguments except for the valgrind core arguments are applied by
+    /// default.
+    ///
+    /// See also [Cachegrind Command-line
+    /// Options](https://valgrind.org/docs/manual/cg-manual.html#cg-manual.cgopts)
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+    /// # fn main() {
+    /// use iai_callgrind::{LibraryBenchmarkConfig, main};
+    ///
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default().cachegrind_args(["cache-sim=yes"]);
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    pub fn cachegrind_args<I, T>(&mut self, args: T) -> &mut Self
+    where
+        I: AsRef<str>,
+        T: IntoIterator<Item = I>,
+    {
+        self.0.cachegrind_args.extend_ignore_flag(args);
+        self
+    }
+
+    /// Set the default tool of the library benchmarks
+    ///
+    /// The default tool runs first and its metrics are used for the baselines and the performance
+    /// regression checks (see [`LibraryBenchmarkConfig::regression`]). The default tool can also
+    /// be set with the command-line argument `--default-tool` or the environment variable
+    /// `IAI_CALLGRIND_DEFAULT_TOOL`, which take precedence over this setting.
+    ///
+    /// Currently, only [`ValgrindTool::Callgrind`] can be the default tool of library benchmarks.
+    /// Cachegrind doesn't support the `--toggle-collect` option, so it can't start the measurement
+    /// at the [`EntryPoint`] and would measure the whole benchmark harness. Therefore,
+    /// [`ValgrindTool::Cachegrind`] as default tool of a library benchmark is an error. Use
+    /// cachegrind as default tool of [binary
+    /// benchmarks](crate::BinaryBenchmarkConfig::default_tool) instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use iai_callgrind::{library_benchmark, library_benchmark_group};
+    /// # #[library_benchmark]
+    /// # fn some_func() {}
+    /// # library_benchmark_group!(name = some_group; benchmarks = some_func);
+    /// # fn main() {
+    /// use iai_callgrind::{LibraryBenchmarkConfig, main, ValgrindTool};
+    ///
+    /// main!(
+    ///     config = LibraryBenchmarkConfig::default().default_tool(ValgrindTool::Callgrind);
+    ///     library_benchmark_groups = some_group
+    /// );
+    /// # }
+    /// ```
+    pub fn default_tool(&mut self, tool: ValgrindTool) -> &mut Self {
+        self.0.default_tool = Some(tool);
+        self
+    }
+
     /// Pass valgrind arguments to all tools
     ///
     /// Only core [valgrind
@@ -794,4 +869,47 @@ impl LibraryBenchmarkConfig {
         self.0.output_format = Some(output_format.into());
         self
     }
+
+    /// Declare the benches of a benchmark function as a parameter sweep
+    ///
+    /// If `true`, the benches of the benchmark function are not treated as unrelated benchmarks
+    /// but as measurements of the same function with a different parameter, for example the size
+    /// of the input. The parameter of each bench is the single number literal in its arguments.
+    /// After all benches of the benchmark function have run, Iai-Callgrind reports the first
+    /// metric (usually `Instructions`) per parameter and fits a complexity estimate (`O(1)`,
+    /// `O(n)`, `O(n log n)` or `O(n^2)`). If the fitted growth class differs from the growth class
+    /// of the baseline, the change is flagged in the output. If performance regressions are
+    /// checked, an increase of the growth class is a regression.
+    ///
+    /// A sweep needs at least 3 different parameters. Benches without a number or more than one
+    /// number in their arguments are not part of the sweep.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::hint::black_box;
+    ///
+    /// use iai_callgrind::{library_benchmark, LibraryBenchmarkConfig};
+    /// # use iai_callgrind::{library_benchmark_group, main};
+    ///
+    /// fn setup_worst_case(size: usize) -> Vec<usize> {
+    ///     (0..size).rev().collect()
+    /// }
+    ///
+    /// #[library_benchmark(config = LibraryBenchmarkConfig::default().sweep(true))]
+    /// #[benches::size(args = [10, 100, 1000], setup = setup_worst_case)]
+    /// fn bench_sort(mut input: Vec<usize>) -> Vec<usize> {
+    ///     input.sort_unstable();
+    ///     black_box(input)
+    /// }
+    ///
+    /// # library_benchmark_group!(name = some_group; benchmarks = bench_sort);
+    /// # fn main() {
+    /// # main!(library_benchmark_groups = some_group);
+    /// # }
+    /// ```
+    pub fn sweep(&mut self, value: bool) -> &mut Self {
+        self.0.sweep = Some(value);
+        self
+    }
 }
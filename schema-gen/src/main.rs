@@ -1,10 +1,10 @@
-//! A utility binary to create the json schema for the summary.json file
+//! A utility binary to create the json schemas for the summary.json and run-summary.json files
 //!
 //! This binary is not considered a part of the published `iai-callgrind-runner` package and is only
 //! used during the development of `iai-callgrind`.
 use std::fs::File;
 
-use iai_callgrind_runner::runner::summary::BenchmarkSummary;
+use iai_callgrind_runner::runner::summary::{BenchmarkSummary, RunSummary};
 use schemars::schema_for;
 
 fn main() {
@@ -13,4 +13,9 @@ fn main() {
         &schema_for!(BenchmarkSummary),
     )
     .expect("Schema creation should be successful");
+    serde_json::to_writer_pretty(
+        File::create("run-summary.schema.json").unwrap(),
+        &schema_for!(RunSummary),
+    )
+    .expect("Schema creation should be successful");
 }